@@ -2,8 +2,10 @@ import { defineConfig } from 'drizzle-kit';
 
 if (!process.env.DATABASE_URL) throw new Error('DATABASE_URL is not set');
 
+// Introspection and studio only: the Rust migrations own the schema, see schema.ts
 export default defineConfig({
 	schema: './src/lib/server/db/schema.ts',
+	out: './drizzle',
 	dialect: 'postgresql',
 	dbCredentials: { url: process.env.DATABASE_URL },
 	verbose: true,
@@ -9,14 +9,27 @@ import {
 	type movementTypeEnum,
 	type positionStatusEnum
 } from './schema';
-import { eq, and, desc } from 'drizzle-orm';
+import { eq, and, desc, inArray } from 'drizzle-orm';
 import { alias } from 'drizzle-orm/pg-core';
+import { AccountAddress } from '@aptos-labs/ts-sdk';
 
 /**
- * Get user by wallet address
+ * Long form (0x and 64 lowercase hex digits) of a wallet address, as stored in users.address
+ * and looked up by the Rust API
+ */
+export function normalizeAddress(address: string): string {
+	return AccountAddress.from(address).toStringLong();
+}
+
+/**
+ * Get user by wallet address, also matching rows stored before addresses were normalized
  */
 export async function getUserByAddress(address: string) {
-	const users = await db.select().from(usersTable).where(eq(usersTable.address, address)).limit(1);
+	const users = await db
+		.select()
+		.from(usersTable)
+		.where(inArray(usersTable.address, [normalizeAddress(address), address]))
+		.limit(1);
 	return users[0] || null;
 }
 
@@ -27,7 +40,7 @@ export async function createUser(address: string, index: number) {
 	const [user] = await db
 		.insert(usersTable)
 		.values({
-			address,
+			address: normalizeAddress(address),
 			index
 		})
 		.returning();
@@ -2,16 +2,24 @@ import {
 	bigint,
 	decimal,
 	doublePrecision,
+	index,
 	integer,
+	jsonb,
 	pgEnum,
 	pgTable,
 	primaryKey,
 	serial,
 	text,
 	timestamp,
+	uniqueIndex,
 	varchar
 } from 'drizzle-orm/pg-core';
 
+// Typed view of the tables for queries only. The Rust migrations (rust/crates/migration) own
+// the schema and run on API startup: change them first, then mirror the change here. Never
+// push or migrate this file with drizzle-kit, `pnpm db:pull` introspects a migrated database
+// into ./drizzle to compare against.
+
 // TODO: use singular name for tables, it's cleaner really.
 
 export const tokensTable = pgTable('tokens', {
@@ -56,9 +64,45 @@ export const positionsTable = pgTable(
 		updatedAt: timestamp('updated_at').defaultNow(),
 		tickLower: bigint('tick_lower', { mode: 'number' }).notNull(),
 		tickUpper: bigint('tick_upper', { mode: 'number' }).notNull(),
-		liquidity: varchar().notNull()
+		liquidity: varchar().notNull(),
+		// Object address of Hyperion positions, NULL for numbered ones
+		address: varchar({ length: 66 })
+	},
+	(table) => [
+		primaryKey({ columns: [table.index, table.pool] }),
+		uniqueIndex('positions_pool_address_idx').on(table.pool, table.address)
+	]
+);
+
+// Appended on every pool scrape, pools only keep the latest values
+export const poolSnapshotsTable = pgTable(
+	'pool_snapshots',
+	{
+		id: serial().primaryKey(),
+		poolId: varchar('pool_id')
+			.references(() => poolsTable.id)
+			.notNull(),
+		tvl: doublePrecision('tvl').notNull(),
+		volumeDay: doublePrecision('volume_day').notNull(),
+		tradingAPR: doublePrecision('trading_apr').notNull(),
+		bonusAPR: doublePrecision('bonus_apr').notNull(),
+		createdAt: timestamp('created_at').notNull().defaultNow()
+	},
+	(table) => [index('pool_snapshots_pool_id_created_at_idx').on(table.poolId, table.createdAt)]
+);
+
+// Price chart points, one series per pool and interval ('1h', '4h', '1d')
+export const poolPricesTable = pgTable(
+	'pool_prices',
+	{
+		poolId: varchar('pool_id')
+			.references(() => poolsTable.id)
+			.notNull(),
+		interval: varchar().notNull(),
+		timestamp: timestamp().notNull(),
+		price: doublePrecision().notNull()
 	},
-	(table) => [primaryKey({ columns: [table.index, table.pool] })]
+	(table) => [primaryKey({ columns: [table.poolId, table.interval, table.timestamp] })]
 );
 
 // chain IDs can be found at https://chainlist.org/
@@ -73,3 +117,110 @@ export const protocolsTable = pgTable('protocols', {
 	name: varchar().notNull(),
 	url: varchar()
 });
+
+export const movementTypeEnum = pgEnum('movement_type', ['deposit', 'withdraw', 'rebalance']);
+export const positionStatusEnum = pgEnum('position_status', ['active', 'closed']);
+
+export const usersTable = pgTable('users', {
+	id: serial().primaryKey(),
+	index: integer().notNull().unique(),
+	address: varchar().notNull().unique(), // long form, 0x and 64 hex digits
+	createdAt: timestamp('created_at').notNull().defaultNow()
+});
+
+export const managedPositionsTable = pgTable(
+	'managed_positions',
+	{
+		id: serial().primaryKey(),
+		userId: integer('user_id')
+			.references(() => usersTable.id)
+			.notNull(),
+		poolId: varchar('pool_id')
+			.references(() => poolsTable.id)
+			.notNull(),
+		positionId: varchar('position_id').notNull(),
+		tickLower: bigint('tick_lower', { mode: 'number' }).notNull(),
+		tickUpper: bigint('tick_upper', { mode: 'number' }).notNull(),
+		liquidity: varchar().notNull(),
+		status: positionStatusEnum().notNull().default('active'),
+		createdAt: timestamp('created_at').notNull().defaultNow(),
+		updatedAt: timestamp('updated_at').notNull().defaultNow(),
+		// Account holding the position on-chain, vaults only count the ones they hold
+		owner: varchar({ length: 66 })
+	},
+	(table) => [index('managed_positions_pool_owner_idx').on(table.poolId, table.owner)]
+);
+
+export const userBalancesTable = pgTable(
+	'user_balances',
+	{
+		userId: integer('user_id')
+			.references(() => usersTable.id)
+			.notNull(),
+		tokenId: varchar('token_id')
+			.references(() => tokensTable.id)
+			.notNull(),
+		amount: varchar().notNull(),
+		updatedAt: timestamp('updated_at').notNull().defaultNow()
+	},
+	(table) => [primaryKey({ columns: [table.userId, table.tokenId] })]
+);
+
+export const userMovementsTable = pgTable('user_movements', {
+	id: serial().primaryKey(),
+	userId: integer('user_id')
+		.references(() => usersTable.id)
+		.notNull(),
+	txHash: varchar('tx_hash').notNull(),
+	txInfo: text('tx_info'),
+	movementType: movementTypeEnum('movement_type').notNull(),
+	createdAt: timestamp('created_at').notNull().defaultNow()
+});
+
+export const jobKindEnum = pgEnum('job_kind', [
+	'refresh_pools',
+	'refresh_pool',
+	'refresh_positions',
+	'refresh_tokens'
+]);
+export const jobStateEnum = pgEnum('job_state', ['queued', 'running', 'succeeded', 'dead']);
+
+// Queue of the Rust API workers
+export const jobsTable = pgTable(
+	'jobs',
+	{
+		id: serial().primaryKey(),
+		kind: jobKindEnum().notNull(),
+		target: varchar(),
+		state: jobStateEnum().notNull().default('queued'),
+		attempts: integer().notNull().default(0),
+		maxAttempts: integer('max_attempts').notNull(),
+		lastError: text('last_error'),
+		result: jsonb(),
+		runAt: timestamp('run_at').notNull().defaultNow(),
+		lockedAt: timestamp('locked_at'),
+		finishedAt: timestamp('finished_at'),
+		createdAt: timestamp('created_at').notNull().defaultNow(),
+		updatedAt: timestamp('updated_at').notNull().defaultNow()
+	},
+	(table) => [index('jobs_state_run_at_idx').on(table.state, table.runAt)]
+);
+
+// Vault shares per user and pool, raw amounts as decimal strings
+export const vaultSharesTable = pgTable(
+	'vault_shares',
+	{
+		userId: integer('user_id')
+			.references(() => usersTable.id)
+			.notNull(),
+		poolId: varchar('pool_id')
+			.references(() => poolsTable.id)
+			.notNull(),
+		shares: varchar().notNull(),
+		updatedAt: timestamp('updated_at').notNull().defaultNow()
+	},
+	(table) => [
+		primaryKey({ columns: [table.userId, table.poolId] }),
+		index('vault_shares_pool_id_idx').on(table.poolId)
+	]
+);
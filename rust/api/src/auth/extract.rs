@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use chrono::Utc;
+use liquidity_core::address::AccountAddress;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+};
+
+/// Caller holding a valid session, from an `Authorization: Bearer <token>` header
+#[derive(Debug, Clone)]
+pub struct SignedIn {
+    pub address: AccountAddress,
+    pub token: String,
+}
+
+impl SignedIn {
+    /// Only let the caller act on their own address
+    pub fn require(&self, address: &str) -> AppResult<()> {
+        let address: AccountAddress = address
+            .parse()
+            .map_err(|_| AppError::BadRequest(format!("Invalid address {}", address)))?;
+
+        if address != self.address {
+            return Err(AppError::Forbidden(
+                "Session belongs to another address".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for SignedIn {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| AppError::Unauthorized("Missing bearer token".to_string()))?;
+
+        let session = state
+            .auth
+            .session(token, Utc::now().timestamp())
+            .ok_or_else(|| AppError::Unauthorized("Invalid or expired session".to_string()))?;
+
+        Ok(SignedIn {
+            address: session.address,
+            token: token.to_string(),
+        })
+    }
+}
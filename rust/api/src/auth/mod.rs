@@ -0,0 +1,448 @@
+//! Wallet signature authentication.
+//!
+//! A client asks for a [`siwa::Challenge`] for its address, signs the challenge message with
+//! its wallet and trades the signature for a session token. Handlers take an
+//! [`extract::SignedIn`] to require that token.
+//!
+//! Challenges aren't stored: their nonce carries the issue time and a tag over it and the
+//! address, keyed by a secret drawn at startup, so issuing one costs no memory and nobody can
+//! exhaust them for others. Nonces are remembered once used, until they expire, so each signs
+//! in at most once. Sessions live in memory, a restart signs everyone out and invalidates the
+//! pending challenges. Expired entries are swept by [`Auth::spawn_pruner`].
+
+pub mod extract;
+pub mod siwa;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::Utc;
+use liquidity_core::address::AccountAddress;
+use sha3::{Digest, Sha3_256};
+use tokio::{sync::watch, task::JoinHandle};
+
+use siwa::{AuthError, Challenge, authentication_key};
+
+/// Seconds a challenge can be signed for
+const CHALLENGE_TTL: i64 = 5 * 60;
+/// Seconds a session lasts
+const SESSION_TTL: i64 = 60 * 60;
+/// Time between two sweeps of the used nonces and expired sessions
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+const TOKEN_BYTES: usize = 32;
+const KEY_BYTES: usize = 32;
+/// A nonce is a random salt, the big endian issue time and the tag, hex encoded
+const SALT_BYTES: usize = 16;
+const TAG_BYTES: usize = 16;
+const NONCE_BYTES: usize = SALT_BYTES + 8 + TAG_BYTES;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub address: AccountAddress,
+    /// Unix seconds
+    pub expires_at: i64,
+}
+
+#[derive(Default)]
+struct Store {
+    /// Nonces already presented, with the expiry of their challenge
+    spent: HashMap<String, i64>,
+    /// Keyed by token
+    sessions: HashMap<String, Session>,
+}
+
+impl Store {
+    fn prune(&mut self, now: i64) {
+        self.spent.retain(|_, expires_at| *expires_at > now);
+        self.sessions.retain(|_, s| s.expires_at > now);
+    }
+}
+
+#[derive(Clone)]
+pub struct Auth {
+    /// Domain named in the challenge message, so a signature can't be replayed elsewhere
+    domain: String,
+    /// Keys the nonce tags
+    key: Arc<[u8; KEY_BYTES]>,
+    store: Arc<Mutex<Store>>,
+}
+
+impl Auth {
+    pub fn new(domain: impl Into<String>) -> Self {
+        let mut key = [0u8; KEY_BYTES];
+        fill_random(&mut key);
+
+        Self {
+            domain: domain.into(),
+            key: Arc::new(key),
+            store: Arc::default(),
+        }
+    }
+
+    /// Issue a single use challenge for `address`
+    pub fn challenge(&self, address: AccountAddress, now: i64) -> Challenge {
+        let mut salt = [0u8; SALT_BYTES];
+        fill_random(&mut salt);
+
+        Challenge::new(
+            &self.domain,
+            address,
+            self.nonce(&address, &salt, now),
+            now,
+            now + CHALLENGE_TTL,
+        )
+    }
+
+    /// Trade a signed challenge for a session token
+    ///
+    /// `signed_message` is the full message a wallet signed around the challenge, the
+    /// challenge message itself when `None`. A nonce issued to the key's account is used up
+    /// whether the signature is valid or not.
+    pub fn sign_in(
+        &self,
+        nonce: &str,
+        public_key: &[u8],
+        signature: &[u8],
+        signed_message: Option<&str>,
+        now: i64,
+    ) -> Result<(String, Session), AuthError> {
+        let (salt, issued_at) = parse_nonce(nonce).ok_or(AuthError::UnknownNonce)?;
+        let public_key: [u8; 32] = public_key
+            .try_into()
+            .map_err(|_| AuthError::InvalidPublicKey)?;
+        // The tag covers the address, so a nonce only verifies for the account it was issued to
+        let address = authentication_key(&public_key);
+        if !constant_time_eq(nonce, &self.nonce(&address, &salt, issued_at)) {
+            return Err(AuthError::UnknownNonce);
+        }
+        let challenge = Challenge::new(
+            &self.domain,
+            address,
+            nonce.to_string(),
+            issued_at,
+            issued_at + CHALLENGE_TTL,
+        );
+
+        let mut store = self.store.lock().expect("auth store poisoned");
+        if store.spent.contains_key(nonce) {
+            return Err(AuthError::UnknownNonce);
+        }
+        store.spent.insert(nonce.to_string(), challenge.expires_at);
+
+        let signed_message = signed_message.unwrap_or(&challenge.message);
+        challenge.verify(&public_key, signature, signed_message, now)?;
+
+        let token = random_hex(TOKEN_BYTES);
+        let session = Session {
+            address: challenge.address,
+            expires_at: now + SESSION_TTL,
+        };
+        store.sessions.insert(token.clone(), session.clone());
+
+        Ok((token, session))
+    }
+
+    /// Session of a token, unless it expired or was signed out
+    pub fn session(&self, token: &str, now: i64) -> Option<Session> {
+        let store = self.store.lock().expect("auth store poisoned");
+        store
+            .sessions
+            .get(token)
+            .filter(|s| s.expires_at > now)
+            .cloned()
+    }
+
+    pub fn sign_out(&self, token: &str) {
+        self.store
+            .lock()
+            .expect("auth store poisoned")
+            .sessions
+            .remove(token);
+    }
+
+    /// Drop the used nonces of expired challenges, and the expired sessions
+    pub fn prune(&self, now: i64) {
+        self.store.lock().expect("auth store poisoned").prune(now);
+    }
+
+    /// Prune every [`PRUNE_INTERVAL`] until `shutdown` is set, so used nonces and sessions
+    /// nobody uses again don't pile up
+    pub fn spawn_pruner(&self, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        let auth = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(PRUNE_INTERVAL) => {}
+                    _ = shutdown.wait_for(|stop| *stop) => return,
+                }
+                auth.prune(Utc::now().timestamp());
+            }
+        })
+    }
+
+    /// Hex encoded nonce of a challenge for `address` issued at `issued_at`
+    ///
+    /// SHA3 isn't open to length extension, so hashing the key ahead of the data is a MAC.
+    fn nonce(&self, address: &AccountAddress, salt: &[u8; SALT_BYTES], issued_at: i64) -> String {
+        let mut hasher = Sha3_256::new();
+        hasher.update(self.key.as_slice());
+        hasher.update(address.0);
+        hasher.update(salt);
+        hasher.update(issued_at.to_be_bytes());
+        let tag = hasher.finalize();
+
+        let mut nonce = Vec::with_capacity(NONCE_BYTES);
+        nonce.extend(salt);
+        nonce.extend(issued_at.to_be_bytes());
+        nonce.extend(&tag[..TAG_BYTES]);
+        hex::encode(nonce)
+    }
+}
+
+/// Salt and issue time of a nonce, its tag is checked by rebuilding it
+fn parse_nonce(nonce: &str) -> Option<([u8; SALT_BYTES], i64)> {
+    let bytes = hex::decode(nonce).ok()?;
+    if bytes.len() != NONCE_BYTES {
+        return None;
+    }
+
+    let salt = bytes[..SALT_BYTES].try_into().ok()?;
+    let issued_at = i64::from_be_bytes(bytes[SALT_BYTES..SALT_BYTES + 8].try_into().ok()?);
+    Some((salt, issued_at))
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+fn fill_random(bytes: &mut [u8]) {
+    getrandom::getrandom(bytes).expect("system randomness is available");
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    fill_random(&mut bytes);
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn signed_in(auth: &Auth, key: &SigningKey) -> Result<(String, Session), AuthError> {
+        let address = authentication_key(key.verifying_key().as_bytes());
+        let challenge = auth.challenge(address, NOW);
+        let signature = key.sign(challenge.message.as_bytes());
+
+        auth.sign_in(
+            &challenge.nonce,
+            key.verifying_key().as_bytes(),
+            &signature.to_bytes(),
+            None,
+            NOW + 1,
+        )
+    }
+
+    #[test]
+    fn signed_challenge_opens_a_session() {
+        let auth = Auth::new("example.com");
+        let key = SigningKey::from_bytes(&[7; 32]);
+
+        let (token, session) = signed_in(&auth, &key).unwrap();
+        assert_eq!(token.len(), TOKEN_BYTES * 2);
+        assert_eq!(
+            session.address,
+            authentication_key(key.verifying_key().as_bytes())
+        );
+        assert_eq!(auth.session(&token, NOW + 2), Some(session.clone()));
+
+        assert_eq!(auth.session(&token, session.expires_at), None);
+        assert_eq!(auth.session("not-a-token", NOW + 2), None);
+
+        auth.sign_out(&token);
+        assert_eq!(auth.session(&token, NOW + 2), None);
+    }
+
+    #[test]
+    fn nonces_are_single_use() {
+        let auth = Auth::new("example.com");
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let address = authentication_key(key.verifying_key().as_bytes());
+
+        let challenge = auth.challenge(address, NOW);
+        let signature = key.sign(challenge.message.as_bytes()).to_bytes();
+        let public_key = key.verifying_key().to_bytes();
+
+        assert!(
+            auth.sign_in(
+                &challenge.nonce,
+                &public_key,
+                &signature,
+                Some(&challenge.message),
+                NOW
+            )
+            .is_ok()
+        );
+        assert_eq!(
+            auth.sign_in(
+                &challenge.nonce,
+                &public_key,
+                &signature,
+                Some(&challenge.message),
+                NOW
+            ),
+            Err(AuthError::UnknownNonce)
+        );
+    }
+
+    #[test]
+    fn failed_attempts_burn_the_challenge() {
+        let auth = Auth::new("example.com");
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let address = authentication_key(key.verifying_key().as_bytes());
+
+        let challenge = auth.challenge(address, NOW);
+        let public_key = key.verifying_key().to_bytes();
+        let forged = SigningKey::from_bytes(&[8; 32])
+            .sign(challenge.message.as_bytes())
+            .to_bytes();
+
+        assert_eq!(
+            auth.sign_in(
+                &challenge.nonce,
+                &public_key,
+                &forged,
+                Some(&challenge.message),
+                NOW
+            ),
+            Err(AuthError::InvalidSignature)
+        );
+        let signature = key.sign(challenge.message.as_bytes()).to_bytes();
+        assert_eq!(
+            auth.sign_in(
+                &challenge.nonce,
+                &public_key,
+                &signature,
+                Some(&challenge.message),
+                NOW
+            ),
+            Err(AuthError::UnknownNonce)
+        );
+    }
+
+    #[test]
+    fn challenges_differ_and_expire() {
+        let auth = Auth::new("example.com");
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let address = authentication_key(key.verifying_key().as_bytes());
+
+        let first = auth.challenge(address, NOW);
+        let second = auth.challenge(address, NOW);
+        assert_ne!(first.nonce, second.nonce);
+        assert_eq!(first.nonce.len(), NONCE_BYTES * 2);
+
+        let signature = key.sign(first.message.as_bytes()).to_bytes();
+        assert_eq!(
+            auth.sign_in(
+                &first.nonce,
+                key.verifying_key().as_bytes(),
+                &signature,
+                None,
+                NOW + CHALLENGE_TTL
+            ),
+            Err(AuthError::Expired)
+        );
+    }
+
+    #[test]
+    fn issuing_challenges_stores_nothing() {
+        let auth = Auth::new("example.com");
+        for i in 0..10_000 {
+            let address: AccountAddress = format!("{:#x}", i + 1).parse().unwrap();
+            auth.challenge(address, NOW);
+        }
+        assert!(auth.store.lock().unwrap().spent.is_empty());
+
+        let key = SigningKey::from_bytes(&[7; 32]);
+        assert!(signed_in(&auth, &key).is_ok());
+    }
+
+    #[test]
+    fn nonces_are_bound_to_the_address_and_the_server() {
+        let auth = Auth::new("example.com");
+        let (owner, intruder) = (
+            SigningKey::from_bytes(&[7; 32]),
+            SigningKey::from_bytes(&[8; 32]),
+        );
+        let challenge = auth.challenge(authentication_key(owner.verifying_key().as_bytes()), NOW);
+
+        // Intruder signs the owner's challenge with its own key
+        let signature = intruder.sign(challenge.message.as_bytes()).to_bytes();
+        assert_eq!(
+            auth.sign_in(
+                &challenge.nonce,
+                intruder.verifying_key().as_bytes(),
+                &signature,
+                None,
+                NOW
+            ),
+            Err(AuthError::UnknownNonce)
+        );
+
+        // Nonce issued by another server, or altered
+        let other = Auth::new("example.com").challenge(challenge.address, NOW);
+        let mut altered = challenge.nonce.clone();
+        altered.replace_range(..1, if altered.starts_with('0') { "1" } else { "0" });
+        let signature = owner.sign(challenge.message.as_bytes()).to_bytes();
+        for nonce in [other.nonce.as_str(), altered.as_str(), "abc"] {
+            assert_eq!(
+                auth.sign_in(
+                    nonce,
+                    owner.verifying_key().as_bytes(),
+                    &signature,
+                    None,
+                    NOW
+                ),
+                Err(AuthError::UnknownNonce)
+            );
+        }
+
+        // None of them used up the owner's challenge
+        assert!(
+            auth.sign_in(
+                &challenge.nonce,
+                owner.verifying_key().as_bytes(),
+                &signature,
+                None,
+                NOW
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn pruning_drops_expired_sessions_and_nonces() {
+        let auth = Auth::new("example.com");
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let (token, session) = signed_in(&auth, &key).unwrap();
+
+        auth.prune(NOW + CHALLENGE_TTL);
+        assert!(auth.store.lock().unwrap().spent.is_empty());
+
+        auth.prune(session.expires_at - 1);
+        assert!(auth.store.lock().unwrap().sessions.contains_key(&token));
+        auth.prune(session.expires_at);
+        assert!(auth.store.lock().unwrap().sessions.is_empty());
+    }
+}
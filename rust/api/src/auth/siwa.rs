@@ -0,0 +1,237 @@
+//! Sign-In-With-Aptos style challenges and their verification.
+//!
+//! Everything here is offline: the address is checked against the authentication key
+//! derived from the submitted public key, so accounts that rotated their key can't sign in.
+
+use std::fmt;
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use liquidity_core::address::AccountAddress;
+use sha3::{Digest, Sha3_256};
+
+/// Scheme byte of legacy Ed25519 accounts, SingleKey accounts use another and aren't supported
+const ED25519_SCHEME: u8 = 0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    UnknownNonce,
+    Expired,
+    AddressMismatch,
+    InvalidPublicKey,
+    InvalidSignature,
+    /// The signed message doesn't embed the issued challenge
+    MessageMismatch,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::UnknownNonce => write!(f, "unknown or already used nonce"),
+            AuthError::Expired => write!(f, "challenge expired"),
+            AuthError::AddressMismatch => write!(f, "public key does not match the address"),
+            AuthError::InvalidPublicKey => write!(f, "invalid public key"),
+            AuthError::InvalidSignature => write!(f, "invalid signature"),
+            AuthError::MessageMismatch => {
+                write!(f, "signed message does not contain the challenge")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Message a wallet signs to sign in, valid until `expires_at`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    pub address: AccountAddress,
+    pub nonce: String,
+    pub message: String,
+    /// Unix seconds
+    pub issued_at: i64,
+    pub expires_at: i64,
+}
+
+impl Challenge {
+    pub fn new(
+        domain: &str,
+        address: AccountAddress,
+        nonce: String,
+        issued_at: i64,
+        expires_at: i64,
+    ) -> Self {
+        let message = format!(
+            "{domain} wants you to sign in with your Aptos account:\n\
+             {address}\n\
+             \n\
+             Nonce: {nonce}\n\
+             Issued At: {issued_at}\n\
+             Expiration Time: {expires_at}"
+        );
+
+        Self {
+            address,
+            nonce,
+            message,
+            issued_at,
+            expires_at,
+        }
+    }
+
+    /// Check that `signature` over `signed_message` was made by the key behind the address
+    ///
+    /// Wallets wrap the message they sign (`APTOS\naddress: ...\nmessage: ...`), so the
+    /// signed message may be longer than the challenge as long as it embeds it.
+    pub fn verify(
+        &self,
+        public_key: &[u8],
+        signature: &[u8],
+        signed_message: &str,
+        now: i64,
+    ) -> Result<(), AuthError> {
+        if now >= self.expires_at {
+            return Err(AuthError::Expired);
+        }
+        if !signed_message.contains(&self.message) {
+            return Err(AuthError::MessageMismatch);
+        }
+
+        let public_key: [u8; 32] = public_key
+            .try_into()
+            .map_err(|_| AuthError::InvalidPublicKey)?;
+        if authentication_key(&public_key) != self.address {
+            return Err(AuthError::AddressMismatch);
+        }
+
+        let key = VerifyingKey::from_bytes(&public_key).map_err(|_| AuthError::InvalidPublicKey)?;
+        let signature =
+            Signature::from_slice(signature).map_err(|_| AuthError::InvalidSignature)?;
+        key.verify_strict(signed_message.as_bytes(), &signature)
+            .map_err(|_| AuthError::InvalidSignature)
+    }
+}
+
+/// Authentication key of a legacy Ed25519 account, its address until the key is rotated
+pub fn authentication_key(public_key: &[u8; 32]) -> AccountAddress {
+    let mut hasher = Sha3_256::new();
+    hasher.update(public_key);
+    hasher.update([ED25519_SCHEME]);
+    AccountAddress(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn challenge(key: &SigningKey) -> Challenge {
+        let address = authentication_key(key.verifying_key().as_bytes());
+        Challenge::new("example.com", address, "abc123".into(), NOW, NOW + 300)
+    }
+
+    #[test]
+    fn message_names_the_account_and_nonce() {
+        let challenge = challenge(&key(1));
+        assert!(
+            challenge
+                .message
+                .starts_with("example.com wants you to sign in")
+        );
+        assert!(challenge.message.contains(&challenge.address.to_string()));
+        assert!(challenge.message.contains("Nonce: abc123"));
+    }
+
+    #[test]
+    fn accepts_the_owner_signature() {
+        let key = key(1);
+        let challenge = challenge(&key);
+        let signature = key.sign(challenge.message.as_bytes());
+
+        let public_key = key.verifying_key().to_bytes();
+        assert_eq!(
+            challenge.verify(&public_key, &signature.to_bytes(), &challenge.message, NOW),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn accepts_wallet_wrapped_messages() {
+        let key = key(1);
+        let challenge = challenge(&key);
+        let full_message = format!(
+            "APTOS\naddress: {}\nmessage: {}\nnonce: {}",
+            challenge.address, challenge.message, challenge.nonce
+        );
+        let signature = key.sign(full_message.as_bytes());
+
+        let public_key = key.verifying_key().to_bytes();
+        assert_eq!(
+            challenge.verify(&public_key, &signature.to_bytes(), &full_message, NOW),
+            Ok(())
+        );
+        assert_eq!(
+            challenge.verify(
+                &public_key,
+                &signature.to_bytes(),
+                "APTOS\nmessage: hi",
+                NOW
+            ),
+            Err(AuthError::MessageMismatch)
+        );
+    }
+
+    #[test]
+    fn rejects_another_key() {
+        let (owner, intruder) = (key(1), key(2));
+        let challenge = challenge(&owner);
+        let signature = intruder.sign(challenge.message.as_bytes());
+
+        // Intruder's own key doesn't derive the address
+        assert_eq!(
+            challenge.verify(
+                &intruder.verifying_key().to_bytes(),
+                &signature.to_bytes(),
+                &challenge.message,
+                NOW
+            ),
+            Err(AuthError::AddressMismatch)
+        );
+        // Owner's key with the intruder's signature
+        assert_eq!(
+            challenge.verify(
+                &owner.verifying_key().to_bytes(),
+                &signature.to_bytes(),
+                &challenge.message,
+                NOW
+            ),
+            Err(AuthError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn rejects_expired_and_malformed_input() {
+        let key = key(1);
+        let challenge = challenge(&key);
+        let signature = key.sign(challenge.message.as_bytes()).to_bytes();
+        let public_key = key.verifying_key().to_bytes();
+
+        assert_eq!(
+            challenge.verify(&public_key, &signature, &challenge.message, NOW + 300),
+            Err(AuthError::Expired)
+        );
+        assert_eq!(
+            challenge.verify(&public_key[..31], &signature, &challenge.message, NOW),
+            Err(AuthError::InvalidPublicKey)
+        );
+        assert_eq!(
+            challenge.verify(&public_key, &signature[..63], &challenge.message, NOW),
+            Err(AuthError::InvalidSignature)
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use axum::{
+    Json,
     http::StatusCode,
     response::{IntoResponse, Response},
-    Json,
 };
 use sea_orm::DbErr;
 use serde_json::json;
@@ -11,6 +11,8 @@ pub enum AppError {
     NotFound,
     Database(DbErr),
     BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
     InternalServer(String),
 }
 
@@ -20,9 +22,14 @@ impl IntoResponse for AppError {
             AppError::NotFound => (StatusCode::NOT_FOUND, "Resource not found".to_string()),
             AppError::Database(e) => {
                 eprintln!("Database error: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Database error".to_string(),
+                )
             }
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             AppError::InternalServer(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
 
@@ -45,4 +52,4 @@ impl From<anyhow::Error> for AppError {
 }
 
 // Type alias for convenience
-pub type AppResult<T> = Result<T, AppError>;
\ No newline at end of file
+pub type AppResult<T> = Result<T, AppError>;
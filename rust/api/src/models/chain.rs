@@ -1,7 +1,6 @@
 use db::entities::chains;
 use serde::Serialize;
 
-
 #[derive(Debug, Serialize)]
 pub struct ChainsResponse {
     pub chains: Vec<chains::Model>,
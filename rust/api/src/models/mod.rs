@@ -1,2 +1,2 @@
+pub mod chain;
 pub mod protocol;
-pub mod chain;
\ No newline at end of file
@@ -9,4 +9,4 @@ pub struct Protocol {
 pub struct ProtocolsResponse {
     pub protocols: Vec<Protocol>,
     pub count: usize,
-}
\ No newline at end of file
+}
@@ -0,0 +1,31 @@
+use config::Settings;
+use db::entities::pools;
+
+use crate::errors::{AppError, AppResult};
+
+/// Current tick of a pool, read from the chain of the DEX it belongs to
+pub async fn current_tick(pool: &pools::Model, settings: &Settings) -> AppResult<i32> {
+    let network = settings.network;
+    let fullnode_url = &settings.rpc.fullnode_url;
+    let tick = match pool.dex.as_str() {
+        "tapp" => {
+            let client = tapp::TappChainClient::from_network(network.try_into()?, fullnode_url)?;
+            let tick = client.get_current_tick_index(&pool.id).await?;
+            i32::try_from(tick).map_err(|_| {
+                AppError::InternalServer(format!("Tick {} out of range for pool {}", tick, pool.id))
+            })?
+        }
+        "hyperion" => {
+            let client = hyperion::HyperionClient::from_network(network.into(), fullnode_url)?;
+            client.get_current_tick(&pool.id).await?
+        }
+        dex => {
+            return Err(AppError::BadRequest(format!(
+                "Reading the current tick is not supported for {} pools",
+                dex
+            )));
+        }
+    };
+
+    Ok(tick)
+}
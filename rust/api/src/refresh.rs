@@ -0,0 +1,128 @@
+use anyhow::{Result, anyhow};
+use config::Settings;
+use db::{
+    entities::{pools, positions, tokens},
+    ingest::{self, IngestStats},
+    query,
+};
+use sea_orm::DatabaseConnection;
+use tapp::{
+    TappChainClient,
+    api::{api::TappHttpClient, models::PoolsQuery},
+    types::Network,
+};
+
+/// Upsert every CLMM pool listed by TAPP's public API
+pub async fn pools(database: &DatabaseConnection, settings: &Settings) -> Result<IngestStats> {
+    let http_client = TappHttpClient::with_base_url(&settings.apis.tapp_url);
+
+    let api_pools = http_client
+        .get_all_pools(PoolsQuery::all_clmm_pools())
+        .await
+        .map_err(|e| anyhow!("Failed to fetch pools from TAPP API: {}", e))?;
+
+    let models = api_pools
+        .into_iter()
+        .map(|p| p.to_active_model())
+        .collect::<Result<Vec<pools::ActiveModel>>>()?;
+
+    Ok(ingest::upsert_pools(database, models).await?)
+}
+
+/// Upsert a single TAPP pool
+pub async fn pool(
+    database: &DatabaseConnection,
+    settings: &Settings,
+    id: &str,
+) -> Result<IngestStats> {
+    let http_client = TappHttpClient::with_base_url(&settings.apis.tapp_url);
+
+    let api_pool = http_client
+        .get_pool(id)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch pool from TAPP API: {}", e))?;
+
+    Ok(ingest::upsert_pools(database, vec![api_pool.to_active_model()?]).await?)
+}
+
+/// Sync the open positions of a TAPP pool with the chain, removing closed ones
+pub async fn positions(
+    database: &DatabaseConnection,
+    settings: &Settings,
+    pool_id: &str,
+) -> Result<IngestStats> {
+    sync_positions(database, &tapp_client(settings)?, pool_id).await
+}
+
+async fn sync_positions(
+    database: &DatabaseConnection,
+    tapp_client: &TappChainClient,
+    pool_id: &str,
+) -> Result<IngestStats> {
+    let positions = tapp_client
+        .fetch_positions(pool_id)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch positions: {}", e))?;
+
+    let models = positions
+        .into_iter()
+        .map(|p| p.to_active_model(pool_id))
+        .collect::<Result<Vec<positions::ActiveModel>>>()
+        .map_err(|e| anyhow!("Invalid position data: {}", e))?;
+
+    Ok(ingest::sync_positions(database, pool_id, models).await?)
+}
+
+/// Sync the positions of the `limit` TAPP pools with the highest TVL
+///
+/// Every pool is attempted, a failure is reported once the others are done.
+pub async fn top_pool_positions(
+    database: &DatabaseConnection,
+    settings: &Settings,
+    limit: u64,
+) -> Result<IngestStats> {
+    let pool_ids = query::top_pools_by_tvl(database, "tapp", limit).await?;
+    let tapp_client = tapp_client(settings)?;
+
+    let mut stats = IngestStats::default();
+    let mut failures = Vec::new();
+    for pool_id in &pool_ids {
+        match sync_positions(database, &tapp_client, pool_id).await {
+            Ok(pool_stats) => stats += pool_stats,
+            Err(e) => failures.push(format!("{}: {}", pool_id, e)),
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(anyhow!(
+            "Positions refresh failed for {} of {} pools ({} rows changed): {}",
+            failures.len(),
+            pool_ids.len(),
+            stats.changed(),
+            failures.join("; ")
+        ));
+    }
+
+    Ok(stats)
+}
+
+fn tapp_client(settings: &Settings) -> Result<TappChainClient> {
+    TappChainClient::from_network(
+        Network::try_from(settings.network)?,
+        &settings.rpc.fullnode_url,
+    )
+}
+
+/// Upsert every token listed by TAPP's public API
+pub async fn tokens(database: &DatabaseConnection, settings: &Settings) -> Result<IngestStats> {
+    let http_client = TappHttpClient::with_base_url(&settings.apis.tapp_url);
+
+    let api_tokens = http_client
+        .get_all_tokens()
+        .await
+        .map_err(|e| anyhow!("Failed to fetch tokens from TAPP API: {}", e))?;
+
+    let models: Vec<tokens::ActiveModel> = api_tokens.iter().map(|t| t.to_active_model()).collect();
+
+    Ok(ingest::upsert_tokens(database, models).await?)
+}
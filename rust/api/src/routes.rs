@@ -1,6 +1,10 @@
+pub mod auth;
+pub mod chains;
+pub mod exchanges;
+pub mod jobs;
 pub mod pools;
 pub mod positions;
 pub mod protocols;
+pub mod simulate;
 pub mod tokens;
-pub mod exchanges;
-pub mod chains;
+pub mod users;
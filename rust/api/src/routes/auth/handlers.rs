@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State, http::StatusCode};
+use chrono::Utc;
+use liquidity_core::address::AccountAddress;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    auth::extract::SignedIn,
+    errors::{AppError, AppResult},
+};
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ChallengeRequest {
+    pub address: String,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ChallengeResponse {
+    pub address: String,
+    pub nonce: String,
+    /// Message to sign with the wallet
+    pub message: String,
+    /// Unix seconds
+    pub issued_at: i64,
+    pub expires_at: i64,
+}
+
+/// POST /auth/challenge - Message to sign to open a session
+#[utoipa::path(
+    post,
+    path = "/auth/challenge",
+    tag = "auth",
+    request_body = ChallengeRequest,
+    responses(
+        (status = 200, description = "Challenge issued", body = ChallengeResponse),
+        (status = 400, description = "Invalid address")
+    )
+)]
+pub async fn create_challenge(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ChallengeRequest>,
+) -> AppResult<Json<ChallengeResponse>> {
+    let address: AccountAddress = request
+        .address
+        .parse()
+        .map_err(|_| AppError::BadRequest(format!("Invalid address {}", request.address)))?;
+
+    let challenge = state.auth.challenge(address, Utc::now().timestamp());
+
+    Ok(Json(ChallengeResponse {
+        address: challenge.address.to_string(),
+        nonce: challenge.nonce,
+        message: challenge.message,
+        issued_at: challenge.issued_at,
+        expires_at: challenge.expires_at,
+    }))
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct VerifyRequest {
+    pub nonce: String,
+    /// Hex encoded Ed25519 public key of the account
+    pub public_key: String,
+    /// Hex encoded signature
+    pub signature: String,
+    /// Full message signed by the wallet, when it wraps the challenge message
+    pub full_message: Option<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct SessionResponse {
+    /// Bearer token for the `Authorization` header
+    pub token: String,
+    pub address: String,
+    /// Unix seconds
+    pub expires_at: i64,
+}
+
+/// POST /auth/verify - Trade a signed challenge for a session token
+///
+/// The address must still be authenticated by the key that signed: accounts that rotated
+/// their key can't sign in.
+#[utoipa::path(
+    post,
+    path = "/auth/verify",
+    tag = "auth",
+    request_body = VerifyRequest,
+    responses(
+        (status = 200, description = "Session opened", body = SessionResponse),
+        (status = 400, description = "Malformed key or signature"),
+        (status = 401, description = "Invalid, expired or already used challenge")
+    )
+)]
+pub async fn verify_challenge(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<VerifyRequest>,
+) -> AppResult<Json<SessionResponse>> {
+    let public_key = decode_hex("public_key", &request.public_key)?;
+    let signature = decode_hex("signature", &request.signature)?;
+
+    let (token, session) = state
+        .auth
+        .sign_in(
+            &request.nonce,
+            &public_key,
+            &signature,
+            request.full_message.as_deref(),
+            Utc::now().timestamp(),
+        )
+        .map_err(|e| AppError::Unauthorized(e.to_string()))?;
+
+    Ok(Json(SessionResponse {
+        token,
+        address: session.address.to_string(),
+        expires_at: session.expires_at,
+    }))
+}
+
+/// POST /auth/logout - Close the current session
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    responses(
+        (status = 204, description = "Session closed"),
+        (status = 401, description = "Missing or invalid session")
+    ),
+    security(("bearer" = []))
+)]
+pub async fn logout(State(state): State<Arc<AppState>>, signed_in: SignedIn) -> StatusCode {
+    state.auth.sign_out(&signed_in.token);
+    StatusCode::NO_CONTENT
+}
+
+fn decode_hex(field: &str, value: &str) -> AppResult<Vec<u8>> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value))
+        .map_err(|_| AppError::BadRequest(format!("{} must be hex encoded", field)))
+}
@@ -0,0 +1,14 @@
+pub mod handlers;
+
+use std::sync::Arc;
+
+use axum::{Router, routing::post};
+
+use crate::AppState;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/auth/challenge", post(handlers::create_challenge))
+        .route("/auth/verify", post(handlers::verify_challenge))
+        .route("/auth/logout", post(handlers::logout))
+}
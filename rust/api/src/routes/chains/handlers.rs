@@ -5,7 +5,7 @@ use crate::{
 };
 use axum::{Json, extract::State};
 use db::entities::{chains, chains::Entity as Chains};
-use sea_orm::{EntityTrait};
+use sea_orm::EntityTrait;
 use std::sync::Arc;
 
 // NOTE: not enough chains to worry about pagination. Not dealing with filtering for now either.
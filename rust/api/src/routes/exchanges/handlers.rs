@@ -1,11 +1,11 @@
 use std::sync::Arc;
 
-use axum::{extract::State, Json};
+use axum::{Json, extract::State};
 use db::entities::pools::{Column as PoolColumn, Entity as Pools};
 use sea_orm::{EntityTrait, QuerySelect};
 use serde::Serialize;
 
-use crate::{errors::AppResult, AppState};
+use crate::{AppState, errors::AppResult};
 
 #[derive(Debug, Serialize)]
 pub struct ExchangesResponse {
@@ -39,7 +39,7 @@ pub async fn list_exchanges(
 
     // If database is empty, return hardcoded list of supported exchanges
     let exchanges = if dexes.is_empty() {
-        vec!["tapp".to_string()]
+        vec!["tapp".to_string(), "hyperion".to_string()]
     } else {
         dexes
     };
@@ -1,10 +1,9 @@
 pub mod handlers;
 
-use std::sync::Arc;
-use axum::{Router, routing::get};
 use crate::AppState;
+use axum::{Router, routing::get};
+use std::sync::Arc;
 
 pub fn router() -> Router<Arc<AppState>> {
-    Router::new()
-        .route("/exchanges", get(handlers::list_exchanges))
+    Router::new().route("/exchanges", get(handlers::list_exchanges))
 }
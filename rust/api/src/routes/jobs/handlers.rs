@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use db::{
+    entities::{
+        jobs,
+        sea_orm_active_enums::{JobKind, JobState},
+    },
+    queue,
+};
+use serde::Serialize;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    scheduler::JobStatus,
+};
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct JobsResponse {
+    /// False when `scheduler.enabled` is off, no job runs then
+    pub enabled: bool,
+    pub jobs: Vec<JobStatus>,
+}
+
+/// GET /jobs - Status of the scheduled refreshes
+///
+/// Reports the last run of every periodic job, its duration, error and rows changed.
+#[utoipa::path(
+    get,
+    path = "/jobs",
+    tag = "jobs",
+    responses(
+        (status = 200, description = "Status of every scheduled job", body = JobsResponse)
+    )
+)]
+pub async fn list_jobs(State(state): State<Arc<AppState>>) -> Json<JobsResponse> {
+    Json(JobsResponse {
+        enabled: state.settings.scheduler.enabled,
+        jobs: state.scheduler.statuses(),
+    })
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct JobAccepted {
+    pub job_id: i32,
+    pub state: JobState,
+    /// Where to follow the job
+    pub location: String,
+}
+
+/// Queue a refresh and answer `202 Accepted` with the job to follow
+pub(crate) async fn enqueue(
+    state: &AppState,
+    kind: JobKind,
+    target: Option<String>,
+) -> AppResult<(StatusCode, Json<JobAccepted>)> {
+    let max_attempts = i32::try_from(state.settings.queue.max_attempts)
+        .map_err(|_| AppError::InternalServer("Invalid queue.max_attempts".to_string()))?;
+    let job = queue::enqueue(&state.database, kind, target, max_attempts).await?;
+    state.workers.wake();
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(JobAccepted {
+            job_id: job.id,
+            state: job.state,
+            location: format!("/v1/jobs/{}", job.id),
+        }),
+    ))
+}
+
+/// GET /jobs/:id - Progress of a queued refresh
+///
+/// `run_at` is the next attempt while the job is queued. A `dead` job ran out of attempts,
+/// `last_error` tells why.
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    tag = "jobs",
+    params(
+        ("id" = i32, Path, description = "Job ID returned when the refresh was requested")
+    ),
+    responses(
+        (status = 200, description = "Job state, attempts and result", body = jobs::Model),
+        (status = 404, description = "Job not found")
+    )
+)]
+pub async fn get_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> AppResult<Json<jobs::Model>> {
+    let job = queue::find(&state.database, id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    Ok(Json(job))
+}
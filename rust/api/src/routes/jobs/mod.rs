@@ -0,0 +1,11 @@
+pub mod handlers;
+
+use crate::AppState;
+use axum::{Router, routing::get};
+use std::sync::Arc;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/jobs", get(handlers::list_jobs))
+        .route("/jobs/{id}", get(handlers::get_job))
+}
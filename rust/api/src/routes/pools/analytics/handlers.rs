@@ -0,0 +1,537 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use chrono::{DateTime, Utc};
+use db::entities::{
+    pool_prices, pool_prices::Entity as PoolPrices, pool_snapshots,
+    pool_snapshots::Entity as PoolSnapshots, pools, pools::Entity as Pools, positions,
+    positions::Entity as Positions, tokens::Entity as Tokens,
+};
+use liquidity_core::{
+    analytics::volatility,
+    clmm::{
+        MathError,
+        distribution::{PositionLiquidity, liquidity_distribution},
+        price::tick_to_price,
+        tick_math::{MAX_TICK, MIN_TICK, align_tick_down, get_sqrt_price_at_tick},
+    },
+};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use tapp::api::models::PriceInterval;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    onchain,
+};
+
+const DEFAULT_BUCKET_WIDTH: u32 = 10;
+const DEFAULT_BUCKETS: u32 = 100;
+const MAX_BUCKETS: u32 = 1000;
+
+const DEFAULT_HISTORY_DAYS: i64 = 7;
+const MAX_HISTORY_DAYS: i64 = 366;
+
+const DEFAULT_VOLATILITY_SHORT_WINDOW: u64 = 12;
+const DEFAULT_VOLATILITY_WINDOW: u64 = 24;
+const DEFAULT_VOLATILITY_BASELINE: u64 = 168;
+const MAX_VOLATILITY_POINTS: u64 = 5000;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct LiquidityQuery {
+    /// Width of a bucket, in ticks, at most the whole tick range
+    pub bucket_width: Option<u32>,
+    /// Number of buckets, centered on the current tick
+    pub buckets: Option<u32>,
+    /// Add the token B per token A price bounds of each bucket
+    pub prices: Option<bool>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct LiquidityBucketResponse {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    /// Active liquidity averaged over the bucket
+    pub liquidity: String,
+    /// Raw token A amount locked in the bucket
+    pub amount_a: String,
+    /// Raw token B amount locked in the bucket
+    pub amount_b: String,
+    pub price_lower: Option<f64>,
+    pub price_upper: Option<f64>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct LiquidityDistributionResponse {
+    pub pool_id: String,
+    pub current_tick: i32,
+    pub bucket_width: u32,
+    pub buckets: Vec<LiquidityBucketResponse>,
+}
+
+/// GET /pools/:id/liquidity - Liquidity distribution of a pool
+///
+/// Buckets the active liquidity of the stored positions around the current tick, with the
+/// token amounts each bucket holds at the current price.
+#[utoipa::path(
+    get,
+    path = "/pools/{id}/liquidity",
+    tag = "pools",
+    params(
+        ("id" = String, Path, description = "Pool ID"),
+        LiquidityQuery
+    ),
+    responses(
+        (status = 200, description = "Liquidity distribution computed", body = LiquidityDistributionResponse),
+        (status = 400, description = "Invalid bucket parameters"),
+        (status = 404, description = "Pool not found")
+    )
+)]
+pub async fn get_pool_liquidity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<LiquidityQuery>,
+) -> AppResult<Json<LiquidityDistributionResponse>> {
+    let bucket_width = params.bucket_width.unwrap_or(DEFAULT_BUCKET_WIDTH);
+    let bucket_count = params.buckets.unwrap_or(DEFAULT_BUCKETS);
+
+    let max_bucket_width = (MAX_TICK - MIN_TICK) as u32;
+    if bucket_width == 0 || bucket_width > max_bucket_width {
+        return Err(AppError::BadRequest(format!(
+            "bucket_width must be between 1 and {}",
+            max_bucket_width
+        )));
+    }
+    if bucket_count == 0 || bucket_count > MAX_BUCKETS {
+        return Err(AppError::BadRequest(format!(
+            "buckets must be between 1 and {}",
+            MAX_BUCKETS
+        )));
+    }
+
+    let pool = Pools::find_by_id(&id)
+        .one(&state.database)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let positions = Positions::find()
+        .filter(positions::Column::Pool.eq(&id))
+        .all(&state.database)
+        .await?
+        .into_iter()
+        .map(|p| {
+            Ok::<_, anyhow::Error>(PositionLiquidity {
+                tick_lower: i32::try_from(p.tick_lower)?,
+                tick_upper: i32::try_from(p.tick_upper)?,
+                liquidity: p.liquidity.parse()?,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let current_tick = onchain::current_tick(&pool, &state.settings).await?;
+    let sqrt_price = get_sqrt_price_at_tick(current_tick)
+        .map_err(|e| AppError::InternalServer(e.to_string()))?;
+
+    let span = (bucket_width as i64 * bucket_count as i64).min(i32::MAX as i64) as i32;
+    let start = align_tick_down(current_tick, bucket_width).saturating_sub(span / 2);
+    let end = start.saturating_add(span);
+
+    let buckets = liquidity_distribution(&positions, sqrt_price, start, end, bucket_width)
+        .map_err(|e| match e {
+            MathError::InvalidRange => AppError::BadRequest(e.to_string()),
+            _ => AppError::InternalServer(e.to_string()),
+        })?;
+
+    let decimals = if params.prices.unwrap_or(false) {
+        Some(token_decimals(&state.database, &pool).await?)
+    } else {
+        None
+    };
+
+    let price = |tick: i32| {
+        decimals
+            .and_then(|(decimals_a, decimals_b)| tick_to_price(tick, decimals_a, decimals_b).ok())
+    };
+
+    let buckets = buckets
+        .into_iter()
+        .map(|b| LiquidityBucketResponse {
+            tick_lower: b.tick_lower,
+            tick_upper: b.tick_upper,
+            liquidity: b.liquidity.to_string(),
+            amount_a: b.amount_a.to_string(),
+            amount_b: b.amount_b.to_string(),
+            price_lower: price(b.tick_lower),
+            price_upper: price(b.tick_upper),
+        })
+        .collect();
+
+    Ok(Json(LiquidityDistributionResponse {
+        pool_id: id,
+        current_tick,
+        bucket_width,
+        buckets,
+    }))
+}
+
+/// Decimals of the pool's token A and token B, needed to turn ticks into prices
+pub(crate) async fn token_decimals(
+    database: &DatabaseConnection,
+    pool: &pools::Model,
+) -> AppResult<(u8, u8)> {
+    let (Some(token_a), Some(token_b)) = (&pool.token_a, &pool.token_b) else {
+        return Err(AppError::BadRequest("Pool tokens are unknown".to_string()));
+    };
+
+    let mut decimals = [0u8; 2];
+    for (slot, token_id) in decimals.iter_mut().zip([token_a, token_b]) {
+        let token = Tokens::find_by_id(token_id)
+            .one(database)
+            .await?
+            .ok_or_else(|| {
+                AppError::BadRequest(format!("Token {} has not been scraped", token_id))
+            })?;
+        *slot = u8::try_from(token.decimals)
+            .map_err(|_| AppError::InternalServer(format!("Invalid decimals for {}", token_id)))?;
+    }
+
+    Ok((decimals[0], decimals[1]))
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryInterval {
+    /// Every stored snapshot
+    Raw,
+    #[default]
+    Hour,
+    Day,
+    Week,
+}
+
+impl HistoryInterval {
+    fn seconds(&self) -> Option<i64> {
+        match self {
+            HistoryInterval::Raw => None,
+            HistoryInterval::Hour => Some(3_600),
+            HistoryInterval::Day => Some(86_400),
+            HistoryInterval::Week => Some(604_800),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct HistoryQuery {
+    /// Start of the range, unix timestamp in seconds (defaults to 7 days before `to`)
+    pub from: Option<i64>,
+    /// End of the range, unix timestamp in seconds (defaults to now)
+    pub to: Option<i64>,
+    pub interval: Option<HistoryInterval>,
+}
+
+/// Snapshots averaged over one interval
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct HistoryPoint {
+    /// Start of the interval, unix timestamp in seconds
+    pub timestamp: i64,
+    pub tvl: f64,
+    pub volume_day: f64,
+    pub trading_apr: f64,
+    pub bonus_apr: f64,
+    pub total_apr: f64,
+    /// Number of snapshots averaged into this point
+    pub samples: u32,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PoolHistoryResponse {
+    pub pool_id: String,
+    pub from: i64,
+    pub to: i64,
+    pub points: Vec<HistoryPoint>,
+}
+
+/// GET /pools/:id/history - TVL, volume and APR history of a pool
+///
+/// Reads the snapshots appended on every pool scrape and averages them per interval.
+#[utoipa::path(
+    get,
+    path = "/pools/{id}/history",
+    tag = "pools",
+    params(
+        ("id" = String, Path, description = "Pool ID"),
+        HistoryQuery
+    ),
+    responses(
+        (status = 200, description = "Pool history fetched successfully", body = PoolHistoryResponse),
+        (status = 400, description = "Invalid time range"),
+        (status = 404, description = "Pool not found")
+    )
+)]
+pub async fn get_pool_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<HistoryQuery>,
+) -> AppResult<Json<PoolHistoryResponse>> {
+    let to = params.to.unwrap_or_else(|| Utc::now().timestamp());
+    let from = match params.from {
+        Some(from) => from,
+        None => to
+            .checked_sub(DEFAULT_HISTORY_DAYS * 86_400)
+            .ok_or_else(|| AppError::BadRequest(format!("Invalid timestamp {}", to)))?,
+    };
+
+    if from >= to {
+        return Err(AppError::BadRequest("from must be before to".to_string()));
+    }
+    if to
+        .checked_sub(from)
+        .is_none_or(|range| range > MAX_HISTORY_DAYS * 86_400)
+    {
+        return Err(AppError::BadRequest(format!(
+            "Range cannot exceed {} days",
+            MAX_HISTORY_DAYS
+        )));
+    }
+
+    let to_datetime = |timestamp: i64| {
+        DateTime::from_timestamp(timestamp, 0)
+            .map(|d| d.naive_utc())
+            .ok_or_else(|| AppError::BadRequest(format!("Invalid timestamp {}", timestamp)))
+    };
+
+    Pools::find_by_id(&id)
+        .one(&state.database)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let snapshots = PoolSnapshots::find()
+        .filter(pool_snapshots::Column::PoolId.eq(&id))
+        .filter(pool_snapshots::Column::CreatedAt.between(to_datetime(from)?, to_datetime(to)?))
+        .order_by_asc(pool_snapshots::Column::CreatedAt)
+        .all(&state.database)
+        .await?;
+
+    let points = downsample(&snapshots, params.interval.unwrap_or_default());
+
+    Ok(Json(PoolHistoryResponse {
+        pool_id: id,
+        from,
+        to,
+        points,
+    }))
+}
+
+/// Average chronologically ordered snapshots per interval
+fn downsample(snapshots: &[pool_snapshots::Model], interval: HistoryInterval) -> Vec<HistoryPoint> {
+    let mut points: Vec<HistoryPoint> = Vec::new();
+
+    for snapshot in snapshots {
+        let created_at = snapshot.created_at.and_utc().timestamp();
+        let timestamp = match interval.seconds() {
+            Some(seconds) => created_at - created_at.rem_euclid(seconds),
+            None => created_at,
+        };
+
+        match points.last_mut() {
+            Some(point) if interval.seconds().is_some() && point.timestamp == timestamp => {
+                // Running mean, so the sums never have to be kept around
+                point.samples += 1;
+                let weight = 1.0 / point.samples as f64;
+                point.tvl += (snapshot.tvl - point.tvl) * weight;
+                point.volume_day += (snapshot.volume_day - point.volume_day) * weight;
+                point.trading_apr += (snapshot.trading_apr - point.trading_apr) * weight;
+                point.bonus_apr += (snapshot.bonus_apr - point.bonus_apr) * weight;
+                point.total_apr = point.trading_apr + point.bonus_apr;
+            }
+            _ => points.push(HistoryPoint {
+                timestamp,
+                tvl: snapshot.tvl,
+                volume_day: snapshot.volume_day,
+                trading_apr: snapshot.trading_apr,
+                bonus_apr: snapshot.bonus_apr,
+                total_apr: snapshot.trading_apr + snapshot.bonus_apr,
+                samples: 1,
+            }),
+        }
+    }
+
+    points
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct VolatilityQuery {
+    /// Price chart resolution, 1h, 4h or 1d (defaults to 1h)
+    #[param(value_type = Option<String>)]
+    pub interval: Option<PriceInterval>,
+    /// Number of latest prices making up the short window (defaults to 12)
+    pub short_window: Option<u64>,
+    /// Number of latest prices making up the recent window (defaults to 24)
+    pub window: Option<u64>,
+    /// Number of latest prices the recent window is compared to (defaults to 168)
+    pub baseline: Option<u64>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct VolatilityResponse {
+    pub pool_id: String,
+    pub interval: String,
+    /// Prices the analysis ran on
+    pub points: usize,
+    /// Annualized volatility over the short window, in percent. The web app's `hour` metric
+    /// with hourly prices and the default window
+    pub short_volatility: f64,
+    /// Annualized volatility over the recent window, in percent
+    pub volatility: f64,
+    /// Annualized volatility over the baseline, in percent
+    pub baseline_volatility: f64,
+    /// low, moderate or high
+    pub level: String,
+    /// increasing, decreasing or stable
+    pub trend: String,
+    pub insight: String,
+}
+
+/// GET /pools/:id/volatility - Realized volatility of a pool
+///
+/// Computed from the stored price history, see the `prices` scraper command.
+#[utoipa::path(
+    get,
+    path = "/pools/{id}/volatility",
+    tag = "pools",
+    params(
+        ("id" = String, Path, description = "Pool ID"),
+        VolatilityQuery
+    ),
+    responses(
+        (status = 200, description = "Volatility computed", body = VolatilityResponse),
+        (status = 400, description = "Invalid window or baseline"),
+        (status = 404, description = "Pool not found")
+    )
+)]
+pub async fn get_pool_volatility(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<VolatilityQuery>,
+) -> AppResult<Json<VolatilityResponse>> {
+    let interval = params.interval.unwrap_or_default();
+    let short_window = params
+        .short_window
+        .unwrap_or(DEFAULT_VOLATILITY_SHORT_WINDOW);
+    let window = params.window.unwrap_or(DEFAULT_VOLATILITY_WINDOW);
+    let baseline = params.baseline.unwrap_or(DEFAULT_VOLATILITY_BASELINE);
+
+    if short_window < 2
+        || short_window > window
+        || window > baseline
+        || baseline > MAX_VOLATILITY_POINTS
+    {
+        return Err(AppError::BadRequest(format!(
+            "Expected 2 <= short_window <= window <= baseline <= {}",
+            MAX_VOLATILITY_POINTS
+        )));
+    }
+
+    Pools::find_by_id(&id)
+        .one(&state.database)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let mut prices: Vec<f64> = PoolPrices::find()
+        .filter(pool_prices::Column::PoolId.eq(&id))
+        .filter(pool_prices::Column::Interval.eq(interval.as_str()))
+        .order_by_desc(pool_prices::Column::Timestamp)
+        .limit(baseline)
+        .all(&state.database)
+        .await?
+        .into_iter()
+        .map(|p| p.price)
+        .collect();
+    prices.reverse();
+
+    let analysis = volatility::analyze(
+        &prices,
+        interval.hours(),
+        short_window as usize,
+        window as usize,
+        baseline as usize,
+    );
+
+    Ok(Json(VolatilityResponse {
+        pool_id: id,
+        interval: interval.as_str().to_string(),
+        points: prices.len(),
+        short_volatility: analysis.short,
+        volatility: analysis.recent,
+        baseline_volatility: analysis.baseline,
+        level: analysis.level.to_string(),
+        trend: analysis.trend.to_string(),
+        insight: analysis.insight,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(created_at: i64, tvl: f64, trading_apr: f64) -> pool_snapshots::Model {
+        pool_snapshots::Model {
+            id: 0,
+            pool_id: "0xpool".to_string(),
+            tvl,
+            volume_day: tvl / 10.0,
+            trading_apr,
+            bonus_apr: 1.0,
+            created_at: DateTime::from_timestamp(created_at, 0).unwrap().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn averages_snapshots_per_interval() {
+        let snapshots = [
+            snapshot(3_600, 100.0, 10.0),
+            snapshot(3_600 + 1_200, 200.0, 20.0),
+            snapshot(3_600 + 2_400, 300.0, 30.0),
+            snapshot(7_200 + 60, 1_000.0, 5.0),
+        ];
+
+        let points = downsample(&snapshots, HistoryInterval::Hour);
+
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].timestamp, 3_600);
+        assert_eq!(points[0].samples, 3);
+        assert!((points[0].tvl - 200.0).abs() < 1e-9);
+        assert!((points[0].volume_day - 20.0).abs() < 1e-9);
+        assert!((points[0].trading_apr - 20.0).abs() < 1e-9);
+        assert!((points[0].total_apr - 21.0).abs() < 1e-9);
+        assert_eq!(points[1].timestamp, 7_200);
+        assert_eq!(points[1].samples, 1);
+        assert_eq!(points[1].tvl, 1_000.0);
+    }
+
+    #[test]
+    fn raw_interval_keeps_every_snapshot() {
+        let snapshots = [snapshot(60, 1.0, 1.0), snapshot(60, 2.0, 2.0)];
+
+        let points = downsample(&snapshots, HistoryInterval::Raw);
+
+        assert_eq!(points.len(), 2);
+        assert!(points.iter().all(|p| p.samples == 1 && p.timestamp == 60));
+    }
+
+    #[test]
+    fn buckets_snapshots_before_the_epoch() {
+        let points = downsample(&[snapshot(-1, 1.0, 1.0)], HistoryInterval::Day);
+
+        assert_eq!(points[0].timestamp, -86_400);
+    }
+
+    #[test]
+    fn empty_history_has_no_points() {
+        assert!(downsample(&[], HistoryInterval::Week).is_empty());
+    }
+}
@@ -0,0 +1,14 @@
+pub mod handlers;
+
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use crate::AppState;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/pools/{id}/liquidity", get(handlers::get_pool_liquidity))
+        .route("/pools/{id}/history", get(handlers::get_pool_history))
+        .route("/pools/{id}/volatility", get(handlers::get_pool_volatility))
+}
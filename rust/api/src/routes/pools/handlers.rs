@@ -1,45 +1,82 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use axum::{
     Json,
     extract::{Path, Query, State},
 };
-use db::entities::{pools, pools::Entity as Pools};
-use sea_orm::{Condition, EntityTrait};
+use db::entities::{pools, pools::Entity as Pools, tokens, tokens::Entity as Tokens};
+use sea_orm::{
+    Condition, DatabaseConnection, EntityTrait, Order, PaginatorTrait,
+    sea_query::{Expr, ExprTrait},
+};
 
 use crate::{
     AppState,
     errors::{AppError, AppResult},
+    routes::tokens::handlers::TokenSummary,
 };
 use sea_orm::ColumnTrait;
 use sea_orm::QueryFilter;
 use sea_orm::QueryOrder;
 use sea_orm::QuerySelect;
 
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Expand {
+    Tokens,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct PoolQuery {
+    /// `tokens` embeds the metadata of both tokens
+    pub expand: Option<Expand>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PoolTokens {
+    pub token_a: Option<TokenSummary>,
+    pub token_b: Option<TokenSummary>,
+}
+
+/// Pool, with the metadata of its tokens when requested with `?expand=tokens`
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PoolResponse {
+    #[serde(flatten)]
+    pub pool: pools::Model,
+    /// Tokens that haven't been scraped are left empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<PoolTokens>,
+}
+
 #[utoipa::path(
     get,
     path = "/pools/{id}",
     tag = "pools",
     params(
-        ("id" = String, Path, description = "Pool ID")
+        ("id" = String, Path, description = "Pool ID"),
+        PoolQuery
     ),
     responses(
-        (status=200, description="Pool fetched successfully")
+        (status=200, description="Pool fetched successfully", body = PoolResponse),
+        (status=404, description="Pool not found")
     )
 )]
 #[axum::debug_handler]
 pub async fn get_pool(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> AppResult<Json<pools::Model>> {
+    Query(params): Query<PoolQuery>,
+) -> AppResult<Json<PoolResponse>> {
     let pool = Pools::find_by_id(id)
         .one(&state.database)
         .await?
         .ok_or(AppError::NotFound)?;
 
-    Ok(Json(pool))
+    let mut pools = with_tokens(&state.database, vec![pool], params.expand).await?;
+
+    Ok(Json(pools.remove(0)))
 }
 
 #[derive(Debug, Deserialize)]
@@ -80,6 +117,9 @@ pub struct PoolsQuery {
     // Pagination
     pub limit: Option<u64>,
     pub offset: Option<u64>,
+
+    // Embedded relations
+    pub expand: Option<Expand>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -123,11 +163,22 @@ pub enum OrderDir {
 // Response struct
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct PoolsResponse {
-    pub pools: Vec<pools::Model>,
-    pub count: usize,
+    pub pools: Vec<PoolResponse>,
+    /// Pools matching the filters, across all pages
+    pub count: u64,
+    /// Offset of the next page, absent on the last one
+    pub next_offset: Option<u64>,
+}
+
+/// `bonus_apr + trading_apr`, computed by the database
+fn total_apr() -> Expr {
+    Expr::col(pools::Column::BonusApr).add(Expr::col(pools::Column::TradingApr))
 }
 
 #[utoipa::path(get, path = "/pools", tag = "pools",
+params(
+        ("expand" = Option<Expand>, Query, description = "`tokens` embeds the metadata of both tokens")
+    ),
 responses(
         (status=200, description="Pools fetched successfully", body = PoolsResponse)
     ))]
@@ -230,13 +281,20 @@ pub async fn get_pools(
             }
         }
         AprType::Total => {
-            // For total APR, we'll filter after fetching
-            // since it's a computed value (bonus_apr + trading_apr)
+            if let Some(apr_min) = params.apr_min {
+                condition = condition.add(total_apr().gte(apr_min));
+            }
+            if let Some(apr_max) = params.apr_max {
+                condition = condition.add(total_apr().lte(apr_max));
+            }
         }
     }
 
     query = query.filter(condition);
 
+    // Count before ordering and pagination
+    let count = query.clone().count(&state.database).await?;
+
     // Apply ordering
     if let Some(order_by_val) = &order_by {
         query = match order_by_val {
@@ -264,14 +322,10 @@ pub async fn get_pools(
                 OrderDir::Asc => query.order_by_asc(pools::Column::TradingApr),
                 OrderDir::Desc => query.order_by_desc(pools::Column::TradingApr),
             },
-            OrderBy::TotalApr => {
-                // For total APR sorting, we'll sort after fetching
-                // Default to bonus_apr in the query
-                match order_dir {
-                    OrderDir::Asc => query.order_by_asc(pools::Column::BonusApr),
-                    OrderDir::Desc => query.order_by_desc(pools::Column::BonusApr),
-                }
-            }
+            OrderBy::TotalApr => match order_dir {
+                OrderDir::Asc => query.order_by(total_apr(), Order::Asc),
+                OrderDir::Desc => query.order_by(total_apr(), Order::Desc),
+            },
             OrderBy::Tvl => match order_dir {
                 OrderDir::Asc => query.order_by_asc(pools::Column::Tvl),
                 OrderDir::Desc => query.order_by_desc(pools::Column::Tvl),
@@ -286,40 +340,74 @@ pub async fn get_pools(
         query = query.order_by_desc(pools::Column::Tvl);
     }
 
+    // Ties on the sort key would otherwise shuffle rows between pages
+    query = query.order_by_asc(pools::Column::Id);
+
     // Apply pagination
+    let offset = params.offset.unwrap_or(0);
     if let Some(limit) = params.limit {
         query = query.limit(limit);
     }
-    if let Some(offset) = params.offset {
-        query = query.offset(offset);
-    }
+    query = query.offset(offset);
 
     // Execute query
-    let mut pools = query.all(&state.database).await?;
+    let pools = query.all(&state.database).await?;
 
-    // Post-fetch filtering for total APR if needed
-    if matches!(apr_type, AprType::Total) {
-        if let Some(apr_min) = params.apr_min {
-            pools.retain(|p| p.bonus_apr + p.trading_apr >= apr_min);
-        }
-        if let Some(apr_max) = params.apr_max {
-            pools.retain(|p| p.bonus_apr + p.trading_apr <= apr_max);
-        }
+    let end = offset + pools.len() as u64;
+    let next_offset = (!pools.is_empty() && end < count).then_some(end);
 
-        // Re-sort if ordering by total APR
-        if matches!(order_by, Some(OrderBy::TotalApr)) {
-            pools.sort_by(|a, b| {
-                let total_a = a.bonus_apr + a.trading_apr;
-                let total_b = b.bonus_apr + b.trading_apr;
-                match order_dir {
-                    OrderDir::Asc => total_a.partial_cmp(&total_b).unwrap(),
-                    OrderDir::Desc => total_b.partial_cmp(&total_a).unwrap(),
-                }
-            });
-        }
+    let pools = with_tokens(&state.database, pools, params.expand).await?;
+
+    Ok(Json(PoolsResponse {
+        pools,
+        count,
+        next_offset,
+    }))
+}
+
+/// Wrap pools in responses, looking up their tokens in one query when expanded
+///
+/// `pools` points at `tokens` twice (`Tokens2` from `token_a`, `Tokens1` from `token_b`), so
+/// there is no `Related<tokens::Entity>` for the loader and `find_also_related` can only join
+/// one side. Fetching both sides by id keeps it to a single query for a page of pools.
+async fn with_tokens(
+    database: &DatabaseConnection,
+    pools: Vec<pools::Model>,
+    expand: Option<Expand>,
+) -> AppResult<Vec<PoolResponse>> {
+    if expand != Some(Expand::Tokens) {
+        return Ok(pools
+            .into_iter()
+            .map(|pool| PoolResponse { pool, tokens: None })
+            .collect());
     }
 
-    let count = pools.len();
+    let token_ids: Vec<String> = pools
+        .iter()
+        .flat_map(|pool| [pool.token_a.clone(), pool.token_b.clone()])
+        .flatten()
+        .collect();
+    let tokens: HashMap<String, tokens::Model> = Tokens::find()
+        .filter(tokens::Column::Id.is_in(token_ids))
+        .all(database)
+        .await?
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect();
+    let summary = |id: &Option<String>| {
+        id.as_ref()
+            .and_then(|id| tokens.get(id))
+            .map(TokenSummary::from)
+    };
 
-    Ok(Json(PoolsResponse { pools, count }))
+    Ok(pools
+        .into_iter()
+        .map(|pool| PoolResponse {
+            tokens: Some(PoolTokens {
+                token_a: summary(&pool.token_a),
+                token_b: summary(&pool.token_b),
+            }),
+            pool,
+        })
+        .collect())
 }
@@ -1,137 +1,40 @@
 use std::sync::Arc;
 
-use axum::{extract::{Path, State}, Json};
-use db::entities::pools::Entity as Pools;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use db::entities::{pools::Entity as Pools, sea_orm_active_enums::JobKind};
 use sea_orm::EntityTrait;
-use serde::Serialize;
 
 use crate::{
-    errors::{AppError, AppResult},
     AppState,
+    errors::{AppError, AppResult},
+    routes::jobs::handlers::{JobAccepted, enqueue},
 };
 
-#[derive(Debug, Serialize, utoipa::ToSchema)]
-pub struct RefreshPoolsResponse {
-    pub status: String,
-    pub message: String,
-    pub pools_updated: usize,
-}
-
 /// POST /pools/refresh - Refresh all pools from TAPP
 ///
-/// This endpoint fetches pool data from TAPP Exchange and updates the database.
-///
-/// NOTE: This implementation requires pool data from TAPP's API or SDK.
-/// The TypeScript implementation uses @tapp-exchange/sdk which provides:
-/// - Pool list with pagination and sorting
-/// - TVL, volume, APR data
-/// - Token pair information
-///
-/// For Rust implementation, options include:
-/// 1. Use TAPP's HTTP API (if publicly documented)
-/// 2. Fetch pool metadata from on-chain resources
-/// 3. Use a proxy service that wraps the TAPP SDK
-///
-/// Current implementation is a PLACEHOLDER showing the expected structure.
+/// Queues a job fetching every CLMM pool from TAPP's public API and upserting it into the
+/// database. Follow it at `GET /jobs/{id}`.
 #[utoipa::path(
     post,
     path = "/pools/refresh",
     tag = "pools",
     responses(
-        (status = 200, description = "Pools refreshed successfully", body = RefreshPoolsResponse)
+        (status = 202, description = "Refresh queued", body = JobAccepted)
     )
 )]
 pub async fn refresh_pools(
-    State(_state): State<Arc<AppState>>,
-) -> AppResult<Json<RefreshPoolsResponse>> {
-    // TODO: Implement pool data fetching
-    //
-    // The SvelteKit app uses @tapp-exchange/sdk:
-    // ```typescript
-    // tapp.sdk.Pool.getPools({
-    //   type: PoolType.CLMM,
-    //   sortBy: 'tvl',
-    //   size: 10,
-    //   page: 1
-    // })
-    // ```
-    //
-    // This returns pool data including:
-    // - poolId
-    // - feeTier
-    // - apr (with feeAprPercentage and boostedAprPercentage)
-    // - tvl
-    // - volumeData (volume24h, volume7d, volume30d, volumeprev24h)
-    // - tokens (array with addr, decimals, img, symbol, verified)
-    //
-    // Possible approaches:
-    // 1. Find TAPP's public API endpoint (similar to how we found /v1/public/token)
-    // 2. Read pool resources directly from blockchain (may not have all metadata)
-    // 3. Use TAPP's GraphQL API if available
-    // 4. Create a bridge service that calls the TypeScript SDK
-
-    return Err(AppError::InternalServer(
-        "Pool refresh not yet implemented. Need TAPP API endpoint or SDK integration.".to_string()
-    ));
-
-    // Example structure of what the implementation would look like:
-    /*
-    let pools_data = fetch_tapp_pools().await?;
-
-    let mut pools_updated = 0;
-
-    for pool_data in pools_data {
-        let pool_model = pools::ActiveModel {
-            id: Set(pool_data.pool_id),
-            token_a: Set(Some(pool_data.tokens[0].addr.clone())),
-            token_b: Set(Some(pool_data.tokens[1].addr.clone())),
-            fee: Set(pool_data.fee_tier.parse::<Decimal>()?),
-            dex: Set("tapp".to_string()),
-            position_index: Set(None),
-            trading_apr: Set(pool_data.apr.fee_apr_percentage),
-            bonus_apr: Set(pool_data.apr.boosted_apr_percentage),
-            tvl: Set(pool_data.tvl.parse::<f64>()?),
-            volume_day: Set(pool_data.volume_data.volume_24h.parse::<f64>()?),
-            volume_week: Set(pool_data.volume_data.volume_7d.parse::<f64>()?),
-            volume_month: Set(pool_data.volume_data.volume_30d.parse::<f64>()?),
-            volume_prev_day: Set(pool_data.volume_data.volume_prev_24h.parse::<f64>()?),
-            updated_at: Set(Some(chrono::Utc::now().naive_utc())),
-        };
-
-        // Insert or update
-        match Pools::find_by_id(&pool_data.pool_id).one(&state.database).await? {
-            Some(existing) => {
-                let mut existing: pools::ActiveModel = existing.into();
-                existing.fee = pool_model.fee;
-                existing.trading_apr = pool_model.trading_apr;
-                existing.bonus_apr = pool_model.bonus_apr;
-                existing.tvl = pool_model.tvl;
-                existing.volume_day = pool_model.volume_day;
-                existing.volume_week = pool_model.volume_week;
-                existing.volume_month = pool_model.volume_month;
-                existing.volume_prev_day = pool_model.volume_prev_day;
-                existing.updated_at = pool_model.updated_at;
-                existing.update(&state.database).await?;
-            }
-            None => {
-                pool_model.insert(&state.database).await?;
-            }
-        }
-
-        pools_updated += 1;
-    }
-
-    Ok(Json(RefreshPoolsResponse {
-        status: "success".to_string(),
-        message: format!("Updated {} pools", pools_updated),
-        pools_updated,
-    }))
-    */
+    State(state): State<Arc<AppState>>,
+) -> AppResult<(StatusCode, Json<JobAccepted>)> {
+    enqueue(&state, JobKind::RefreshPools, None).await
 }
 
 /// POST /pools/:id/refresh - Refresh a single pool
 ///
-/// This endpoint fetches the latest data for a specific pool from TAPP.
+/// Queues a job fetching the latest data for a specific pool from TAPP.
 #[utoipa::path(
     post,
     path = "/pools/{id}/refresh",
@@ -140,24 +43,19 @@ pub async fn refresh_pools(
         ("id" = String, Path, description = "Pool ID to refresh")
     ),
     responses(
-        (status = 200, description = "Pool refreshed successfully"),
+        (status = 202, description = "Refresh queued", body = JobAccepted),
         (status = 404, description = "Pool not found")
     )
 )]
 pub async fn refresh_single_pool(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> AppResult<Json<RefreshPoolsResponse>> {
+) -> AppResult<(StatusCode, Json<JobAccepted>)> {
     // Verify pool exists
     let _pool = Pools::find_by_id(&id)
         .one(&state.database)
         .await?
         .ok_or(AppError::NotFound)?;
 
-    // TODO: Implement single pool refresh
-    // Similar to refresh_pools but for a single pool
-
-    Err(AppError::InternalServer(
-        "Single pool refresh not yet implemented. Need TAPP API endpoint.".to_string()
-    ))
+    enqueue(&state, JobKind::RefreshPool, Some(id)).await
 }
@@ -1,10 +1,8 @@
 pub mod handlers;
 
-use std::sync::Arc;
 use crate::AppState;
-use axum::{
-    routing::post, Router
-};
+use axum::{Router, routing::post};
+use std::sync::Arc;
 
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
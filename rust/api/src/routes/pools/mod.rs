@@ -1,3 +1,4 @@
+pub mod analytics;
 pub mod handlers;
 pub mod jobs;
 use std::sync::Arc;
@@ -11,4 +12,5 @@ pub fn router() -> Router<Arc<AppState>> {
         .route("/pools", get(handlers::get_pools))
         .route("/pools/{id}", get(handlers::get_pool))
         .merge(jobs::router())
+        .merge(analytics::router())
 }
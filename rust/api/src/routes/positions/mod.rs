@@ -1,10 +1,12 @@
 pub mod handlers;
 
-use std::sync::Arc;
-use axum::{Router, routing::post};
 use crate::AppState;
+use axum::{Router, routing::post};
+use std::sync::Arc;
 
 pub fn router() -> Router<Arc<AppState>> {
-    Router::new()
-        .route("/positions/refresh/{pool_id}", post(handlers::refresh_positions))
+    Router::new().route(
+        "/positions/refresh/{pool_id}",
+        post(handlers::refresh_positions),
+    )
 }
@@ -1,14 +1,20 @@
 use crate::models::protocol;
-use crate::{errors::AppResult, AppState};
+use crate::{AppState, errors::AppResult};
 
+use axum::{Json, extract::State};
 use std::sync::Arc;
-use axum::{extract::State, Json};
 
 pub async fn list_protocols() -> AppResult<Json<protocol::ProtocolsResponse>> {
-    let protocols: Vec<protocol::Protocol> = vec![protocol::Protocol {
-        name: "tapp".to_string(),
-        url: None
-    }];
+    let protocols: Vec<protocol::Protocol> = vec![
+        protocol::Protocol {
+            name: "tapp".to_string(),
+            url: None,
+        },
+        protocol::Protocol {
+            name: "hyperion".to_string(),
+            url: None,
+        },
+    ];
 
     let count = protocols.len();
 
@@ -1,8 +1,8 @@
 mod handlers;
 
-use std::sync::Arc;
-use axum::{Router, routing::get};
 use crate::AppState;
+use axum::{Router, routing::get};
+use std::sync::Arc;
 
 pub fn router() -> Router<Arc<AppState>> {
     Router::new().route("/protocols", get(handlers::list_protocols))
@@ -0,0 +1,545 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use chrono::DateTime;
+use db::entities::{
+    pool_prices, pool_prices::Entity as PoolPrices, pool_snapshots,
+    pool_snapshots::Entity as PoolSnapshots, pools, pools::Entity as Pools, positions,
+    positions::Entity as Positions, tokens::Entity as Tokens,
+};
+use liquidity_core::{
+    analytics::{
+        backtest::{BacktestConfig, LiquidityProfile, MarketStep, Strategy, run_backtest},
+        impermanent_loss::{ConcentratedPosition, PositionValue, impermanent_loss, simulate_path},
+    },
+    clmm::{
+        distribution::{PositionLiquidity, liquidity_distribution},
+        price::{price_to_tick, tick_to_price},
+        tick_math::get_sqrt_price_at_tick,
+    },
+};
+use rust_decimal::prelude::ToPrimitive;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use tapp::api::models::PriceInterval;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    onchain,
+    routes::pools::analytics::handlers::token_decimals,
+};
+
+/// Price points replayed at most by a backtest
+const MAX_BACKTEST_POINTS: u64 = 20_000;
+/// Buckets used to approximate the pool's liquidity distribution
+const PROFILE_BUCKETS: i32 = 500;
+/// Symbols of the stablecoins taken as worth one USD, the unit of the snapshots' volume
+const USD_STABLECOINS: &[&str] = &["USDC", "USDT"];
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ImpermanentLossRequest {
+    pub pool_id: String,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    /// Value deposited, in token B
+    pub deposit: f64,
+    /// Price (token B per token A) the position is opened at, defaults to the current price
+    pub entry_price: Option<f64>,
+    /// Price to evaluate the position at, ignored when `price_path` is set
+    pub end_price: Option<f64>,
+    /// Prices to evaluate the position at, in order. The last one is the end price.
+    pub price_path: Option<Vec<f64>>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PositionValueResponse {
+    pub price: f64,
+    pub amount_a: f64,
+    pub amount_b: f64,
+    /// Value of the position, in token B
+    pub lp_value: f64,
+    /// Value of the deposited tokens had they been held, in token B
+    pub hodl_value: f64,
+    /// `lp_value / hodl_value - 1`, as a fraction
+    pub impermanent_loss: f64,
+    pub in_range: bool,
+}
+
+impl From<PositionValue> for PositionValueResponse {
+    fn from(value: PositionValue) -> Self {
+        Self {
+            price: value.price,
+            amount_a: value.amount_a,
+            amount_b: value.amount_b,
+            lp_value: value.lp_value,
+            hodl_value: value.hodl_value,
+            impermanent_loss: value.impermanent_loss,
+            in_range: value.in_range,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ImpermanentLossResponse {
+    pub pool_id: String,
+    pub price_lower: f64,
+    pub price_upper: f64,
+    pub entry_price: f64,
+    pub initial_amount_a: f64,
+    pub initial_amount_b: f64,
+    /// Position at the end price
+    pub result: PositionValueResponse,
+    /// Position at every price of the path, empty without `price_path`
+    pub path: Vec<PositionValueResponse>,
+}
+
+/// POST /simulate/il - Impermanent loss of a concentrated position
+///
+/// Compares a position opened over a tick range to holding the deposited tokens, for an
+/// end price or a whole price path. Fees are not included.
+#[utoipa::path(
+    post,
+    path = "/simulate/il",
+    tag = "simulate",
+    request_body = ImpermanentLossRequest,
+    responses(
+        (status = 200, description = "Simulation ran successfully", body = ImpermanentLossResponse),
+        (status = 400, description = "Invalid range, deposit or prices"),
+        (status = 404, description = "Pool not found")
+    )
+)]
+pub async fn simulate_impermanent_loss(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ImpermanentLossRequest>,
+) -> AppResult<Json<ImpermanentLossResponse>> {
+    let path = request.price_path.unwrap_or_default();
+    let Some(end_price) = path.last().copied().or(request.end_price) else {
+        return Err(AppError::BadRequest(
+            "Either end_price or price_path is required".to_string(),
+        ));
+    };
+    if path
+        .iter()
+        .chain([&end_price])
+        .any(|p| *p <= 0.0 || !p.is_finite())
+    {
+        return Err(AppError::BadRequest("Prices must be positive".to_string()));
+    }
+
+    let pool = Pools::find_by_id(&request.pool_id)
+        .one(&state.database)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let (decimals_a, decimals_b) = token_decimals(&state.database, &pool).await?;
+    let price_at = |tick: i32| {
+        tick_to_price(tick, decimals_a, decimals_b).map_err(|e| AppError::BadRequest(e.to_string()))
+    };
+
+    let price_lower = price_at(request.tick_lower)?;
+    let price_upper = price_at(request.tick_upper)?;
+    let entry_price = match request.entry_price {
+        Some(price) => price,
+        None => price_at(onchain::current_tick(&pool, &state.settings).await?)?,
+    };
+
+    let position =
+        ConcentratedPosition::from_deposit(price_lower, price_upper, entry_price, request.deposit)
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let (initial_amount_a, initial_amount_b) = position.amounts_at(entry_price);
+
+    Ok(Json(ImpermanentLossResponse {
+        pool_id: request.pool_id,
+        price_lower,
+        price_upper,
+        entry_price,
+        initial_amount_a,
+        initial_amount_b,
+        result: impermanent_loss(&position, entry_price, end_price).into(),
+        path: simulate_path(&position, entry_price, &path)
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StrategyRequest {
+    /// Keep the same tick range for the whole backtest
+    Fixed { tick_lower: i32, tick_upper: i32 },
+    /// ±k standard deviations of the price over `lookback` prices, reset when out of range
+    Sigma { k: f64, lookback: usize },
+    /// ±`width` (a fraction) around the price, re-centered every `every` prices
+    Recenter { width: f64, every: usize },
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct BacktestRequest {
+    pub pool_id: String,
+    pub strategy: StrategyRequest,
+    /// Value deposited, in token B
+    pub deposit: f64,
+    /// Cost of a rebalance, in token B (defaults to 0)
+    pub gas_cost: Option<f64>,
+    /// Resolution of the replayed price history, 1h, 4h or 1d (defaults to 1h)
+    #[schema(value_type = Option<String>)]
+    pub interval: Option<PriceInterval>,
+    /// Start of the replay, unix timestamp in seconds (defaults to the oldest stored price)
+    pub from: Option<i64>,
+    /// End of the replay, unix timestamp in seconds (defaults to the latest stored price)
+    pub to: Option<i64>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct BacktestStepResponse {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub price: f64,
+    pub lp_value: f64,
+    /// Fees earned so far
+    pub fees: f64,
+    pub in_range: bool,
+    pub rebalanced: bool,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct BacktestResponse {
+    pub pool_id: String,
+    /// Fee rate used for the estimation, as a fraction
+    pub fee_rate: f64,
+    pub fees: f64,
+    pub rebalances: u32,
+    pub gas_spent: f64,
+    pub final_lp_value: f64,
+    pub hodl_value: f64,
+    /// Loss compared to holding, gas excluded, as a fraction
+    pub impermanent_loss: f64,
+    /// Final value plus fees minus deposit, in token B
+    pub net_pnl: f64,
+    /// Final value plus fees minus the value of holding, in token B
+    pub pnl_vs_hodl: f64,
+    /// Share of the replay spent in range
+    pub time_in_range: f64,
+    pub steps: Vec<BacktestStepResponse>,
+}
+
+/// POST /simulate/backtest - Backtest a range strategy on a pool
+///
+/// Replays the stored price history of the pool. Fees are estimated from the pool fee tier,
+/// the daily volume of the pool snapshots and the position's share of the liquidity
+/// currently provided around each price.
+///
+/// Snapshot volumes are in USD while the deposit and results are in token B, so only pools
+/// with a USD stablecoin side can be backtested: the volume is converted to token B at each
+/// step's price.
+#[utoipa::path(
+    post,
+    path = "/simulate/backtest",
+    tag = "simulate",
+    request_body = BacktestRequest,
+    responses(
+        (status = 200, description = "Backtest ran successfully", body = BacktestResponse),
+        (status = 400, description = "Invalid strategy or not enough stored history"),
+        (status = 404, description = "Pool not found")
+    )
+)]
+pub async fn simulate_backtest(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<BacktestRequest>,
+) -> AppResult<Json<BacktestResponse>> {
+    let pool = Pools::find_by_id(&request.pool_id)
+        .one(&state.database)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let (decimals_a, decimals_b) = token_decimals(&state.database, &pool).await?;
+    let usd_side = usd_side(&state.database, &pool).await?;
+    let interval = request.interval.unwrap_or_default();
+
+    let mut query = PoolPrices::find()
+        .filter(pool_prices::Column::PoolId.eq(&pool.id))
+        .filter(pool_prices::Column::Interval.eq(interval.as_str()));
+    if let Some(from) = request.from {
+        query = query.filter(pool_prices::Column::Timestamp.gte(to_datetime(from)?));
+    }
+    if let Some(to) = request.to {
+        query = query.filter(pool_prices::Column::Timestamp.lte(to_datetime(to)?));
+    }
+    let mut prices = query
+        .order_by_desc(pool_prices::Column::Timestamp)
+        .limit(MAX_BACKTEST_POINTS)
+        .all(&state.database)
+        .await?;
+    prices.reverse();
+
+    let Some(last) = prices.last() else {
+        return Err(AppError::BadRequest(
+            "No stored prices for this pool and interval".to_string(),
+        ));
+    };
+
+    let snapshots = PoolSnapshots::find()
+        .filter(pool_snapshots::Column::PoolId.eq(&pool.id))
+        .filter(pool_snapshots::Column::CreatedAt.lte(last.timestamp))
+        .order_by_asc(pool_snapshots::Column::CreatedAt)
+        .all(&state.database)
+        .await?;
+
+    let step_share = interval.hours() / 24.0;
+    let volumes = daily_volumes(&prices, &snapshots, pool.volume_day);
+    let market: Vec<MarketStep> = prices
+        .iter()
+        .zip(volumes)
+        .map(|(p, volume_day)| MarketStep {
+            price: p.price,
+            volume: usd_side.to_token_b(volume_day * step_share, p.price),
+        })
+        .collect();
+
+    let (min_price, max_price) = market.iter().fold((f64::MAX, f64::MIN), |(min, max), m| {
+        (min.min(m.price), max.max(m.price))
+    });
+    let profile = liquidity_profile(
+        &state.database,
+        &pool.id,
+        (min_price, max_price),
+        (decimals_a, decimals_b),
+    )
+    .await?;
+
+    let strategy = match request.strategy {
+        StrategyRequest::Fixed {
+            tick_lower,
+            tick_upper,
+        } => Strategy::Fixed {
+            price_lower: tick_to_price(tick_lower, decimals_a, decimals_b)
+                .map_err(|e| AppError::BadRequest(e.to_string()))?,
+            price_upper: tick_to_price(tick_upper, decimals_a, decimals_b)
+                .map_err(|e| AppError::BadRequest(e.to_string()))?,
+        },
+        StrategyRequest::Sigma { k, lookback } => Strategy::Sigma { k, lookback },
+        StrategyRequest::Recenter { width, every } => Strategy::Recenter { width, every },
+    };
+
+    // `pools.fee` is a percentage
+    let fee_rate = pool.fee.to_f64().unwrap_or_default() / 100.0;
+    let config = BacktestConfig {
+        strategy,
+        deposit: request.deposit,
+        fee_rate,
+        gas_cost: request.gas_cost.unwrap_or_default(),
+    };
+
+    let result = run_backtest(&config, &market, &profile).map_err(|e| {
+        AppError::BadRequest(format!("Cannot backtest {} prices: {}", market.len(), e))
+    })?;
+
+    // The first steps of a sigma strategy are only used to measure volatility
+    let skipped = prices.len() - result.steps.len();
+    let steps = prices[skipped..]
+        .iter()
+        .zip(result.steps)
+        .map(|(price, step)| BacktestStepResponse {
+            timestamp: price.timestamp.and_utc().timestamp(),
+            price: step.price,
+            lp_value: step.lp_value,
+            fees: step.fees,
+            in_range: step.in_range,
+            rebalanced: step.rebalanced,
+        })
+        .collect();
+
+    Ok(Json(BacktestResponse {
+        pool_id: pool.id,
+        fee_rate,
+        fees: result.fees,
+        rebalances: result.rebalances,
+        gas_spent: result.gas_spent,
+        final_lp_value: result.final_lp_value,
+        hodl_value: result.hodl_value,
+        impermanent_loss: result.impermanent_loss,
+        net_pnl: result.net_pnl,
+        pnl_vs_hodl: result.pnl_vs_hodl,
+        time_in_range: result.time_in_range,
+        steps,
+    }))
+}
+
+/// Side of a pool holding a USD stablecoin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UsdSide {
+    A,
+    B,
+}
+
+impl UsdSide {
+    /// `usd` in token B, at `price` token B per token A
+    fn to_token_b(self, usd: f64, price: f64) -> f64 {
+        match self {
+            UsdSide::B => usd,
+            UsdSide::A => usd * price,
+        }
+    }
+}
+
+/// Side of the pool quoted in USD, token B first, or an error for a pair without a stablecoin
+async fn usd_side(database: &DatabaseConnection, pool: &pools::Model) -> AppResult<UsdSide> {
+    for (side, token_id) in [(UsdSide::B, &pool.token_b), (UsdSide::A, &pool.token_a)] {
+        let Some(token_id) = token_id else { continue };
+        let token = Tokens::find_by_id(token_id).one(database).await?;
+        if token.is_some_and(|t| is_usd_stablecoin(&t.symbol)) {
+            return Ok(side);
+        }
+    }
+
+    Err(AppError::BadRequest(
+        "Backtests need a pool with a USD stablecoin side, its volume history is in USD"
+            .to_string(),
+    ))
+}
+
+fn is_usd_stablecoin(symbol: &str) -> bool {
+    USD_STABLECOINS
+        .iter()
+        .any(|usd| usd.eq_ignore_ascii_case(symbol))
+}
+
+/// Liquidity of the stored positions between two prices, in decimal adjusted units
+async fn liquidity_profile(
+    database: &DatabaseConnection,
+    pool_id: &str,
+    (min_price, max_price): (f64, f64),
+    (decimals_a, decimals_b): (u8, u8),
+) -> AppResult<LiquidityProfile> {
+    let positions = Positions::find()
+        .filter(positions::Column::Pool.eq(pool_id))
+        .all(database)
+        .await?
+        .into_iter()
+        .map(|p| {
+            Ok::<_, anyhow::Error>(PositionLiquidity {
+                tick_lower: i32::try_from(p.tick_lower)?,
+                tick_upper: i32::try_from(p.tick_upper)?,
+                liquidity: p.liquidity.parse()?,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let to_tick = |price: f64| {
+        price_to_tick(price, decimals_a, decimals_b)
+            .map_err(|e| AppError::BadRequest(e.to_string()))
+    };
+    let tick_start = to_tick(min_price)?;
+    let tick_end = to_tick(max_price)?.max(tick_start) + 1;
+    let bucket_width = ((tick_end - tick_start) / PROFILE_BUCKETS).max(1) as u32;
+
+    // Amounts aren't used, any price works
+    let sqrt_price =
+        get_sqrt_price_at_tick(tick_start).map_err(|e| AppError::InternalServer(e.to_string()))?;
+    let buckets =
+        liquidity_distribution(&positions, sqrt_price, tick_start, tick_end, bucket_width)
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    // Raw liquidity is sqrt(raw amount A * raw amount B)
+    let scale = 10f64.powf((decimals_a as f64 + decimals_b as f64) / 2.0);
+    let mut steps = Vec::with_capacity(buckets.len() + 1);
+    for bucket in &buckets {
+        let price = tick_to_price(bucket.tick_lower, decimals_a, decimals_b)
+            .map_err(|e| AppError::InternalServer(e.to_string()))?;
+        steps.push((price, bucket.liquidity as f64 / scale));
+    }
+    if let Some(bucket) = buckets.last() {
+        let price = tick_to_price(bucket.tick_upper, decimals_a, decimals_b)
+            .map_err(|e| AppError::InternalServer(e.to_string()))?;
+        steps.push((price, 0.0));
+    }
+
+    Ok(LiquidityProfile::new(steps))
+}
+
+/// Daily USD volume at each price, from the latest snapshot taken by then
+///
+/// Prices older than every snapshot take `current`, the pool's own volume, rather than the
+/// first snapshot's.
+fn daily_volumes(
+    prices: &[pool_prices::Model],
+    snapshots: &[pool_snapshots::Model],
+    current: f64,
+) -> Vec<f64> {
+    let mut taken = 0;
+    prices
+        .iter()
+        .map(|p| {
+            while taken < snapshots.len() && snapshots[taken].created_at <= p.timestamp {
+                taken += 1;
+            }
+            taken
+                .checked_sub(1)
+                .map_or(current, |latest| snapshots[latest].volume_day)
+        })
+        .collect()
+}
+
+fn to_datetime(timestamp: i64) -> AppResult<chrono::NaiveDateTime> {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|d| d.naive_utc())
+        .ok_or_else(|| AppError::BadRequest(format!("Invalid timestamp {}", timestamp)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usd_volume_is_converted_to_token_b() {
+        // APT/USDC at 5 USDC per APT: 100 USD of volume is 100 USDC
+        assert_eq!(UsdSide::B.to_token_b(100.0, 5.0), 100.0);
+        // USDC/APT at 0.2 APT per USDC: 100 USD of volume is 20 APT
+        assert_eq!(UsdSide::A.to_token_b(100.0, 0.2), 20.0);
+    }
+
+    #[test]
+    fn volume_before_the_first_snapshot_is_the_pools() {
+        let at = |hour| {
+            chrono::NaiveDate::from_ymd_opt(2026, 10, 1)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        let prices: Vec<_> = [0, 1, 2, 3, 4]
+            .into_iter()
+            .map(|hour| pool_prices::Model {
+                pool_id: "0xabc".into(),
+                interval: "1h".into(),
+                timestamp: at(hour),
+                price: 1.0,
+            })
+            .collect();
+        let snapshot = |id, hour, volume_day| pool_snapshots::Model {
+            id,
+            pool_id: "0xabc".into(),
+            tvl: 0.0,
+            volume_day,
+            trading_apr: 0.0,
+            bonus_apr: 0.0,
+            created_at: at(hour),
+        };
+        let snapshots = [snapshot(1, 2, 200.0), snapshot(2, 4, 400.0)];
+
+        assert_eq!(
+            daily_volumes(&prices, &snapshots, 50.0),
+            [50.0, 50.0, 200.0, 200.0, 400.0]
+        );
+        assert_eq!(daily_volumes(&prices, &[], 50.0), [50.0; 5]);
+    }
+
+    #[test]
+    fn stablecoins_match_regardless_of_case() {
+        assert!(is_usd_stablecoin("USDC"));
+        assert!(is_usd_stablecoin("USDt"));
+        assert!(!is_usd_stablecoin("APT"));
+        assert!(!is_usd_stablecoin("USDCx"));
+    }
+}
@@ -0,0 +1,13 @@
+pub mod handlers;
+
+use std::sync::Arc;
+
+use axum::{Router, routing::post};
+
+use crate::AppState;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/simulate/il", post(handlers::simulate_impermanent_loss))
+        .route("/simulate/backtest", post(handlers::simulate_backtest))
+}
@@ -1,15 +1,14 @@
 use std::sync::Arc;
 
-use axum::{extract::State, Json};
-use db::entities::{tokens, tokens::Entity as Tokens};
-use sea_orm::{ActiveModelTrait, EntityTrait, QueryOrder, QuerySelect, Set};
-use serde::{Deserialize, Serialize};
-use tapp::api::{api::TappHttpClient};
-use tapp::api::models::TokenListQuery;
 use crate::{
-    errors::{AppError, AppResult},
     AppState,
+    errors::AppResult,
+    routes::jobs::handlers::{JobAccepted, enqueue},
 };
+use axum::{Json, extract::State, http::StatusCode};
+use db::entities::{sea_orm_active_enums::JobKind, tokens, tokens::Entity as Tokens};
+use sea_orm::{EntityTrait, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize)]
 pub struct TokensResponse {
@@ -23,6 +22,26 @@ pub struct TokensQuery {
     pub offset: Option<u64>,
 }
 
+/// Token metadata embedded in pool and position responses
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TokenSummary {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub logo: Option<String>,
+    pub decimals: i32,
+}
+
+impl From<&tokens::Model> for TokenSummary {
+    fn from(token: &tokens::Model) -> Self {
+        Self {
+            symbol: token.symbol.clone(),
+            name: token.name.clone(),
+            logo: token.logo.clone(),
+            decimals: token.decimals,
+        }
+    }
+}
+
 /// GET /tokens - List all tokens
 #[utoipa::path(
     get,
@@ -51,78 +70,19 @@ pub async fn list_tokens(
     Ok(Json(TokensResponse { tokens, count }))
 }
 
-#[derive(Debug, Serialize, utoipa::ToSchema)]
-pub struct RefreshResponse {
-    pub status: String,
-    pub message: String,
-    pub tokens_updated: usize,
-}
-
 /// POST /tokens/refresh - Refresh token list from TAPP API
+///
+/// Queues a job, follow it at `GET /jobs/{id}`.
 #[utoipa::path(
     post,
     path = "/tokens/refresh",
     tag = "tokens",
     responses(
-        (status = 200, description = "Tokens refreshed successfully", body = RefreshResponse)
+        (status = 202, description = "Refresh queued", body = JobAccepted)
     )
 )]
-pub async fn refresh_tokens(State(state): State<Arc<AppState>>) -> AppResult<Json<RefreshResponse>> {
-    let http_client = TappHttpClient::new();
-
-    // Fetch tokens from TAPP API
-    // Note: API has a limit of 30 per page, but we'll fetch 100 to cover current token count
-    let query = TokenListQuery {
-        start_time: None,
-        end_time: None,
-        keyword: None,
-        page: Some(1),
-        page_size: Some(100),
-    };
-
-    let api_tokens = http_client
-        .get_token_list(query)
-        .await
-        .map_err(|e| AppError::InternalServer(format!("Failed to fetch tokens from TAPP API: {}", e)))?;
-
-    // Upsert tokens to database
-    let mut tokens_updated = 0;
-
-    for token in &api_tokens {
-        let token_model = tokens::ActiveModel {
-            id: Set(token.addr.clone()),
-            symbol: Set(token.ticker.clone()),
-            name: Set(Some(token.name.clone())),
-            about: Set(None),
-            logo: Set(Some(token.img.clone())),
-            decimals: Set(token.decimals as i32),
-            updated_at: Set(Some(chrono::Utc::now().naive_utc())),
-        };
-
-        // Insert or update
-        match Tokens::find_by_id(&token.addr).one(&state.database).await? {
-            Some(existing) => {
-                // Update existing token
-                let mut existing: tokens::ActiveModel = existing.into();
-                existing.symbol = Set(token.ticker.clone());
-                existing.name = Set(Some(token.name.clone()));
-                existing.logo = Set(Some(token.img.clone()));
-                existing.decimals = Set(token.decimals as i32);
-                existing.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
-                existing.update(&state.database).await?;
-            }
-            None => {
-                // Insert new token
-                token_model.insert(&state.database).await?;
-            }
-        }
-
-        tokens_updated += 1;
-    }
-
-    Ok(Json(RefreshResponse {
-        status: "success".to_string(),
-        message: format!("Updated {} tokens", tokens_updated),
-        tokens_updated,
-    }))
+pub async fn refresh_tokens(
+    State(state): State<Arc<AppState>>,
+) -> AppResult<(StatusCode, Json<JobAccepted>)> {
+    enqueue(&state, JobKind::RefreshTokens, None).await
 }
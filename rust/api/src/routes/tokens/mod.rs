@@ -1,8 +1,11 @@
 pub mod handlers;
 
-use std::sync::Arc;
-use axum::{Router, routing::{get, post}};
 use crate::AppState;
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use std::sync::Arc;
 
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
@@ -0,0 +1,319 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use chrono::NaiveDateTime;
+use db::entities::{
+    managed_positions, managed_positions::Entity as ManagedPositions, pools::Entity as Pools,
+    sea_orm_active_enums::PositionStatus, tokens, tokens::Entity as Tokens, user_balances,
+    user_balances::Entity as UserBalances, user_movements, user_movements::Entity as UserMovements,
+    users, users::Entity as Users,
+};
+use liquidity_core::address::AccountAddress;
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    auth::extract::SignedIn,
+    errors::{AppError, AppResult},
+    routes::tokens::handlers::TokenSummary,
+};
+
+const DEFAULT_MOVEMENTS_LIMIT: u64 = 50;
+const MAX_MOVEMENTS_LIMIT: u64 = 200;
+
+/// GET /users/:address - User registered with a wallet address
+#[utoipa::path(
+    get,
+    path = "/users/{address}",
+    tag = "users",
+    params(
+        ("address" = String, Path, description = "Wallet address")
+    ),
+    responses(
+        (status = 200, description = "User fetched successfully", body = users::Model),
+        (status = 400, description = "Invalid address"),
+        (status = 401, description = "Missing or invalid session"),
+        (status = 403, description = "Session belongs to another address"),
+        (status = 404, description = "User not found")
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_user(
+    State(state): State<Arc<AppState>>,
+    signed_in: SignedIn,
+    Path(address): Path<String>,
+) -> AppResult<Json<users::Model>> {
+    signed_in.require(&address)?;
+    Ok(Json(find_user(&state.database, &address).await?))
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusFilter {
+    Active,
+    Closed,
+}
+
+impl From<StatusFilter> for PositionStatus {
+    fn from(status: StatusFilter) -> Self {
+        match status {
+            StatusFilter::Active => PositionStatus::Active,
+            StatusFilter::Closed => PositionStatus::Closed,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct PositionsQuery {
+    /// Only return positions with this status
+    pub status: Option<StatusFilter>,
+}
+
+/// Managed position with the metadata of its pool and tokens
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ManagedPositionResponse {
+    #[serde(flatten)]
+    pub position: managed_positions::Model,
+    /// Pool fee, as a percentage
+    pub fee: Option<Decimal>,
+    pub token_a: Option<TokenSummary>,
+    pub token_b: Option<TokenSummary>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ManagedPositionsResponse {
+    pub positions: Vec<ManagedPositionResponse>,
+    pub count: usize,
+}
+
+/// GET /users/:address/positions - Managed positions of a user
+///
+/// Positions come with their pool fee and token metadata. Tokens that haven't been scraped
+/// are left empty.
+#[utoipa::path(
+    get,
+    path = "/users/{address}/positions",
+    tag = "users",
+    params(
+        ("address" = String, Path, description = "Wallet address"),
+        PositionsQuery
+    ),
+    responses(
+        (status = 200, description = "Managed positions fetched successfully", body = ManagedPositionsResponse),
+        (status = 400, description = "Invalid address"),
+        (status = 401, description = "Missing or invalid session"),
+        (status = 403, description = "Session belongs to another address"),
+        (status = 404, description = "User not found")
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_user_positions(
+    State(state): State<Arc<AppState>>,
+    signed_in: SignedIn,
+    Path(address): Path<String>,
+    Query(params): Query<PositionsQuery>,
+) -> AppResult<Json<ManagedPositionsResponse>> {
+    signed_in.require(&address)?;
+    let user = find_user(&state.database, &address).await?;
+
+    let mut query = ManagedPositions::find()
+        .filter(managed_positions::Column::UserId.eq(user.id))
+        .order_by_desc(managed_positions::Column::CreatedAt);
+    if let Some(status) = params.status {
+        query = query.filter(managed_positions::Column::Status.eq(PositionStatus::from(status)));
+    }
+    let rows = query.find_also_related(Pools).all(&state.database).await?;
+
+    let token_ids: Vec<String> = rows
+        .iter()
+        .filter_map(|(_, pool)| pool.as_ref())
+        .flat_map(|pool| [pool.token_a.clone(), pool.token_b.clone()])
+        .flatten()
+        .collect();
+    let tokens: HashMap<String, tokens::Model> = Tokens::find()
+        .filter(tokens::Column::Id.is_in(token_ids))
+        .all(&state.database)
+        .await?
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect();
+    let summary = |id: &Option<String>| {
+        id.as_ref()
+            .and_then(|id| tokens.get(id))
+            .map(TokenSummary::from)
+    };
+
+    let positions: Vec<ManagedPositionResponse> = rows
+        .into_iter()
+        .map(|(position, pool)| ManagedPositionResponse {
+            position,
+            fee: pool.as_ref().map(|p| p.fee),
+            token_a: pool.as_ref().and_then(|p| summary(&p.token_a)),
+            token_b: pool.as_ref().and_then(|p| summary(&p.token_b)),
+        })
+        .collect();
+    let count = positions.len();
+
+    Ok(Json(ManagedPositionsResponse { positions, count }))
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct BalanceResponse {
+    pub token_id: String,
+    /// Raw amount, in the token's smallest unit
+    pub amount: String,
+    pub updated_at: NaiveDateTime,
+    pub token: Option<TokenSummary>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct BalancesResponse {
+    pub balances: Vec<BalanceResponse>,
+    pub count: usize,
+}
+
+/// GET /users/:address/balances - Token balances of a user
+#[utoipa::path(
+    get,
+    path = "/users/{address}/balances",
+    tag = "users",
+    params(
+        ("address" = String, Path, description = "Wallet address")
+    ),
+    responses(
+        (status = 200, description = "Balances fetched successfully", body = BalancesResponse),
+        (status = 400, description = "Invalid address"),
+        (status = 401, description = "Missing or invalid session"),
+        (status = 403, description = "Session belongs to another address"),
+        (status = 404, description = "User not found")
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_user_balances(
+    State(state): State<Arc<AppState>>,
+    signed_in: SignedIn,
+    Path(address): Path<String>,
+) -> AppResult<Json<BalancesResponse>> {
+    signed_in.require(&address)?;
+    let user = find_user(&state.database, &address).await?;
+
+    let balances: Vec<BalanceResponse> = UserBalances::find()
+        .filter(user_balances::Column::UserId.eq(user.id))
+        .order_by_asc(user_balances::Column::TokenId)
+        .find_also_related(Tokens)
+        .all(&state.database)
+        .await?
+        .into_iter()
+        .map(|(balance, token)| BalanceResponse {
+            token_id: balance.token_id,
+            amount: balance.amount,
+            updated_at: balance.updated_at,
+            token: token.as_ref().map(TokenSummary::from),
+        })
+        .collect();
+    let count = balances.len();
+
+    Ok(Json(BalancesResponse { balances, count }))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct MovementsQuery {
+    /// Page size, 50 by default and at most 200
+    pub limit: Option<u64>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<i32>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct MovementsResponse {
+    /// Newest first
+    pub movements: Vec<user_movements::Model>,
+    /// Cursor of the next page, absent on the last one
+    pub next_cursor: Option<i32>,
+}
+
+/// GET /users/:address/movements - Deposits, withdrawals and rebalances of a user
+///
+/// Paginated newest first. Pass the returned `next_cursor` to get the following page, which
+/// stays consistent when new movements are recorded in between.
+#[utoipa::path(
+    get,
+    path = "/users/{address}/movements",
+    tag = "users",
+    params(
+        ("address" = String, Path, description = "Wallet address"),
+        MovementsQuery
+    ),
+    responses(
+        (status = 200, description = "Movements fetched successfully", body = MovementsResponse),
+        (status = 400, description = "Invalid address or limit"),
+        (status = 401, description = "Missing or invalid session"),
+        (status = 403, description = "Session belongs to another address"),
+        (status = 404, description = "User not found")
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_user_movements(
+    State(state): State<Arc<AppState>>,
+    signed_in: SignedIn,
+    Path(address): Path<String>,
+    Query(params): Query<MovementsQuery>,
+) -> AppResult<Json<MovementsResponse>> {
+    let limit = params.limit.unwrap_or(DEFAULT_MOVEMENTS_LIMIT);
+    if limit == 0 || limit > MAX_MOVEMENTS_LIMIT {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_MOVEMENTS_LIMIT
+        )));
+    }
+
+    signed_in.require(&address)?;
+    let user = find_user(&state.database, &address).await?;
+
+    // Ids grow with time, so they order the movements and make a stable cursor
+    let mut query = UserMovements::find()
+        .filter(user_movements::Column::UserId.eq(user.id))
+        .order_by_desc(user_movements::Column::Id);
+    if let Some(cursor) = params.cursor {
+        query = query.filter(user_movements::Column::Id.lt(cursor));
+    }
+
+    // One extra row tells whether there is a next page
+    let mut movements = query.limit(limit + 1).all(&state.database).await?;
+    let next_cursor = if movements.len() as u64 > limit {
+        movements.truncate(limit as usize);
+        movements.last().map(|m| m.id)
+    } else {
+        None
+    };
+
+    Ok(Json(MovementsResponse {
+        movements,
+        next_cursor,
+    }))
+}
+
+/// User registered with `address`.
+///
+/// The web app stores the long form, but rows written before it normalized addresses hold
+/// the wallet's string as given, so the short form and the raw input match as well.
+async fn find_user(database: &DatabaseConnection, address: &str) -> AppResult<users::Model> {
+    let parsed: AccountAddress = address
+        .parse()
+        .map_err(|_| AppError::BadRequest(format!("Invalid address {}", address)))?;
+
+    Users::find()
+        .filter(users::Column::Address.is_in([
+            parsed.to_string(),
+            parsed.to_short_string(),
+            address.to_string(),
+        ]))
+        .one(database)
+        .await?
+        .ok_or(AppError::NotFound)
+}
@@ -0,0 +1,24 @@
+pub mod handlers;
+
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use crate::AppState;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/users/{address}", get(handlers::get_user))
+        .route(
+            "/users/{address}/positions",
+            get(handlers::get_user_positions),
+        )
+        .route(
+            "/users/{address}/balances",
+            get(handlers::get_user_balances),
+        )
+        .route(
+            "/users/{address}/movements",
+            get(handlers::get_user_movements),
+        )
+}
@@ -0,0 +1,315 @@
+//! Periodic refreshes run inside the API.
+//!
+//! Every [`Job`] runs on its own task, following the schedule from [`config::SchedulerSettings`]
+//! plus a random jitter so restarts don't hit TAPP all at once. Interval jobs run once at
+//! startup, cron jobs wait for their first slot. A job never overlaps itself: a run that
+//! outlasts its interval delays the next one. On shutdown the jobs stop waiting, runs in
+//! flight are allowed to finish.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use config::{Schedule, SchedulerSettings, Settings};
+use db::ingest::IngestStats;
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::refresh;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Job {
+    /// Every TAPP pool
+    Pools,
+    /// Positions of the top pools by TVL
+    Positions,
+    /// Every TAPP token
+    Tokens,
+}
+
+impl Job {
+    const ALL: [Job; 3] = [Job::Pools, Job::Positions, Job::Tokens];
+
+    fn schedule(self, settings: &SchedulerSettings) -> Option<&Schedule> {
+        match self {
+            Job::Pools => settings.pools.as_ref(),
+            Job::Positions => settings.positions.as_ref(),
+            Job::Tokens => settings.tokens.as_ref(),
+        }
+    }
+
+    async fn run(self, database: &DatabaseConnection, settings: &Settings) -> Result<IngestStats> {
+        match self {
+            Job::Pools => refresh::pools(database, settings).await,
+            Job::Positions => {
+                let limit = settings.scheduler.positions_top_pools;
+                refresh::top_pool_positions(database, settings, limit).await
+            }
+            Job::Tokens => refresh::tokens(database, settings).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct JobStatus {
+    pub job: Job,
+    /// `every 5m` or `cron <expression>`, missing when the job is disabled
+    pub schedule: Option<String>,
+    pub running: bool,
+    pub last_started_at: Option<NaiveDateTime>,
+    pub last_duration_ms: Option<u64>,
+    /// Error of the last run, cleared by a successful one
+    pub last_error: Option<String>,
+    /// Rows inserted, updated or deleted by the last successful run
+    pub rows_changed: Option<u64>,
+    pub next_run_at: Option<NaiveDateTime>,
+    pub runs: u64,
+    pub failures: u64,
+}
+
+impl JobStatus {
+    fn new(job: Job, schedule: Option<&Schedule>) -> Self {
+        Self {
+            job,
+            schedule: schedule.map(Schedule::to_string),
+            running: false,
+            last_started_at: None,
+            last_duration_ms: None,
+            last_error: None,
+            rows_changed: None,
+            next_run_at: None,
+            runs: 0,
+            failures: 0,
+        }
+    }
+}
+
+/// Status of every job, shared between the job tasks and the `/jobs` handler
+#[derive(Clone)]
+pub struct Scheduler {
+    jobs: Arc<Mutex<Vec<JobStatus>>>,
+}
+
+impl Scheduler {
+    pub fn new(settings: &SchedulerSettings) -> Self {
+        let jobs = Job::ALL
+            .into_iter()
+            .map(|job| {
+                let schedule = settings.enabled.then(|| job.schedule(settings)).flatten();
+                JobStatus::new(job, schedule)
+            })
+            .collect();
+
+        Self {
+            jobs: Arc::new(Mutex::new(jobs)),
+        }
+    }
+
+    pub fn statuses(&self) -> Vec<JobStatus> {
+        self.jobs.lock().expect("scheduler poisoned").clone()
+    }
+
+    /// Start a task per scheduled job, they return once `shutdown` turns true
+    pub fn spawn(
+        &self,
+        database: DatabaseConnection,
+        settings: Settings,
+        shutdown: watch::Receiver<bool>,
+    ) -> Vec<JoinHandle<()>> {
+        if !settings.scheduler.enabled {
+            return Vec::new();
+        }
+
+        let settings = Arc::new(settings);
+        Job::ALL
+            .into_iter()
+            .filter_map(|job| {
+                let schedule = job.schedule(&settings.scheduler)?.clone();
+                let scheduler = self.clone();
+                let database = database.clone();
+                let settings = settings.clone();
+                let shutdown = shutdown.clone();
+                Some(tokio::spawn(async move {
+                    scheduler
+                        .run_job(job, schedule, database, settings, shutdown)
+                        .await
+                }))
+            })
+            .collect()
+    }
+
+    async fn run_job(
+        &self,
+        job: Job,
+        schedule: Schedule,
+        database: DatabaseConnection,
+        settings: Arc<Settings>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        let mut first = true;
+        loop {
+            let wait = match (&schedule, first) {
+                (Schedule::Every(_), true) => Duration::ZERO,
+                _ => schedule.delay_from(Utc::now()),
+            };
+            first = false;
+            let wait = wait.saturating_add(jitter(settings.scheduler.jitter));
+            self.update(job, |status| {
+                status.next_run_at = chrono::Duration::from_std(wait)
+                    .ok()
+                    .map(|wait| (Utc::now() + wait).naive_utc());
+            });
+
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = shutdown.wait_for(|stop| *stop) => return,
+            }
+
+            if !self.try_start(job) {
+                continue;
+            }
+            let started = Instant::now();
+            let result = job.run(&database, &settings).await;
+            self.finish(job, started.elapsed(), &result);
+
+            match result {
+                Ok(stats) => println!("Scheduled {:?} refresh: {}", job, stats),
+                Err(e) => eprintln!("Scheduled {:?} refresh failed: {}", job, e),
+            }
+        }
+    }
+
+    /// Mark `job` as running, false if a run is already in flight
+    fn try_start(&self, job: Job) -> bool {
+        let mut started = false;
+        self.update(job, |status| {
+            if !status.running {
+                status.running = true;
+                status.last_started_at = Some(Utc::now().naive_utc());
+                status.next_run_at = None;
+                started = true;
+            }
+        });
+        started
+    }
+
+    fn finish(&self, job: Job, elapsed: Duration, result: &Result<IngestStats>) {
+        self.update(job, |status| {
+            status.running = false;
+            status.runs += 1;
+            status.last_duration_ms = Some(elapsed.as_millis() as u64);
+            match result {
+                Ok(stats) => {
+                    status.last_error = None;
+                    status.rows_changed = Some(stats.changed());
+                }
+                Err(e) => {
+                    status.failures += 1;
+                    status.last_error = Some(e.to_string());
+                }
+            }
+        });
+    }
+
+    fn update(&self, job: Job, f: impl FnOnce(&mut JobStatus)) {
+        let mut jobs = self.jobs.lock().expect("scheduler poisoned");
+        if let Some(status) = jobs.iter_mut().find(|status| status.job == job) {
+            f(status);
+        }
+    }
+}
+
+/// Random delay between zero and `max`
+fn jitter(max: Duration) -> Duration {
+    let max_ms = max.as_millis() as u64;
+    if max_ms == 0 {
+        return Duration::ZERO;
+    }
+    let mut bytes = [0u8; 8];
+    getrandom::getrandom(&mut bytes).expect("system randomness is available");
+    Duration::from_millis(u64::from_le_bytes(bytes) % (max_ms + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> SchedulerSettings {
+        SchedulerSettings {
+            enabled: true,
+            jitter: Duration::from_secs(30),
+            pools: Some(Schedule::Every(Duration::from_secs(300))),
+            positions: None,
+            positions_top_pools: 10,
+            tokens: Some(Schedule::Every(Duration::from_secs(3600))),
+        }
+    }
+
+    #[test]
+    fn a_job_runs_one_at_a_time() {
+        let scheduler = Scheduler::new(&settings());
+
+        assert!(scheduler.try_start(Job::Pools));
+        assert!(!scheduler.try_start(Job::Pools));
+        // Other jobs are independent
+        assert!(scheduler.try_start(Job::Tokens));
+
+        scheduler.finish(
+            Job::Pools,
+            Duration::from_millis(1500),
+            &Ok(IngestStats {
+                inserted: 2,
+                updated: 3,
+                deleted: 1,
+            }),
+        );
+        let pools = &scheduler.statuses()[0];
+        assert!(!pools.running);
+        assert_eq!(pools.runs, 1);
+        assert_eq!(pools.last_duration_ms, Some(1500));
+        assert_eq!(pools.rows_changed, Some(6));
+        assert!(scheduler.try_start(Job::Pools));
+
+        scheduler.finish(
+            Job::Pools,
+            Duration::ZERO,
+            &Err(anyhow::anyhow!("TAPP down")),
+        );
+        let pools = &scheduler.statuses()[0];
+        assert_eq!(pools.failures, 1);
+        assert_eq!(pools.last_error.as_deref(), Some("TAPP down"));
+        // The rows of the last successful run are kept
+        assert_eq!(pools.rows_changed, Some(6));
+    }
+
+    #[test]
+    fn disabled_jobs_have_no_schedule() {
+        let statuses = Scheduler::new(&settings()).statuses();
+        assert_eq!(statuses[0].schedule.as_deref(), Some("every 5m"));
+        assert_eq!(statuses[1].schedule, None);
+
+        let disabled = SchedulerSettings {
+            enabled: false,
+            ..settings()
+        };
+        assert!(
+            Scheduler::new(&disabled)
+                .statuses()
+                .iter()
+                .all(|status| status.schedule.is_none())
+        );
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(jitter(Duration::from_secs(30)) <= Duration::from_secs(30));
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Workers running the refreshes queued in the `jobs` table.
+//!
+//! Each worker claims one due job at a time and polls for more, handlers wake them up right
+//! after enqueueing. A failed run is retried with exponential backoff until the job is out of
+//! attempts, then it is left `dead` with its last error. On shutdown the workers stop claiming,
+//! a job in flight is allowed to finish.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Result, anyhow};
+use config::Settings;
+use db::{
+    entities::{jobs, sea_orm_active_enums::JobKind},
+    ingest::IngestStats,
+    queue,
+};
+use sea_orm::DatabaseConnection;
+use tokio::{
+    sync::{Notify, watch},
+    task::JoinHandle,
+};
+
+use crate::refresh;
+
+/// Time between polls while the queue is empty, covers jobs enqueued by other processes
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Longest wait between two attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone, Default)]
+pub struct Workers {
+    wake: Arc<Notify>,
+}
+
+impl Workers {
+    /// Have an idle worker look for a job now
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Start `settings.queue.workers` workers, they return once `shutdown` turns true
+    pub fn spawn(
+        &self,
+        database: DatabaseConnection,
+        settings: Settings,
+        shutdown: watch::Receiver<bool>,
+    ) -> Vec<JoinHandle<()>> {
+        let settings = Arc::new(settings);
+        (0..settings.queue.workers)
+            .map(|_| {
+                let workers = self.clone();
+                let database = database.clone();
+                let settings = settings.clone();
+                let shutdown = shutdown.clone();
+                tokio::spawn(async move { workers.work(database, settings, shutdown).await })
+            })
+            .collect()
+    }
+
+    async fn work(
+        &self,
+        database: DatabaseConnection,
+        settings: Arc<Settings>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        while !*shutdown.borrow() {
+            let job = match queue::claim(&database, settings.queue.lease).await {
+                Ok(Some(job)) => job,
+                Ok(None) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                        _ = self.wake.notified() => {}
+                        _ = shutdown.wait_for(|stop| *stop) => {}
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("Failed to claim a job: {}", e);
+                    tokio::select! {
+                        _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                        _ = shutdown.wait_for(|stop| *stop) => {}
+                    }
+                    continue;
+                }
+            };
+
+            let id = job.id;
+            let recorded = match run(&database, &settings, &job).await {
+                Ok(stats) => {
+                    println!("Job {} ({:?}) succeeded: {}", id, job.kind, stats);
+                    queue::succeed(&database, job, stats).await
+                }
+                Err(e) => {
+                    let retry_in = retry_delay(settings.queue.backoff, job.attempts);
+                    eprintln!(
+                        "Job {} ({:?}) failed, attempt {} of {}: {}",
+                        id, job.kind, job.attempts, job.max_attempts, e
+                    );
+                    queue::fail(&database, job, e.to_string(), retry_in).await
+                }
+            };
+            // The lease hands the job to another worker if this update is lost
+            match recorded {
+                Ok(true) => {}
+                Ok(false) => eprintln!(
+                    "Job {} outlived its lease, its outcome was dropped for the newer claim",
+                    id
+                ),
+                Err(e) => eprintln!("Failed to record the outcome of job {}: {}", id, e),
+            }
+        }
+    }
+}
+
+async fn run(
+    database: &DatabaseConnection,
+    settings: &Settings,
+    job: &jobs::Model,
+) -> Result<IngestStats> {
+    let target = || {
+        job.target
+            .as_deref()
+            .ok_or_else(|| anyhow!("{:?} job {} has no target pool", job.kind, job.id))
+    };
+
+    match job.kind {
+        JobKind::RefreshPools => refresh::pools(database, settings).await,
+        JobKind::RefreshPool => refresh::pool(database, settings, target()?).await,
+        JobKind::RefreshPositions => refresh::positions(database, settings, target()?).await,
+        JobKind::RefreshTokens => refresh::tokens(database, settings).await,
+    }
+}
+
+/// Wait before the next attempt of a job that failed its `attempt`-th run
+fn retry_delay(backoff: Duration, attempt: i32) -> Duration {
+    let doublings = attempt.saturating_sub(1).clamp(0, 31) as u32;
+    backoff.saturating_mul(1 << doublings).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_back_off_exponentially() {
+        let backoff = Duration::from_secs(10);
+
+        assert_eq!(retry_delay(backoff, 1), Duration::from_secs(10));
+        assert_eq!(retry_delay(backoff, 2), Duration::from_secs(20));
+        assert_eq!(retry_delay(backoff, 4), Duration::from_secs(80));
+        assert_eq!(retry_delay(backoff, 40), MAX_BACKOFF);
+        assert_eq!(retry_delay(Duration::ZERO, 3), Duration::ZERO);
+    }
+}
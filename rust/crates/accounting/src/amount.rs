@@ -0,0 +1,42 @@
+use anyhow::{Result, bail};
+use liquidity_core::clmm::U256;
+
+/// Parse a raw token amount stored as a decimal string
+///
+/// Fails instead of truncating when the string holds anything but digits or doesn't fit in
+/// 256 bits.
+pub fn parse_amount(value: &str) -> Result<U256> {
+    if value.is_empty() {
+        bail!("Empty amount");
+    }
+
+    U256::from_dec_str(value).map_err(|e| anyhow::anyhow!("Invalid amount {value:?}: {e:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_every_digit() {
+        let large =
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(parse_amount(large).unwrap(), U256::MAX);
+        assert_eq!(parse_amount(large).unwrap().to_string(), large);
+        assert_eq!(parse_amount("0").unwrap(), U256::zero());
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_represent() {
+        assert!(parse_amount("").is_err());
+        assert!(parse_amount("-1").is_err());
+        assert!(parse_amount("1.5").is_err());
+        assert!(parse_amount("1e6").is_err());
+        assert!(
+            parse_amount(
+                "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,547 @@
+//! Share balances and movements, persisted in `vault_shares` and `user_movements`.
+//!
+//! Every movement runs in one database transaction holding a lock on the vault's `pools` row,
+//! so concurrent movements of a vault are applied one after the other on a consistent supply.
+
+use anyhow::{Context, Result, bail};
+use db::entities::{
+    managed_positions::{self, Entity as ManagedPositions},
+    pools::Entity as Pools,
+    sea_orm_active_enums::{MovementType, PositionStatus},
+    user_movements::{self, Entity as UserMovements},
+    vault_shares::{self, Entity as VaultShares},
+};
+use liquidity_core::{
+    address::AccountAddress,
+    clmm::{U256, distribution::PositionLiquidity},
+};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    QueryFilter, QuerySelect, TransactionTrait,
+    sea_query::OnConflict,
+    sqlx::types::chrono::Utc,
+};
+use serde_json::json;
+
+use crate::{
+    amount::parse_amount,
+    shares::{pro_rata, shares_for_deposit},
+    valuation::Holdings,
+};
+
+/// State of a vault that isn't stored in the database
+///
+/// The idle amounts are the owner account's on-chain balances of the pool's two tokens, read
+/// from a fullnode by the caller. `user_balances` mirrors users' own wallets for the app and
+/// is not a source for them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vault {
+    pub pool_id: String,
+    /// Account holding the vault's positions and idle tokens
+    pub owner: AccountAddress,
+    /// Current Q64.64 sqrt price of the pool
+    pub sqrt_price: u128,
+    /// Tokens held by the owner outside of its positions, before the movement
+    pub idle_a: U256,
+    pub idle_b: U256,
+}
+
+/// Tokens received on-chain for a user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deposit {
+    pub tx_hash: String,
+    pub amount_a: U256,
+    pub amount_b: U256,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Minted {
+    /// Value of the deposit in raw units of token B
+    pub value: U256,
+    pub shares: U256,
+    /// Shares held by the user after the deposit
+    pub balance: U256,
+}
+
+/// Tokens owed to a user for the burnt shares, to be paid out on-chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Withdrawal {
+    pub amount_a: U256,
+    pub amount_b: U256,
+    pub value: U256,
+    pub balance: U256,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Valuation {
+    pub holdings: Holdings,
+    /// Value of the holdings in raw units of token B
+    pub total_value: U256,
+    pub total_shares: U256,
+}
+
+pub struct Ledger {
+    database: DatabaseConnection,
+}
+
+impl Ledger {
+    pub fn new(database: DatabaseConnection) -> Self {
+        Self { database }
+    }
+
+    /// Holdings, value and share supply of a vault
+    pub async fn valuation(&self, vault: &Vault) -> Result<Valuation> {
+        valuation(&self.database, vault).await
+    }
+
+    /// Shares of a vault held by a user
+    pub async fn shares_of(&self, user_id: i32, pool_id: &str) -> Result<U256> {
+        balance(&self.database, user_id, pool_id).await
+    }
+
+    /// Mint shares for tokens a user deposited, and record the deposit
+    pub async fn deposit(&self, user_id: i32, vault: &Vault, deposit: &Deposit) -> Result<Minted> {
+        let txn = self.database.begin().await?;
+        lock_vault(&txn, &vault.pool_id).await?;
+        ensure_new_movement(&txn, &deposit.tx_hash).await?;
+
+        let before = valuation(&txn, vault).await?;
+        if before.total_shares.is_zero() && !before.total_value.is_zero() {
+            // The first depositor would be handed tokens nobody has a share of
+            bail!(
+                "Vault of {} holds {} without any share outstanding",
+                vault.pool_id,
+                before.total_value
+            );
+        }
+        let value = Holdings {
+            amount_a: deposit.amount_a,
+            amount_b: deposit.amount_b,
+        }
+        .value(vault.sqrt_price)?;
+        let shares = shares_for_deposit(value, before.total_shares, before.total_value)?;
+        if shares.is_zero() {
+            bail!("Deposit worth {value} is too small to mint a share");
+        }
+
+        let previous = balance(&txn, user_id, &vault.pool_id).await?;
+        let balance = previous + shares;
+        set_balance(&txn, user_id, &vault.pool_id, balance).await?;
+
+        record(
+            &txn,
+            user_id,
+            &deposit.tx_hash,
+            MovementType::Deposit,
+            json!({
+                "poolId": vault.pool_id,
+                "amountA": deposit.amount_a.to_string(),
+                "amountB": deposit.amount_b.to_string(),
+                "value": value.to_string(),
+                "shares": shares.to_string(),
+                "previousBalance": previous.to_string(),
+                "newBalance": balance.to_string(),
+            }),
+        )
+        .await?;
+
+        txn.commit().await?;
+        Ok(Minted {
+            value,
+            shares,
+            balance,
+        })
+    }
+
+    /// Burn shares of a user, and record the withdrawal of their part of each holding
+    pub async fn withdraw(
+        &self,
+        user_id: i32,
+        vault: &Vault,
+        tx_hash: &str,
+        shares: U256,
+    ) -> Result<Withdrawal> {
+        if shares.is_zero() {
+            bail!("Nothing to withdraw");
+        }
+
+        let txn = self.database.begin().await?;
+        lock_vault(&txn, &vault.pool_id).await?;
+        ensure_new_movement(&txn, tx_hash).await?;
+
+        let previous = balance(&txn, user_id, &vault.pool_id).await?;
+        if shares > previous {
+            bail!("User {user_id} holds {previous} shares, cannot burn {shares}");
+        }
+
+        let before = valuation(&txn, vault).await?;
+        let amount_a = pro_rata(before.holdings.amount_a, shares, before.total_shares)?;
+        let amount_b = pro_rata(before.holdings.amount_b, shares, before.total_shares)?;
+        let value = pro_rata(before.total_value, shares, before.total_shares)?;
+
+        let balance = previous - shares;
+        set_balance(&txn, user_id, &vault.pool_id, balance).await?;
+
+        record(
+            &txn,
+            user_id,
+            tx_hash,
+            MovementType::Withdraw,
+            json!({
+                "poolId": vault.pool_id,
+                "amountA": amount_a.to_string(),
+                "amountB": amount_b.to_string(),
+                "value": value.to_string(),
+                "shares": shares.to_string(),
+                "previousBalance": previous.to_string(),
+                "newBalance": balance.to_string(),
+            }),
+        )
+        .await?;
+
+        txn.commit().await?;
+        Ok(Withdrawal {
+            amount_a,
+            amount_b,
+            value,
+            balance,
+        })
+    }
+}
+
+/// Lock the vault's pool row until the transaction ends
+async fn lock_vault(txn: &DatabaseTransaction, pool_id: &str) -> Result<()> {
+    Pools::find_by_id(pool_id)
+        .lock_exclusive()
+        .one(txn)
+        .await?
+        .with_context(|| format!("Pool {pool_id} not found"))?;
+
+    Ok(())
+}
+
+/// Movements are keyed by transaction, whatever their type: recording one twice would mint or
+/// burn twice, and a transaction recorded as a deposit can't be replayed as a withdrawal
+async fn ensure_new_movement(txn: &DatabaseTransaction, tx_hash: &str) -> Result<()> {
+    let existing = UserMovements::find()
+        .filter(user_movements::Column::TxHash.eq(tx_hash))
+        .one(txn)
+        .await?;
+
+    if existing.is_some() {
+        bail!("Transaction {tx_hash} is already recorded");
+    }
+    Ok(())
+}
+
+/// Only the active positions the vault's owner holds count, positions users manage from their
+/// own accounts in the same pool are theirs
+async fn valuation<C: ConnectionTrait>(db: &C, vault: &Vault) -> Result<Valuation> {
+    let positions = ManagedPositions::find()
+        .filter(managed_positions::Column::PoolId.eq(&vault.pool_id))
+        .filter(managed_positions::Column::Owner.eq(vault.owner.to_string()))
+        .filter(managed_positions::Column::Status.eq(PositionStatus::Active))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| {
+            Ok(PositionLiquidity {
+                tick_lower: i32::try_from(p.tick_lower)?,
+                tick_upper: i32::try_from(p.tick_upper)?,
+                liquidity: p
+                    .liquidity
+                    .parse()
+                    .with_context(|| format!("Invalid liquidity on position {}", p.id))?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let holdings =
+        Holdings::from_positions(vault.sqrt_price, &positions, vault.idle_a, vault.idle_b)?;
+
+    let mut total_shares = U256::zero();
+    for row in VaultShares::find()
+        .filter(vault_shares::Column::PoolId.eq(&vault.pool_id))
+        .all(db)
+        .await?
+    {
+        total_shares = total_shares
+            .checked_add(parse_amount(&row.shares)?)
+            .context("Share supply overflows")?;
+    }
+
+    Ok(Valuation {
+        total_value: holdings.value(vault.sqrt_price)?,
+        holdings,
+        total_shares,
+    })
+}
+
+async fn balance<C: ConnectionTrait>(db: &C, user_id: i32, pool_id: &str) -> Result<U256> {
+    match VaultShares::find_by_id((user_id, pool_id.to_string()))
+        .one(db)
+        .await?
+    {
+        Some(row) => parse_amount(&row.shares),
+        None => Ok(U256::zero()),
+    }
+}
+
+async fn set_balance(
+    txn: &DatabaseTransaction,
+    user_id: i32,
+    pool_id: &str,
+    shares: U256,
+) -> Result<()> {
+    VaultShares::insert(vault_shares::ActiveModel {
+        user_id: Set(user_id),
+        pool_id: Set(pool_id.to_string()),
+        shares: Set(shares.to_string()),
+        updated_at: Set(Utc::now().naive_utc()),
+    })
+    .on_conflict(
+        OnConflict::columns([vault_shares::Column::UserId, vault_shares::Column::PoolId])
+            .update_columns([
+                vault_shares::Column::Shares,
+                vault_shares::Column::UpdatedAt,
+            ])
+            .to_owned(),
+    )
+    .exec_without_returning(txn)
+    .await?;
+
+    Ok(())
+}
+
+async fn record(
+    txn: &DatabaseTransaction,
+    user_id: i32,
+    tx_hash: &str,
+    movement_type: MovementType,
+    tx_info: serde_json::Value,
+) -> Result<()> {
+    user_movements::ActiveModel {
+        id: NotSet,
+        user_id: Set(user_id),
+        tx_hash: Set(tx_hash.to_string()),
+        tx_info: Set(Some(tx_info.to_string())),
+        movement_type: Set(movement_type),
+        created_at: Set(Utc::now().naive_utc()),
+    }
+    .insert(txn)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use db::entities::pools;
+    use sea_orm::{
+        DatabaseBackend, MockDatabase, MockExecResult, Transaction,
+        prelude::{DateTime, Decimal},
+    };
+
+    use super::*;
+
+    const POOL: &str = "0xpool";
+    const OWNER: &str = "0x5";
+    /// Sqrt price of 1 (tick 0)
+    const ONE: u128 = 1 << 64;
+
+    fn vault() -> Vault {
+        Vault {
+            pool_id: POOL.into(),
+            owner: OWNER.parse().unwrap(),
+            sqrt_price: ONE,
+            idle_a: U256::from(600),
+            idle_b: U256::from(400),
+        }
+    }
+
+    fn pool() -> pools::Model {
+        pools::Model {
+            id: POOL.into(),
+            token_a: Some("0xa".into()),
+            token_b: Some("0xb".into()),
+            fee: Decimal::new(3, 1),
+            dex: "tapp".into(),
+            position_index: None,
+            updated_at: None,
+            bonus_apr: 0.0,
+            volume_day: 0.0,
+            volume_week: 0.0,
+            volume_month: 0.0,
+            volume_prev_day: 0.0,
+            trading_apr: 0.0,
+            tvl: 0.0,
+        }
+    }
+
+    fn shares(user_id: i32, shares: u64) -> vault_shares::Model {
+        vault_shares::Model {
+            user_id,
+            pool_id: POOL.into(),
+            shares: shares.to_string(),
+            updated_at: DateTime::default(),
+        }
+    }
+
+    fn movement(movement_type: MovementType) -> user_movements::Model {
+        user_movements::Model {
+            id: 1,
+            user_id: 1,
+            tx_hash: "0xtx".into(),
+            tx_info: None,
+            movement_type,
+            created_at: DateTime::default(),
+        }
+    }
+
+    fn written(rows_affected: u64) -> MockExecResult {
+        MockExecResult {
+            last_insert_id: 0,
+            rows_affected,
+        }
+    }
+
+    fn statements(log: &[Transaction]) -> Vec<String> {
+        log.iter()
+            .flat_map(|txn| txn.statements())
+            .map(|statement| statement.sql.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn deposit_mints_shares_in_vault_shares() {
+        let database = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![pool()]])
+            .append_query_results([Vec::<user_movements::Model>::new()])
+            .append_query_results([Vec::<managed_positions::Model>::new()])
+            // 1 000 shares outstanding for the 1 000 idle tokens
+            .append_query_results([vec![shares(2, 1_000)]])
+            .append_query_results([Vec::<vault_shares::Model>::new()])
+            .append_exec_results([written(1)])
+            .append_query_results([vec![movement(MovementType::Deposit)]])
+            .into_connection();
+        let ledger = Ledger::new(database);
+
+        let minted = ledger
+            .deposit(
+                1,
+                &vault(),
+                &Deposit {
+                    tx_hash: "0xtx".into(),
+                    amount_a: U256::from(300),
+                    amount_b: U256::from(200),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(minted.value, U256::from(500));
+        assert_eq!(minted.shares, U256::from(500));
+        assert_eq!(minted.balance, U256::from(500));
+
+        let statements = statements(&ledger.database.into_transaction_log());
+        let lock = statements
+            .iter()
+            .find(|sql| sql.contains(r#"FROM "pools""#))
+            .unwrap();
+        assert!(lock.ends_with("FOR UPDATE"));
+        let positions = statements
+            .iter()
+            .find(|sql| sql.contains(r#"FROM "managed_positions""#))
+            .unwrap();
+        assert!(positions.contains(r#""managed_positions"."owner" = "#));
+        assert!(
+            statements
+                .iter()
+                .any(|sql| sql.contains(r#"INSERT INTO "vault_shares""#))
+        );
+        assert!(
+            statements
+                .iter()
+                .all(|sql| !sql.contains(r#""tokens""#) && !sql.contains(r#""user_balances""#))
+        );
+    }
+
+    #[tokio::test]
+    async fn withdraw_burns_shares_and_pays_each_holding_pro_rata() {
+        let database = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![pool()]])
+            .append_query_results([Vec::<user_movements::Model>::new()])
+            .append_query_results([vec![shares(1, 250)]])
+            .append_query_results([Vec::<managed_positions::Model>::new()])
+            .append_query_results([vec![shares(1, 250), shares(2, 750)]])
+            .append_exec_results([written(1)])
+            .append_query_results([vec![movement(MovementType::Withdraw)]])
+            .into_connection();
+        let ledger = Ledger::new(database);
+
+        let withdrawal = ledger
+            .withdraw(1, &vault(), "0xtx", U256::from(100))
+            .await
+            .unwrap();
+        assert_eq!(withdrawal.amount_a, U256::from(60));
+        assert_eq!(withdrawal.amount_b, U256::from(40));
+        assert_eq!(withdrawal.value, U256::from(100));
+        assert_eq!(withdrawal.balance, U256::from(150));
+    }
+
+    #[tokio::test]
+    async fn recorded_transaction_is_not_applied_twice() {
+        let database = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![pool()]])
+            .append_query_results([vec![movement(MovementType::Withdraw)]])
+            .into_connection();
+        let ledger = Ledger::new(database);
+
+        let error = ledger
+            .withdraw(1, &vault(), "0xtx", U256::from(100))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("already recorded"));
+
+        let statements = statements(&ledger.database.into_transaction_log());
+        assert!(statements.iter().all(|sql| !sql.starts_with("INSERT")));
+    }
+
+    #[tokio::test]
+    async fn transaction_recorded_as_another_movement_is_rejected() {
+        let database = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![pool()]])
+            .append_query_results([vec![movement(MovementType::Withdraw)]])
+            .into_connection();
+        let ledger = Ledger::new(database);
+
+        let deposit = Deposit {
+            tx_hash: "0xtx".into(),
+            amount_a: U256::from(300),
+            amount_b: U256::from(200),
+        };
+        let error = ledger.deposit(1, &vault(), &deposit).await.unwrap_err();
+        assert!(error.to_string().contains("already recorded"));
+
+        let statements = statements(&ledger.database.into_transaction_log());
+        let lookup = statements
+            .iter()
+            .find(|sql| sql.contains(r#"FROM "user_movements""#))
+            .unwrap();
+        let (_, filter) = lookup.split_once(" WHERE ").unwrap();
+        assert!(!filter.contains("movement_type"));
+    }
+
+    #[tokio::test]
+    async fn shares_of_reads_the_vault_row() {
+        let database = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![shares(1, 42)]])
+            .append_query_results([Vec::<vault_shares::Model>::new()])
+            .into_connection();
+        let ledger = Ledger::new(database);
+
+        assert_eq!(ledger.shares_of(1, POOL).await.unwrap(), U256::from(42));
+        assert_eq!(ledger.shares_of(2, POOL).await.unwrap(), U256::zero());
+    }
+}
@@ -0,0 +1,16 @@
+//! Vault share accounting for managed liquidity.
+//!
+//! Each pool is a vault: depositors receive shares worth their part of the vault's managed
+//! positions and idle tokens, and burn them to withdraw. [`shares`] and [`valuation`] hold
+//! the exact integer math, [`ledger`] persists balances and movements.
+//!
+//! Amounts are raw token units stored as decimal strings and handled as [`U256`], they are
+//! never converted to floating point.
+
+pub mod amount;
+pub mod ledger;
+pub mod shares;
+pub mod valuation;
+
+pub use ledger::{Deposit, Ledger, Vault, Withdrawal};
+pub use liquidity_core::clmm::U256;
@@ -0,0 +1,85 @@
+//! Share minting and redemption.
+//!
+//! Every rounding favors the vault: deposits mint shares rounded down and withdrawals pay out
+//! amounts rounded down, so no movement can take value from the other holders.
+
+use liquidity_core::clmm::{MathResult, U256, full_math::mul_div_floor};
+
+/// Shares minted for a deposit worth `value`, given the supply and value of the vault
+/// before the deposit
+///
+/// The first deposit mints one share per unit of value. A vault with shares but no value
+/// can't price new shares and returns an error.
+pub fn shares_for_deposit(value: U256, total_shares: U256, total_value: U256) -> MathResult<U256> {
+    if total_shares.is_zero() {
+        return Ok(value);
+    }
+
+    mul_div_floor(value, total_shares, total_value)
+}
+
+/// Part of `amount` owned by `shares` out of `total_shares`, rounded down
+///
+/// Used both to value shares and to split each holding on withdrawal.
+pub fn pro_rata(amount: U256, shares: U256, total_shares: U256) -> MathResult<U256> {
+    mul_div_floor(amount, shares, total_shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use liquidity_core::clmm::MathError;
+
+    use super::*;
+
+    fn u(value: u64) -> U256 {
+        U256::from(value)
+    }
+
+    #[test]
+    fn first_deposit_mints_its_value() {
+        assert_eq!(
+            shares_for_deposit(u(5_000), U256::zero(), U256::zero()).unwrap(),
+            u(5_000)
+        );
+    }
+
+    #[test]
+    fn later_deposits_mint_at_the_share_price() {
+        // 1_000 shares worth 2_000: each share is worth 2
+        assert_eq!(
+            shares_for_deposit(u(500), u(1_000), u(2_000)).unwrap(),
+            u(250)
+        );
+        // Rounded down, the remainder stays with the vault
+        assert_eq!(shares_for_deposit(u(3), u(1_000), u(2_000)).unwrap(), u(1));
+        assert_eq!(shares_for_deposit(u(1), u(1_000), u(2_000)).unwrap(), u(0));
+    }
+
+    #[test]
+    fn worthless_vault_cannot_price_shares() {
+        assert_eq!(
+            shares_for_deposit(u(500), u(1_000), U256::zero()),
+            Err(MathError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn deposit_then_withdraw_never_gains() {
+        let (total_shares, total_value) = (u(999_999), u(1_234_567));
+        for value in [1u64, 7, 1_000, 99_999] {
+            let minted = shares_for_deposit(u(value), total_shares, total_value).unwrap();
+            let redeemed = pro_rata(total_value + u(value), minted, total_shares + minted).unwrap();
+            assert!(redeemed <= u(value), "{value} redeemed as {redeemed}");
+        }
+    }
+
+    #[test]
+    fn pro_rata_is_exact_on_large_amounts() {
+        let amount = U256::from_dec_str("340282366920938463463374607431768211457").unwrap();
+        assert_eq!(pro_rata(amount, u(1), u(1)).unwrap(), amount);
+        assert_eq!(
+            pro_rata(amount, u(1), u(3)).unwrap().to_string(),
+            "113427455640312821154458202477256070485"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use liquidity_core::clmm::{
+    MathError, MathResult, U256, distribution::PositionLiquidity, full_math::mul_div_floor,
+    liquidity_math::get_amounts_for_ticks,
+};
+
+/// Underlying tokens of a vault, in raw units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Holdings {
+    pub amount_a: U256,
+    pub amount_b: U256,
+}
+
+impl Holdings {
+    /// Tokens withdrawable from `positions` at the current price, plus the idle amounts
+    pub fn from_positions(
+        sqrt_price: u128,
+        positions: &[PositionLiquidity],
+        idle_a: U256,
+        idle_b: U256,
+    ) -> MathResult<Self> {
+        let mut holdings = Holdings {
+            amount_a: idle_a,
+            amount_b: idle_b,
+        };
+
+        for position in positions {
+            let amounts = get_amounts_for_ticks(
+                sqrt_price,
+                position.tick_lower,
+                position.tick_upper,
+                position.liquidity,
+                false,
+            )?;
+            holdings.amount_a = holdings
+                .amount_a
+                .checked_add(U256::from(amounts.amount_a))
+                .ok_or(MathError::Overflow)?;
+            holdings.amount_b = holdings
+                .amount_b
+                .checked_add(U256::from(amounts.amount_b))
+                .ok_or(MathError::Overflow)?;
+        }
+
+        Ok(holdings)
+    }
+
+    /// Value in raw units of token B, rounded down
+    pub fn value(&self, sqrt_price: u128) -> MathResult<U256> {
+        value_in_b(self.amount_a, self.amount_b, sqrt_price)
+    }
+}
+
+/// `amount_a * price + amount_b`, with the price read from a Q64.64 sqrt price
+pub fn value_in_b(amount_a: U256, amount_b: U256, sqrt_price: u128) -> MathResult<U256> {
+    let sqrt_price = U256::from(sqrt_price);
+    let price = sqrt_price
+        .checked_mul(sqrt_price)
+        .ok_or(MathError::Overflow)?;
+    let a_in_b = mul_div_floor(amount_a, price, U256::one() << 128)?;
+
+    a_in_b.checked_add(amount_b).ok_or(MathError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use liquidity_core::clmm::tick_math::get_sqrt_price_at_tick;
+
+    use super::*;
+
+    /// Sqrt price of 1 (tick 0)
+    const ONE: u128 = 1 << 64;
+
+    #[test]
+    fn values_token_a_at_the_price() {
+        // A sqrt price of 2 is a price of 4
+        assert_eq!(
+            value_in_b(U256::from(10), U256::from(5), ONE * 2).unwrap(),
+            U256::from(45)
+        );
+        assert_eq!(
+            value_in_b(U256::from(10), U256::from(5), ONE).unwrap(),
+            U256::from(15)
+        );
+        assert_eq!(value_in_b(U256::zero(), U256::MAX, ONE).unwrap(), U256::MAX);
+        assert!(value_in_b(U256::one(), U256::MAX, ONE).is_err());
+    }
+
+    #[test]
+    fn adds_idle_tokens_to_positions() {
+        let sqrt_price = get_sqrt_price_at_tick(0).unwrap();
+        let positions = [
+            PositionLiquidity {
+                tick_lower: -100,
+                tick_upper: 100,
+                liquidity: 1_000_000,
+            },
+            // Above the price, only token A
+            PositionLiquidity {
+                tick_lower: 100,
+                tick_upper: 200,
+                liquidity: 1_000_000,
+            },
+        ];
+
+        let idle = Holdings::from_positions(sqrt_price, &[], U256::from(7), U256::from(3)).unwrap();
+        assert_eq!(idle.value(sqrt_price).unwrap(), U256::from(10));
+
+        let holdings =
+            Holdings::from_positions(sqrt_price, &positions, U256::from(7), U256::from(3)).unwrap();
+        let in_range = get_amounts_for_ticks(sqrt_price, -100, 100, 1_000_000, false).unwrap();
+        let above = get_amounts_for_ticks(sqrt_price, 100, 200, 1_000_000, false).unwrap();
+        assert_eq!(above.amount_b, 0);
+        assert_eq!(
+            holdings.amount_a,
+            U256::from(7 + in_range.amount_a + above.amount_a)
+        );
+        assert_eq!(holdings.amount_b, U256::from(3 + in_range.amount_b));
+
+        assert_eq!(
+            Holdings::from_positions(sqrt_price, &positions, U256::MAX, U256::zero()),
+            Err(MathError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{Result, bail};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+        }
+    }
+
+    pub fn fullnode_url(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "https://api.mainnet.aptoslabs.com/v1",
+            Network::Testnet => "https://api.testnet.aptoslabs.com/v1",
+        }
+    }
+
+    pub fn hyperion_url(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "https://api.hyperion.xyz/v1/graphql",
+            Network::Testnet => "https://api-testnet.hyperion.xyz/v1/graphql",
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            other => bail!("unknown network {other}, expected mainnet or testnet"),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
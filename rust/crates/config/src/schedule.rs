@@ -0,0 +1,89 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+
+/// When a periodic job runs, either a fixed interval (`30s`, `5m`, `1h`, `1d`) or a cron
+/// expression with a seconds field (`0 */5 * * * *`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    Every(Duration),
+    Cron(Box<cron::Schedule>),
+}
+
+impl Schedule {
+    /// Time to wait from `now` until the next run
+    pub fn delay_from(&self, now: DateTime<Utc>) -> Duration {
+        match self {
+            Schedule::Every(interval) => *interval,
+            Schedule::Cron(cron) => cron
+                .after(&now)
+                .next()
+                .and_then(|next| (next - now).to_std().ok())
+                .unwrap_or(Duration::MAX),
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.contains(' ') {
+            let cron = s
+                .parse()
+                .with_context(|| format!("invalid cron expression {s}"))?;
+            return Ok(Schedule::Cron(Box::new(cron)));
+        }
+
+        let interval = parse_duration(s)?;
+        if interval.is_zero() {
+            bail!("a schedule interval must be longer than zero");
+        }
+        Ok(Schedule::Every(interval))
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Every(interval) => write!(f, "every {}", format_duration(*interval)),
+            Schedule::Cron(cron) => write!(f, "cron {}", cron.source()),
+        }
+    }
+}
+
+/// Parse a duration such as `90s`, `5m`, `1h` or `1d`, a bare number counts seconds
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: u64 = value
+        .parse()
+        .with_context(|| format!("invalid duration {s}, expected e.g. 30s, 5m or 1h"))?;
+
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        other => bail!("unknown duration unit {other} in {s}, expected s, m, h or d"),
+    };
+    let seconds = value
+        .checked_mul(seconds)
+        .with_context(|| format!("duration {s} is too long"))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Print a duration in the largest unit [`parse_duration`] reads back exactly
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0 => "0s".to_string(),
+        s if s % 86_400 == 0 => format!("{}d", s / 86_400),
+        s if s % 3_600 == 0 => format!("{}h", s / 3_600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{s}s"),
+    }
+}
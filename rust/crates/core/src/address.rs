@@ -0,0 +1,96 @@
+//! Aptos account addresses, shared by the API, the keeper and the payload builders.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// 32 bytes Aptos account address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AccountAddress(pub [u8; 32]);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidAddress(pub String);
+
+impl fmt::Display for InvalidAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid account address {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidAddress {}
+
+impl AccountAddress {
+    /// Shortest form, leading zeros dropped (`0x1`), as some wallets report addresses
+    pub fn to_short_string(&self) -> String {
+        let hex = hex::encode(self.0);
+        match hex.trim_start_matches('0') {
+            "" => "0x0".to_string(),
+            trimmed => format!("0x{trimmed}"),
+        }
+    }
+}
+
+impl FromStr for AccountAddress {
+    type Err = InvalidAddress;
+
+    /// Parse a `0x` prefixed hex address, short forms (`0x1`) are zero padded
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix("0x").unwrap_or(s);
+        if hex.is_empty() || hex.len() > 64 {
+            return Err(InvalidAddress(s.to_string()));
+        }
+
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(format!("{hex:0>64}"), &mut bytes)
+            .map_err(|_| InvalidAddress(s.to_string()))?;
+        Ok(AccountAddress(bytes))
+    }
+}
+
+impl fmt::Display for AccountAddress {
+    /// Long form: `0x` and 64 lowercase hex digits
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl Serialize for AccountAddress {
+    /// Fixed length, no length prefix
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AccountAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <[u8; 32]>::deserialize(deserializer).map(AccountAddress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_short_and_full_addresses() {
+        let short: AccountAddress = "0x1".parse().unwrap();
+        assert_eq!(short.0[31], 1);
+        assert!(short.0[..31].iter().all(|b| *b == 0));
+
+        let long = format!("0x{}", "ab".repeat(32));
+        assert_eq!(long.parse::<AccountAddress>().unwrap().to_string(), long);
+
+        assert!("0x".parse::<AccountAddress>().is_err());
+        assert!("0xzz".parse::<AccountAddress>().is_err());
+        assert!(format!("{long}0").parse::<AccountAddress>().is_err());
+    }
+
+    #[test]
+    fn short_form_drops_leading_zeros() {
+        let address: AccountAddress = "0x00ab".parse().unwrap();
+
+        assert_eq!(address.to_short_string(), "0xab");
+        assert_eq!(address.to_string(), format!("0x{:0>64}", "ab"));
+        assert_eq!(AccountAddress([0; 32]).to_short_string(), "0x0");
+    }
+}
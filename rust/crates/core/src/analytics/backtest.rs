@@ -0,0 +1,403 @@
+//! Replay of a price and volume series to evaluate a liquidity range strategy.
+//!
+//! The position is valued with [`ConcentratedPosition`], fees are its share of the pool's
+//! active liquidity applied to the traded volume, and every rebalance closes the position at
+//! the current price, pays the gas cost and reopens the remaining value around that price.
+//! Swap costs of rebalancing and fee compounding are not modeled.
+
+use super::{
+    impermanent_loss::ConcentratedPosition,
+    volatility::{log_returns, std_dev},
+};
+use crate::clmm::{MathError, MathResult};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    /// Same range for the whole backtest
+    Fixed { price_lower: f64, price_upper: f64 },
+    /// ±`k` standard deviations of the price move over `lookback` steps, measured on the
+    /// previous `lookback` steps. The range is reset when the price exits it.
+    ///
+    /// The first `lookback` steps of the series are only used to measure volatility.
+    Sigma { k: f64, lookback: usize },
+    /// ±`width` (a fraction, 0.1 for 10%) around the price, re-centered every `every` steps
+    Recenter { width: f64, every: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacktestConfig {
+    pub strategy: Strategy,
+    /// Value deposited, in token B
+    pub deposit: f64,
+    /// Swap fee as a fraction, 0.003 for a 0.3% pool
+    pub fee_rate: f64,
+    /// Cost of a rebalance, in token B
+    pub gas_cost: f64,
+}
+
+/// Market state at one point of the series
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketStep {
+    /// Price, token B per token A
+    pub price: f64,
+    /// Volume traded since the previous step, in token B
+    pub volume: f64,
+}
+
+/// Liquidity of the other LPs of the pool, as a step function of the price
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LiquidityProfile {
+    /// `(price, liquidity)` sorted by price, each active up to the next price
+    steps: Vec<(f64, f64)>,
+}
+
+impl LiquidityProfile {
+    pub fn new(mut steps: Vec<(f64, f64)>) -> Self {
+        steps.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { steps }
+    }
+
+    pub fn constant(liquidity: f64) -> Self {
+        Self {
+            steps: vec![(0.0, liquidity)],
+        }
+    }
+
+    /// Liquidity active at `price`, zero outside of the profile
+    pub fn at(&self, price: f64) -> f64 {
+        match self.steps.partition_point(|(start, _)| *start <= price) {
+            0 => 0.0,
+            index => self.steps[index - 1].1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacktestStep {
+    pub price: f64,
+    /// Value of the position, fees excluded, in token B
+    pub lp_value: f64,
+    /// Fees earned so far, in token B
+    pub fees: f64,
+    pub in_range: bool,
+    /// Whether the position was moved at this step
+    pub rebalanced: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestResult {
+    /// Fees earned, in token B
+    pub fees: f64,
+    pub rebalances: u32,
+    /// Gas paid for rebalances, in token B
+    pub gas_spent: f64,
+    /// Value of the position at the last price, fees excluded
+    pub final_lp_value: f64,
+    /// Value of the initially deposited tokens at the last price
+    pub hodl_value: f64,
+    /// Loss of the position compared to holding, gas excluded, as a fraction
+    pub impermanent_loss: f64,
+    /// `final_lp_value + fees - deposit`
+    pub net_pnl: f64,
+    /// `final_lp_value + fees - hodl_value`
+    pub pnl_vs_hodl: f64,
+    /// Share of the steps spent in range
+    pub time_in_range: f64,
+    pub steps: Vec<BacktestStep>,
+}
+
+/// Simulate `config` over `market`, earning fees against the liquidity in `profile`
+pub fn run_backtest(
+    config: &BacktestConfig,
+    market: &[MarketStep],
+    profile: &LiquidityProfile,
+) -> MathResult<BacktestResult> {
+    validate(config)?;
+
+    let warmup = match config.strategy {
+        Strategy::Sigma { lookback, .. } => lookback,
+        _ => 0,
+    };
+    if market.len() < warmup + 2 {
+        return Err(MathError::InvalidRange);
+    }
+    if market.iter().any(|m| !(m.price > 0.0 && m.volume >= 0.0)) {
+        return Err(MathError::InvalidRange);
+    }
+
+    let prices: Vec<f64> = market.iter().map(|m| m.price).collect();
+
+    let entry_price = prices[warmup];
+    let (price_lower, price_upper) = range_at(&config.strategy, &prices, warmup)?;
+    let mut position =
+        ConcentratedPosition::from_deposit(price_lower, price_upper, entry_price, config.deposit)?;
+    let (hodl_a, hodl_b) = position.amounts_at(entry_price);
+
+    let mut fees = 0.0;
+    let mut rebalances = 0;
+    let mut gas_spent = 0.0;
+    let mut in_range_steps = 0;
+    let mut last_rebalance = warmup;
+    let mut steps = Vec::with_capacity(market.len() - warmup);
+
+    steps.push(BacktestStep {
+        price: entry_price,
+        lp_value: config.deposit,
+        fees: 0.0,
+        in_range: position.in_range(entry_price),
+        rebalanced: false,
+    });
+
+    for (index, step) in market.iter().enumerate().skip(warmup + 1) {
+        let in_range = position.in_range(step.price);
+        if in_range {
+            in_range_steps += 1;
+
+            let others = profile.at(step.price);
+            let share = position.liquidity / (position.liquidity + others);
+            if share.is_finite() {
+                fees += step.volume * config.fee_rate * share;
+            }
+        }
+
+        let rebalance = match config.strategy {
+            Strategy::Fixed { .. } => false,
+            Strategy::Sigma { .. } => !in_range,
+            Strategy::Recenter { every, .. } => index - last_rebalance >= every,
+        };
+
+        if rebalance {
+            let value = (position.value_at(step.price) - config.gas_cost).max(0.0);
+            let (price_lower, price_upper) = range_at(&config.strategy, &prices, index)?;
+
+            position =
+                ConcentratedPosition::from_deposit(price_lower, price_upper, step.price, value)?;
+            rebalances += 1;
+            gas_spent += config.gas_cost;
+            last_rebalance = index;
+        }
+
+        steps.push(BacktestStep {
+            price: step.price,
+            lp_value: position.value_at(step.price),
+            fees,
+            in_range,
+            rebalanced: rebalance,
+        });
+    }
+
+    let final_price = prices[prices.len() - 1];
+    let final_lp_value = position.value_at(final_price);
+    let hodl_value = hodl_a * final_price + hodl_b;
+    let impermanent_loss = if hodl_value > 0.0 {
+        (final_lp_value + gas_spent) / hodl_value - 1.0
+    } else {
+        0.0
+    };
+
+    Ok(BacktestResult {
+        fees,
+        rebalances,
+        gas_spent,
+        final_lp_value,
+        hodl_value,
+        impermanent_loss,
+        net_pnl: final_lp_value + fees - config.deposit,
+        pnl_vs_hodl: final_lp_value + fees - hodl_value,
+        time_in_range: in_range_steps as f64 / (steps.len() - 1) as f64,
+        steps,
+    })
+}
+
+fn validate(config: &BacktestConfig) -> MathResult<()> {
+    let valid_strategy = match config.strategy {
+        Strategy::Fixed {
+            price_lower,
+            price_upper,
+        } => price_lower > 0.0 && price_lower < price_upper,
+        Strategy::Sigma { k, lookback } => k > 0.0 && lookback >= 2,
+        Strategy::Recenter { width, every } => width > 0.0 && every > 0,
+    };
+
+    if valid_strategy && config.deposit > 0.0 && config.fee_rate >= 0.0 && config.gas_cost >= 0.0 {
+        Ok(())
+    } else {
+        Err(MathError::InvalidRange)
+    }
+}
+
+/// Range the strategy opens at step `index`
+fn range_at(strategy: &Strategy, prices: &[f64], index: usize) -> MathResult<(f64, f64)> {
+    let price = prices[index];
+
+    let half_width = match *strategy {
+        Strategy::Fixed {
+            price_lower,
+            price_upper,
+        } => return Ok((price_lower, price_upper)),
+        Strategy::Sigma { k, lookback } => {
+            let returns = log_returns(&prices[index.saturating_sub(lookback)..=index]);
+            k * std_dev(&returns) * (lookback as f64).sqrt()
+        }
+        Strategy::Recenter { width, .. } => (1.0 + width).ln(),
+    };
+
+    if half_width > 0.0 && half_width.is_finite() {
+        Ok((price * (-half_width).exp(), price * half_width.exp()))
+    } else {
+        // Flat history, no volatility to size the range with
+        Err(MathError::InvalidRange)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(prices: &[f64], volume: f64) -> Vec<MarketStep> {
+        prices
+            .iter()
+            .map(|price| MarketStep {
+                price: *price,
+                volume,
+            })
+            .collect()
+    }
+
+    fn config(strategy: Strategy) -> BacktestConfig {
+        BacktestConfig {
+            strategy,
+            deposit: 1_000.0,
+            fee_rate: 0.003,
+            gas_cost: 1.0,
+        }
+    }
+
+    #[test]
+    fn fixed_range_on_flat_market_only_earns_fees() {
+        let strategy = Strategy::Fixed {
+            price_lower: 90.0,
+            price_upper: 110.0,
+        };
+        let result = run_backtest(
+            &config(strategy),
+            &market(&[100.0; 11], 10_000.0),
+            &LiquidityProfile::constant(0.0),
+        )
+        .unwrap();
+
+        // Sole LP of the pool, so every fee is ours
+        assert!((result.fees - 10.0 * 10_000.0 * 0.003).abs() < 1e-9);
+        assert_eq!(result.rebalances, 0);
+        assert!(result.impermanent_loss.abs() < 1e-12);
+        assert!((result.time_in_range - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn fees_are_shared_with_other_lps() {
+        let strategy = Strategy::Fixed {
+            price_lower: 90.0,
+            price_upper: 110.0,
+        };
+        let alone = run_backtest(
+            &config(strategy),
+            &market(&[100.0; 5], 1_000.0),
+            &LiquidityProfile::constant(0.0),
+        )
+        .unwrap();
+
+        let position = ConcentratedPosition::from_deposit(90.0, 110.0, 100.0, 1_000.0).unwrap();
+        let shared = run_backtest(
+            &config(strategy),
+            &market(&[100.0; 5], 1_000.0),
+            &LiquidityProfile::constant(position.liquidity * 3.0),
+        )
+        .unwrap();
+
+        assert!((shared.fees - alone.fees / 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_fees_out_of_range() {
+        let strategy = Strategy::Fixed {
+            price_lower: 90.0,
+            price_upper: 110.0,
+        };
+        let result = run_backtest(
+            &config(strategy),
+            &market(&[100.0, 120.0, 130.0], 1_000.0),
+            &LiquidityProfile::constant(0.0),
+        )
+        .unwrap();
+
+        assert_eq!(result.fees, 0.0);
+        assert_eq!(result.time_in_range, 0.0);
+        assert!(result.impermanent_loss < 0.0);
+    }
+
+    #[test]
+    fn recenter_rebalances_periodically() {
+        let prices: Vec<f64> = (0..=10).map(|i| 100.0 + i as f64).collect();
+        let strategy = Strategy::Recenter {
+            width: 0.05,
+            every: 3,
+        };
+        let result = run_backtest(
+            &config(strategy),
+            &market(&prices, 0.0),
+            &LiquidityProfile::constant(0.0),
+        )
+        .unwrap();
+
+        assert_eq!(result.rebalances, 3);
+        assert!((result.gas_spent - 3.0).abs() < 1e-12);
+        assert!(result.steps[3].rebalanced);
+        assert!(result.pnl_vs_hodl < 0.0);
+    }
+
+    #[test]
+    fn sigma_range_follows_the_price_out_of_range() {
+        let mut prices: Vec<f64> = (0..24).map(|i| 100.0 + (i % 2) as f64).collect();
+        prices.extend([101.0, 150.0, 151.0]);
+
+        let strategy = Strategy::Sigma {
+            k: 2.0,
+            lookback: 24,
+        };
+        let result = run_backtest(
+            &config(strategy),
+            &market(&prices, 100.0),
+            &LiquidityProfile::constant(0.0),
+        )
+        .unwrap();
+
+        assert_eq!(result.rebalances, 1);
+        assert!(result.steps.last().unwrap().in_range);
+    }
+
+    #[test]
+    fn profile_is_a_step_function() {
+        let profile = LiquidityProfile::new(vec![(2.0, 20.0), (1.0, 10.0), (3.0, 0.0)]);
+
+        assert_eq!(profile.at(0.5), 0.0);
+        assert_eq!(profile.at(1.0), 10.0);
+        assert_eq!(profile.at(2.5), 20.0);
+        assert_eq!(profile.at(10.0), 0.0);
+    }
+
+    #[test]
+    fn rejects_series_shorter_than_warmup() {
+        let strategy = Strategy::Sigma {
+            k: 2.0,
+            lookback: 24,
+        };
+        assert_eq!(
+            run_backtest(
+                &config(strategy),
+                &market(&[100.0; 10], 0.0),
+                &LiquidityProfile::default()
+            ),
+            Err(MathError::InvalidRange)
+        );
+    }
+}
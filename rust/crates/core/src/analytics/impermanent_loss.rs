@@ -0,0 +1,201 @@
+//! Impermanent loss of a concentrated liquidity position compared to holding its tokens.
+//!
+//! Prices are token B per token A, in whatever unit the caller uses for both the range and
+//! the path (raw or decimal adjusted), and values are expressed in token B. Fees aren't
+//! accounted for.
+
+use crate::clmm::{MathError, MathResult};
+
+/// Position of `liquidity` between two prices, in the `f64` domain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConcentratedPosition {
+    pub price_lower: f64,
+    pub price_upper: f64,
+    pub liquidity: f64,
+}
+
+/// Composition and value of a position at a given price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionValue {
+    pub price: f64,
+    pub amount_a: f64,
+    pub amount_b: f64,
+    /// Value of the position, in token B
+    pub lp_value: f64,
+    /// Value of the tokens initially deposited had they been held, in token B
+    pub hodl_value: f64,
+    /// `lp_value / hodl_value - 1`, zero or negative
+    pub impermanent_loss: f64,
+    pub in_range: bool,
+}
+
+impl ConcentratedPosition {
+    /// Position bought with `deposit_value` (in token B) worth of tokens at `entry_price`
+    pub fn from_deposit(
+        price_lower: f64,
+        price_upper: f64,
+        entry_price: f64,
+        deposit_value: f64,
+    ) -> MathResult<Self> {
+        if !(price_lower > 0.0 && price_lower < price_upper && price_upper.is_finite()) {
+            return Err(MathError::InvalidRange);
+        }
+        if !(entry_price > 0.0 && deposit_value >= 0.0) {
+            return Err(MathError::InvalidRange);
+        }
+
+        let unit = ConcentratedPosition {
+            price_lower,
+            price_upper,
+            liquidity: 1.0,
+        };
+        let value_per_liquidity = unit.value_at(entry_price);
+        if value_per_liquidity <= 0.0 {
+            return Err(MathError::DivisionByZero);
+        }
+
+        Ok(ConcentratedPosition {
+            liquidity: deposit_value / value_per_liquidity,
+            ..unit
+        })
+    }
+
+    pub fn in_range(&self, price: f64) -> bool {
+        price >= self.price_lower && price <= self.price_upper
+    }
+
+    /// Token amounts held by the position at `price`.
+    ///
+    /// Below the range the position is fully in token A, above it fully in token B.
+    pub fn amounts_at(&self, price: f64) -> (f64, f64) {
+        let sqrt_lower = self.price_lower.sqrt();
+        let sqrt_upper = self.price_upper.sqrt();
+        let sqrt_price = price.clamp(self.price_lower, self.price_upper).sqrt();
+
+        let amount_a = self.liquidity * (1.0 / sqrt_price - 1.0 / sqrt_upper);
+        let amount_b = self.liquidity * (sqrt_price - sqrt_lower);
+
+        (amount_a, amount_b)
+    }
+
+    pub fn value_at(&self, price: f64) -> f64 {
+        let (amount_a, amount_b) = self.amounts_at(price);
+        amount_a * price + amount_b
+    }
+}
+
+/// Value of `position`, opened at `entry_price`, once the price reaches `price`
+pub fn impermanent_loss(
+    position: &ConcentratedPosition,
+    entry_price: f64,
+    price: f64,
+) -> PositionValue {
+    let (initial_a, initial_b) = position.amounts_at(entry_price);
+    let (amount_a, amount_b) = position.amounts_at(price);
+
+    let lp_value = amount_a * price + amount_b;
+    let hodl_value = initial_a * price + initial_b;
+    let impermanent_loss = if hodl_value > 0.0 {
+        lp_value / hodl_value - 1.0
+    } else {
+        0.0
+    };
+
+    PositionValue {
+        price,
+        amount_a,
+        amount_b,
+        lp_value,
+        hodl_value,
+        impermanent_loss,
+        in_range: position.in_range(price),
+    }
+}
+
+/// Value of `position` at every price of `path`
+pub fn simulate_path(
+    position: &ConcentratedPosition,
+    entry_price: f64,
+    path: &[f64],
+) -> Vec<PositionValue> {
+    path.iter()
+        .map(|price| impermanent_loss(position, entry_price, *price))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn deposit_is_worth_its_value_at_entry() {
+        let position = ConcentratedPosition::from_deposit(80.0, 125.0, 100.0, 1_000.0).unwrap();
+        let value = impermanent_loss(&position, 100.0, 100.0);
+
+        assert_close(value.lp_value, 1_000.0);
+        assert_close(value.impermanent_loss, 0.0);
+        assert!(value.in_range);
+    }
+
+    #[test]
+    fn wide_range_matches_full_range_formula() {
+        // 2 * sqrt(r) / (1 + r) - 1 for a price ratio r
+        let position = ConcentratedPosition::from_deposit(1e-12, 1e12, 1.0, 1.0).unwrap();
+        let value = impermanent_loss(&position, 1.0, 4.0);
+
+        assert!((value.impermanent_loss - (2.0 * 2.0 / 5.0 - 1.0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn concentration_amplifies_loss() {
+        let wide = ConcentratedPosition::from_deposit(50.0, 200.0, 100.0, 1_000.0).unwrap();
+        let narrow = ConcentratedPosition::from_deposit(90.0, 110.0, 100.0, 1_000.0).unwrap();
+
+        let wide_loss = impermanent_loss(&wide, 100.0, 105.0).impermanent_loss;
+        let narrow_loss = impermanent_loss(&narrow, 100.0, 105.0).impermanent_loss;
+
+        assert!(narrow_loss < wide_loss);
+        assert!(wide_loss < 0.0);
+    }
+
+    #[test]
+    fn exiting_the_range_converts_to_one_token() {
+        let position = ConcentratedPosition::from_deposit(90.0, 110.0, 100.0, 1_000.0).unwrap();
+
+        let below = impermanent_loss(&position, 100.0, 50.0);
+        assert!(!below.in_range);
+        assert_close(below.amount_b, 0.0);
+        assert!(below.amount_a > 0.0);
+
+        let above = impermanent_loss(&position, 100.0, 200.0);
+        assert!(!above.in_range);
+        assert_close(above.amount_a, 0.0);
+        // Out of range above, the position's value stops growing
+        assert_close(
+            above.lp_value,
+            impermanent_loss(&position, 100.0, 110.0).lp_value,
+        );
+        assert!(
+            above.impermanent_loss < impermanent_loss(&position, 100.0, 110.0).impermanent_loss
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        assert_eq!(
+            ConcentratedPosition::from_deposit(110.0, 90.0, 100.0, 1.0),
+            Err(MathError::InvalidRange)
+        );
+        assert_eq!(
+            ConcentratedPosition::from_deposit(0.0, 90.0, 100.0, 1.0),
+            Err(MathError::InvalidRange)
+        );
+    }
+}
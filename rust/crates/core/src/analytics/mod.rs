@@ -0,0 +1,8 @@
+//! Statistics over stored market data (price history, pool snapshots).
+//!
+//! Unlike [`crate::clmm`], everything here works on `f64`: the inputs are already lossy
+//! decimal prices and the outputs are meant for display and range sizing.
+
+pub mod backtest;
+pub mod impermanent_loss;
+pub mod volatility;
@@ -0,0 +1,285 @@
+//! Realized volatility of a price series.
+//!
+//! Port of the web app's `volatility.ts`, so the API and the frontend agree on the numbers.
+
+use std::fmt;
+
+const HOURS_PER_YEAR: f64 = 8760.0;
+
+/// Relative change between recent and baseline volatility considered a trend
+const TREND_THRESHOLD: f64 = 0.15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolatilityLevel {
+    Low,
+    Moderate,
+    High,
+}
+
+impl VolatilityLevel {
+    /// Classify an annualized volatility percentage.
+    /// Thresholds are rough estimates for DeFi assets.
+    pub fn from_volatility(volatility: f64) -> Self {
+        if volatility < 30.0 {
+            VolatilityLevel::Low
+        } else if volatility < 60.0 {
+            VolatilityLevel::Moderate
+        } else {
+            VolatilityLevel::High
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VolatilityLevel::Low => "low",
+            VolatilityLevel::Moderate => "moderate",
+            VolatilityLevel::High => "high",
+        }
+    }
+}
+
+impl fmt::Display for VolatilityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolatilityTrend {
+    Increasing,
+    Decreasing,
+    Stable,
+}
+
+impl VolatilityTrend {
+    /// Compare recent volatility to a baseline
+    pub fn from_volatilities(recent: f64, baseline: f64) -> Self {
+        let change = (recent - baseline) / baseline;
+
+        // A zero baseline gives NaN or infinity, which fall through like in the web app
+        if change > TREND_THRESHOLD {
+            VolatilityTrend::Increasing
+        } else if change < -TREND_THRESHOLD {
+            VolatilityTrend::Decreasing
+        } else {
+            VolatilityTrend::Stable
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VolatilityTrend::Increasing => "increasing",
+            VolatilityTrend::Decreasing => "decreasing",
+            VolatilityTrend::Stable => "stable",
+        }
+    }
+}
+
+impl fmt::Display for VolatilityTrend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolatilityAnalysis {
+    /// Annualized volatility over the short window, in percent
+    pub short: f64,
+    /// Annualized volatility over the recent window, in percent
+    pub recent: f64,
+    /// Annualized volatility over the baseline window, in percent
+    pub baseline: f64,
+    pub level: VolatilityLevel,
+    pub trend: VolatilityTrend,
+    pub insight: String,
+}
+
+/// Logarithmic returns between consecutive prices, skipping non-positive prices
+pub fn log_returns(prices: &[f64]) -> Vec<f64> {
+    prices
+        .windows(2)
+        .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+        .map(|w| (w[1] / w[0]).ln())
+        .collect()
+}
+
+/// Annualized volatility, in percent, of returns spanning `hours_in_period` hours
+pub fn annualized_volatility(returns: &[f64], hours_in_period: f64) -> f64 {
+    if returns.len() < 2 || hours_in_period <= 0.0 {
+        return 0.0;
+    }
+
+    std_dev(returns) * (HOURS_PER_YEAR / hours_in_period).sqrt() * 100.0
+}
+
+/// Population standard deviation, zero for fewer than two values
+pub fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+
+    variance.sqrt()
+}
+
+/// Annualized volatility of the last `points` prices sampled every `interval_hours`
+pub fn window_volatility(prices: &[f64], points: usize, interval_hours: f64) -> f64 {
+    let window = &prices[prices.len().saturating_sub(points)..];
+    annualized_volatility(&log_returns(window), window.len() as f64 * interval_hours)
+}
+
+/// User facing summary, e.g. "High volatility - volatility increasing"
+pub fn insight(level: VolatilityLevel, trend: VolatilityTrend) -> String {
+    let level = match level {
+        VolatilityLevel::Low => "Low volatility",
+        VolatilityLevel::Moderate => "Moderate volatility",
+        VolatilityLevel::High => "High volatility",
+    };
+
+    match trend {
+        VolatilityTrend::Stable => format!("{level} - stable conditions"),
+        trend => format!("{level} - volatility {trend}"),
+    }
+}
+
+/// Compare the volatility of the last `window` prices to the last `baseline` ones.
+///
+/// The level is taken from the baseline, the `short` window is only reported. With hourly
+/// prices, a short window of 12, a window of 24 and a baseline covering the whole week this
+/// gives the web app's `hour`, `day`, `week`, `level` and `trend`.
+/// Volatilities are rounded to two decimals.
+pub fn analyze(
+    prices: &[f64],
+    interval_hours: f64,
+    short: usize,
+    window: usize,
+    baseline: usize,
+) -> VolatilityAnalysis {
+    if prices.len() < 2 {
+        return VolatilityAnalysis {
+            short: 0.0,
+            recent: 0.0,
+            baseline: 0.0,
+            level: VolatilityLevel::Low,
+            trend: VolatilityTrend::Stable,
+            insight: "Insufficient data for volatility analysis".to_string(),
+        };
+    }
+
+    let short = window_volatility(prices, short, interval_hours);
+    let recent = window_volatility(prices, window, interval_hours);
+    let baseline = window_volatility(prices, baseline, interval_hours);
+
+    let level = VolatilityLevel::from_volatility(baseline);
+    let trend = VolatilityTrend::from_volatilities(recent, baseline);
+
+    VolatilityAnalysis {
+        short: round2(short),
+        recent: round2(recent),
+        baseline: round2(baseline),
+        level,
+        trend,
+        insight: insight(level, trend),
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_returns_skip_invalid_prices() {
+        let returns = log_returns(&[1.0, 2.0, 0.0, 4.0, 8.0]);
+        assert_eq!(returns.len(), 2);
+        assert!((returns[0] - 2f64.ln()).abs() < 1e-12);
+        assert!((returns[1] - 2f64.ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn constant_returns_have_no_volatility() {
+        let prices: Vec<f64> = (0..48).map(|i| 1.01f64.powi(i)).collect();
+        assert!(window_volatility(&prices, 48, 1.0) < 1e-9);
+    }
+
+    #[test]
+    fn matches_web_app_annualization() {
+        // Alternating +/-1% moves, population std dev of the returns is ~0.01
+        let returns: Vec<f64> = (0..24)
+            .map(|i| if i % 2 == 0 { 0.01 } else { -0.01 })
+            .collect();
+        let expected = 0.01 * (8760.0f64 / 24.0).sqrt() * 100.0;
+        assert!((annualized_volatility(&returns, 24.0) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn classifies_levels_and_trends() {
+        assert_eq!(VolatilityLevel::from_volatility(29.9), VolatilityLevel::Low);
+        assert_eq!(
+            VolatilityLevel::from_volatility(30.0),
+            VolatilityLevel::Moderate
+        );
+        assert_eq!(
+            VolatilityLevel::from_volatility(60.0),
+            VolatilityLevel::High
+        );
+
+        assert_eq!(
+            VolatilityTrend::from_volatilities(116.0, 100.0),
+            VolatilityTrend::Increasing
+        );
+        assert_eq!(
+            VolatilityTrend::from_volatilities(84.0, 100.0),
+            VolatilityTrend::Decreasing
+        );
+        assert_eq!(
+            VolatilityTrend::from_volatilities(110.0, 100.0),
+            VolatilityTrend::Stable
+        );
+        assert_eq!(
+            VolatilityTrend::from_volatilities(0.0, 0.0),
+            VolatilityTrend::Stable
+        );
+    }
+
+    #[test]
+    fn insight_messages() {
+        assert_eq!(
+            insight(VolatilityLevel::Low, VolatilityTrend::Stable),
+            "Low volatility - stable conditions"
+        );
+        assert_eq!(
+            insight(VolatilityLevel::High, VolatilityTrend::Increasing),
+            "High volatility - volatility increasing"
+        );
+    }
+
+    #[test]
+    fn recent_turbulence_is_an_increasing_trend() {
+        let mut prices: Vec<f64> = (0..144).map(|i| 100.0 + (i % 2) as f64 * 0.1).collect();
+        prices.extend((0..24).map(|i| 100.0 + (i % 2) as f64 * 5.0));
+
+        let analysis = analyze(&prices, 1.0, 12, 24, prices.len());
+        assert_eq!(analysis.trend, VolatilityTrend::Increasing);
+        assert!(analysis.recent > analysis.baseline);
+        // The web app's `hour`, last 12 prices
+        assert_eq!(analysis.short, round2(window_volatility(&prices, 12, 1.0)));
+    }
+
+    #[test]
+    fn too_few_prices() {
+        let analysis = analyze(&[1.0], 1.0, 12, 24, 168);
+        assert_eq!(analysis.short, 0.0);
+        assert_eq!(analysis.recent, 0.0);
+        assert_eq!(
+            analysis.insight,
+            "Insufficient data for volatility analysis"
+        );
+    }
+}
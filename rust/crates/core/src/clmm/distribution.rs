@@ -0,0 +1,261 @@
+//! Active liquidity histogram built from a pool's positions.
+
+use std::collections::BTreeMap;
+
+use primitive_types::U256;
+
+use super::{
+    MathError, MathResult,
+    full_math::to_u128,
+    liquidity_math::get_amounts_for_ticks,
+    tick_math::{MAX_TICK, MIN_TICK, align_tick_down},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionLiquidity {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+}
+
+/// Liquidity over `[tick_lower, tick_upper)`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LiquidityBucket {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    /// Active liquidity averaged over the ticks of the bucket
+    pub liquidity: u128,
+    /// Token A locked in the bucket at the current price
+    pub amount_a: u128,
+    /// Token B locked in the bucket at the current price
+    pub amount_b: u128,
+}
+
+/// Liquidity added and removed when crossing a tick upwards
+#[derive(Debug, Clone, Copy, Default)]
+struct TickDelta {
+    added: u128,
+    removed: u128,
+}
+
+impl TickDelta {
+    /// Active liquidity once the tick is crossed upwards
+    fn cross(&self, active: u128) -> MathResult<u128> {
+        active
+            .checked_add(self.added)
+            .and_then(|active| active.checked_sub(self.removed))
+            .ok_or(MathError::Overflow)
+    }
+}
+
+/// Bucket the active liquidity of `positions` between `tick_start` and `tick_end`.
+///
+/// Buckets are `bucket_width` ticks wide and aligned on multiples of the width, so the first
+/// and last buckets may extend past the requested bounds (but never past the tick range).
+/// A width wider than the whole tick range is rejected.
+pub fn liquidity_distribution(
+    positions: &[PositionLiquidity],
+    sqrt_price_current: u128,
+    tick_start: i32,
+    tick_end: i32,
+    bucket_width: u32,
+) -> MathResult<Vec<LiquidityBucket>> {
+    let width = i32::try_from(bucket_width).map_err(|_| MathError::InvalidRange)?;
+    if tick_start >= tick_end || width == 0 || width > MAX_TICK - MIN_TICK {
+        return Err(MathError::InvalidRange);
+    }
+
+    let mut deltas: BTreeMap<i32, TickDelta> = BTreeMap::new();
+    for position in positions {
+        if position.liquidity == 0 || position.tick_lower >= position.tick_upper {
+            continue;
+        }
+        let lower = deltas.entry(position.tick_lower).or_default();
+        lower.added = lower
+            .added
+            .checked_add(position.liquidity)
+            .ok_or(MathError::Overflow)?;
+        let upper = deltas.entry(position.tick_upper).or_default();
+        upper.removed = upper
+            .removed
+            .checked_add(position.liquidity)
+            .ok_or(MathError::Overflow)?;
+    }
+
+    let start = align_tick_down(tick_start, bucket_width).max(MIN_TICK);
+    let end = tick_end.min(MAX_TICK);
+
+    // Liquidity already active when entering the first bucket
+    let mut active: u128 = 0;
+    for delta in deltas.range(..=start).map(|(_, d)| d) {
+        active = delta.cross(active)?;
+    }
+
+    let mut buckets = Vec::new();
+    let mut bucket_lower = start;
+    while bucket_lower < end {
+        let bucket_upper = bucket_lower.saturating_add(width).min(MAX_TICK);
+
+        let mut bucket = LiquidityBucket {
+            tick_lower: bucket_lower,
+            tick_upper: bucket_upper,
+            ..Default::default()
+        };
+        let mut weighted = U256::zero();
+
+        // Split the bucket at every tick where liquidity changes
+        let crossings = deltas
+            .range(bucket_lower + 1..bucket_upper)
+            .map(|(tick, delta)| (*tick, Some(*delta)))
+            .chain(std::iter::once((bucket_upper, None)));
+
+        let mut segment_lower = bucket_lower;
+        for (segment_upper, delta) in crossings {
+            if active > 0 {
+                let amounts = get_amounts_for_ticks(
+                    sqrt_price_current,
+                    segment_lower,
+                    segment_upper,
+                    active,
+                    false,
+                )?;
+                bucket.amount_a += amounts.amount_a;
+                bucket.amount_b += amounts.amount_b;
+                weighted += U256::from(active) * U256::from(segment_upper - segment_lower);
+            }
+            if let Some(delta) = delta {
+                active = delta.cross(active)?;
+            }
+            segment_lower = segment_upper;
+        }
+
+        // Crossing into the next bucket
+        if let Some(delta) = deltas.get(&bucket_upper) {
+            active = delta.cross(active)?;
+        }
+
+        bucket.liquidity = to_u128(weighted / U256::from(bucket_upper - bucket_lower))?;
+        buckets.push(bucket);
+        bucket_lower = bucket_upper;
+    }
+
+    Ok(buckets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clmm::{liquidity_math::get_amounts_for_ticks, tick_math::get_sqrt_price_at_tick};
+
+    fn position(tick_lower: i32, tick_upper: i32, liquidity: u128) -> PositionLiquidity {
+        PositionLiquidity {
+            tick_lower,
+            tick_upper,
+            liquidity,
+        }
+    }
+
+    #[test]
+    fn stacks_overlapping_positions() {
+        let positions = [position(-100, 100, 1_000), position(0, 200, 500)];
+        let current = get_sqrt_price_at_tick(0).unwrap();
+
+        let buckets = liquidity_distribution(&positions, current, -200, 300, 100).unwrap();
+        let liquidity: Vec<(i32, u128)> = buckets
+            .iter()
+            .map(|b| (b.tick_lower, b.liquidity))
+            .collect();
+
+        assert_eq!(
+            liquidity,
+            vec![(-200, 0), (-100, 1_000), (0, 1_500), (100, 500), (200, 0)]
+        );
+    }
+
+    #[test]
+    fn averages_liquidity_changing_inside_a_bucket() {
+        let positions = [position(-50, 50, 1_000)];
+        let current = get_sqrt_price_at_tick(0).unwrap();
+
+        let buckets = liquidity_distribution(&positions, current, -100, 100, 100).unwrap();
+
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].liquidity, 500);
+        assert_eq!(buckets[1].liquidity, 500);
+    }
+
+    #[test]
+    fn splits_amounts_around_the_current_price() {
+        let positions = [position(-100, 100, 1_000_000_000)];
+        let current = get_sqrt_price_at_tick(0).unwrap();
+
+        let buckets = liquidity_distribution(&positions, current, -100, 100, 100).unwrap();
+
+        // Below the price only token B is left, above only token A
+        assert_eq!(buckets[0].amount_a, 0);
+        assert!(buckets[0].amount_b > 0);
+        assert!(buckets[1].amount_a > 0);
+        assert_eq!(buckets[1].amount_b, 0);
+
+        let total = get_amounts_for_ticks(current, -100, 100, 1_000_000_000, false).unwrap();
+        let amount_a: u128 = buckets.iter().map(|b| b.amount_a).sum();
+        let amount_b: u128 = buckets.iter().map(|b| b.amount_b).sum();
+        assert!(total.amount_a - amount_a <= 1);
+        assert!(total.amount_b - amount_b <= 1);
+    }
+
+    #[test]
+    fn counts_positions_opened_before_the_window() {
+        let positions = [position(-10_000, 10_000, 42)];
+        let current = get_sqrt_price_at_tick(0).unwrap();
+
+        let buckets = liquidity_distribution(&positions, current, 0, 20, 10).unwrap();
+
+        assert!(buckets.iter().all(|b| b.liquidity == 42));
+    }
+
+    #[test]
+    fn rejects_buckets_wider_than_the_tick_range() {
+        let current = get_sqrt_price_at_tick(0).unwrap();
+
+        for width in [
+            (MAX_TICK - MIN_TICK) as u32 + 1,
+            i32::MAX as u32 + 1,
+            u32::MAX,
+        ] {
+            assert_eq!(
+                liquidity_distribution(&[], current, -100, 100, width),
+                Err(MathError::InvalidRange)
+            );
+        }
+        assert!(
+            liquidity_distribution(&[], current, -100, 100, (MAX_TICK - MIN_TICK) as u32).is_ok()
+        );
+    }
+
+    #[test]
+    fn reports_overflowing_liquidity() {
+        let current = get_sqrt_price_at_tick(0).unwrap();
+
+        // Summed on the same tick
+        let same_tick = [position(-10, 10, u128::MAX), position(-10, 20, 1)];
+        assert_eq!(
+            liquidity_distribution(&same_tick, current, 0, 10, 10),
+            Err(MathError::Overflow)
+        );
+        // Stacked while crossing ticks
+        let stacked = [position(-20, -10, u128::MAX), position(-15, -5, 1)];
+        assert_eq!(
+            liquidity_distribution(&stacked, current, 0, 10, 10),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn rejects_empty_window() {
+        assert_eq!(
+            liquidity_distribution(&[], 1 << 64, 10, 10, 1),
+            Err(MathError::InvalidRange)
+        );
+    }
+}
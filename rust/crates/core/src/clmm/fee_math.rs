@@ -0,0 +1,135 @@
+//! Fee growth accounting.
+//!
+//! Fee growth values are Q64.64 fees per unit of liquidity and are allowed to overflow, so
+//! every difference is computed with wrapping arithmetic like the on-chain code does.
+
+use primitive_types::U256;
+
+/// Fee growth accumulated on the far side of a tick, per token
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeGrowth {
+    pub a: u128,
+    pub b: u128,
+}
+
+impl FeeGrowth {
+    fn wrapping_sub(self, other: FeeGrowth) -> FeeGrowth {
+        FeeGrowth {
+            a: self.a.wrapping_sub(other.a),
+            b: self.b.wrapping_sub(other.b),
+        }
+    }
+}
+
+/// Fee growth inside `[tick_lower, tick_upper)` given the pool's global fee growth and the
+/// `fee_growth_outside` of both boundary ticks.
+pub fn get_fee_growth_inside(
+    tick_current: i32,
+    tick_lower: i32,
+    fee_growth_outside_lower: FeeGrowth,
+    tick_upper: i32,
+    fee_growth_outside_upper: FeeGrowth,
+    fee_growth_global: FeeGrowth,
+) -> FeeGrowth {
+    let below = if tick_current >= tick_lower {
+        fee_growth_outside_lower
+    } else {
+        fee_growth_global.wrapping_sub(fee_growth_outside_lower)
+    };
+
+    let above = if tick_current < tick_upper {
+        fee_growth_outside_upper
+    } else {
+        fee_growth_global.wrapping_sub(fee_growth_outside_upper)
+    };
+
+    fee_growth_global.wrapping_sub(below).wrapping_sub(above)
+}
+
+/// Fees earned by `liquidity` since the fee growth inside was last checkpointed
+pub fn get_fees_owed(
+    liquidity: u128,
+    fee_growth_inside: u128,
+    fee_growth_inside_last: u128,
+) -> u64 {
+    let delta = fee_growth_inside.wrapping_sub(fee_growth_inside_last);
+    // Fees owed are truncated to u64 on-chain as well
+    ((U256::from(liquidity) * U256::from(delta)) >> 64).low_u64()
+}
+
+/// Fees owed for both tokens of a position
+pub fn get_position_fees(
+    liquidity: u128,
+    fee_growth_inside: FeeGrowth,
+    fee_growth_inside_last: FeeGrowth,
+) -> (u64, u64) {
+    (
+        get_fees_owed(liquidity, fee_growth_inside.a, fee_growth_inside_last.a),
+        get_fees_owed(liquidity, fee_growth_inside.b, fee_growth_inside_last.b),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: u128 = 1 << 64;
+
+    fn growth(a: u128, b: u128) -> FeeGrowth {
+        FeeGrowth { a, b }
+    }
+
+    #[test]
+    fn current_tick_inside_range() {
+        let inside = get_fee_growth_inside(
+            0,
+            -10,
+            growth(2 * ONE, ONE),
+            10,
+            growth(3 * ONE, ONE),
+            growth(10 * ONE, 4 * ONE),
+        );
+        assert_eq!(inside, growth(5 * ONE, 2 * ONE));
+    }
+
+    #[test]
+    fn current_tick_below_range() {
+        // Everything below the lower tick happened outside the range
+        let inside = get_fee_growth_inside(
+            -20,
+            -10,
+            growth(6 * ONE, 0),
+            10,
+            growth(ONE, 0),
+            growth(10 * ONE, 0),
+        );
+        assert_eq!(inside, growth(5 * ONE, 0));
+    }
+
+    #[test]
+    fn current_tick_above_range() {
+        let inside = get_fee_growth_inside(
+            20,
+            -10,
+            growth(2 * ONE, 0),
+            10,
+            growth(7 * ONE, 0),
+            growth(10 * ONE, 0),
+        );
+        assert_eq!(inside, growth(5 * ONE, 0));
+    }
+
+    #[test]
+    fn wrapping_growth_still_yields_fees() {
+        let last = u128::MAX - ONE + 1;
+        let now = ONE;
+        // Growth went from -1 to +1 (mod 2^128): two units of fees per unit of liquidity
+        assert_eq!(get_fees_owed(1_000, now, last), 2_000);
+    }
+
+    #[test]
+    fn fees_for_both_tokens() {
+        let fees = get_position_fees(500, growth(3 * ONE, ONE / 2), growth(ONE, 0));
+        assert_eq!(fees, (1_000, 250));
+    }
+}
@@ -0,0 +1,87 @@
+//! Overflow-free multiplication and division helpers.
+
+use primitive_types::{U256, U512};
+
+use super::{MathError, MathResult};
+
+/// `a * b / denominator`, rounded down, with a 512-bit intermediate product
+pub fn mul_div_floor(a: U256, b: U256, denominator: U256) -> MathResult<U256> {
+    mul_div(a, b, denominator, false)
+}
+
+/// `a * b / denominator`, rounded up, with a 512-bit intermediate product
+pub fn mul_div_ceil(a: U256, b: U256, denominator: U256) -> MathResult<U256> {
+    mul_div(a, b, denominator, true)
+}
+
+pub fn mul_div(a: U256, b: U256, denominator: U256, round_up: bool) -> MathResult<U256> {
+    if denominator.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+
+    let (quotient, remainder) = a.full_mul(b).div_mod(U512::from(denominator));
+    let quotient = if round_up && !remainder.is_zero() {
+        quotient + U512::one()
+    } else {
+        quotient
+    };
+
+    U256::try_from(quotient).map_err(|_| MathError::Overflow)
+}
+
+/// `(a * b) >> shift`, rounded down
+pub fn mul_shr(a: u128, b: u128, shift: u8) -> MathResult<u128> {
+    to_u128((U256::from(a) * U256::from(b)) >> shift)
+}
+
+pub fn to_u128(value: U256) -> MathResult<u128> {
+    if value > U256::from(u128::MAX) {
+        return Err(MathError::Overflow);
+    }
+    Ok(value.as_u128())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_rounds_in_requested_direction() {
+        let a = U256::from(10u8);
+        let b = U256::from(10u8);
+        let d = U256::from(3u8);
+
+        assert_eq!(mul_div_floor(a, b, d).unwrap(), U256::from(33u8));
+        assert_eq!(mul_div_ceil(a, b, d).unwrap(), U256::from(34u8));
+        assert_eq!(
+            mul_div_ceil(a, b, U256::from(5u8)).unwrap(),
+            U256::from(20u8)
+        );
+    }
+
+    #[test]
+    fn mul_div_survives_overflowing_product() {
+        let result = mul_div_floor(U256::MAX, U256::MAX, U256::MAX).unwrap();
+        assert_eq!(result, U256::MAX);
+    }
+
+    #[test]
+    fn mul_div_reports_overflow_and_zero_division() {
+        assert_eq!(
+            mul_div_floor(U256::MAX, U256::from(2u8), U256::one()),
+            Err(MathError::Overflow)
+        );
+        assert_eq!(
+            mul_div_floor(U256::one(), U256::one(), U256::zero()),
+            Err(MathError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn mul_shr_matches_fixed_point_product() {
+        let one = 1u128 << 64;
+        assert_eq!(mul_shr(one, one, 64).unwrap(), one);
+        assert_eq!(mul_shr(3 * one, one / 2, 64).unwrap(), 3 * one / 2);
+        assert_eq!(mul_shr(u128::MAX, u128::MAX, 64), Err(MathError::Overflow));
+    }
+}
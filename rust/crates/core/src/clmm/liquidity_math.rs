@@ -0,0 +1,266 @@
+//! Liquidity ↔ token amount conversions for a price range.
+//!
+//! Token A is the base token: holding only A means the price is below the range, holding only
+//! B means it is above. All sqrt prices are Q64.64.
+
+use primitive_types::U256;
+
+use super::{
+    MathError, MathResult,
+    full_math::{mul_div, to_u128},
+    tick_math::get_sqrt_price_at_tick,
+};
+
+/// Token amounts backing some liquidity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenAmounts {
+    pub amount_a: u128,
+    pub amount_b: u128,
+}
+
+fn ordered(sqrt_price_0: u128, sqrt_price_1: u128) -> (u128, u128) {
+    if sqrt_price_0 <= sqrt_price_1 {
+        (sqrt_price_0, sqrt_price_1)
+    } else {
+        (sqrt_price_1, sqrt_price_0)
+    }
+}
+
+/// Amount of token A between two sqrt prices: `L * (√Pb - √Pa) / (√Pa * √Pb)`
+pub fn get_delta_a(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> MathResult<u128> {
+    let (lower, upper) = ordered(sqrt_price_0, sqrt_price_1);
+    if liquidity == 0 || lower == upper {
+        return Ok(0);
+    }
+
+    let numerator = U256::from(liquidity) << 64;
+    let denominator = U256::from(lower) * U256::from(upper);
+    to_u128(mul_div(
+        numerator,
+        U256::from(upper - lower),
+        denominator,
+        round_up,
+    )?)
+}
+
+/// Amount of token B between two sqrt prices: `L * (√Pb - √Pa)`
+pub fn get_delta_b(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> MathResult<u128> {
+    let (lower, upper) = ordered(sqrt_price_0, sqrt_price_1);
+    if liquidity == 0 || lower == upper {
+        return Ok(0);
+    }
+
+    let product = U256::from(liquidity) * U256::from(upper - lower);
+    let mut amount = product >> 64;
+    if round_up && !(product & U256::from(u64::MAX)).is_zero() {
+        amount += U256::one();
+    }
+    to_u128(amount)
+}
+
+/// Token amounts held by `liquidity` in `[sqrt_price_lower, sqrt_price_upper]` at the current price
+pub fn get_amounts_for_liquidity(
+    sqrt_price_current: u128,
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> MathResult<TokenAmounts> {
+    if sqrt_price_lower >= sqrt_price_upper {
+        return Err(MathError::InvalidRange);
+    }
+
+    let amounts = if sqrt_price_current <= sqrt_price_lower {
+        TokenAmounts {
+            amount_a: get_delta_a(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?,
+            amount_b: 0,
+        }
+    } else if sqrt_price_current < sqrt_price_upper {
+        TokenAmounts {
+            amount_a: get_delta_a(sqrt_price_current, sqrt_price_upper, liquidity, round_up)?,
+            amount_b: get_delta_b(sqrt_price_lower, sqrt_price_current, liquidity, round_up)?,
+        }
+    } else {
+        TokenAmounts {
+            amount_a: 0,
+            amount_b: get_delta_b(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?,
+        }
+    };
+
+    Ok(amounts)
+}
+
+/// Same as [`get_amounts_for_liquidity`] with the range given in ticks
+pub fn get_amounts_for_ticks(
+    sqrt_price_current: u128,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+    round_up: bool,
+) -> MathResult<TokenAmounts> {
+    get_amounts_for_liquidity(
+        sqrt_price_current,
+        get_sqrt_price_at_tick(tick_lower)?,
+        get_sqrt_price_at_tick(tick_upper)?,
+        liquidity,
+        round_up,
+    )
+}
+
+/// Liquidity provided by `amount_a` of token A over a range, rounded down
+pub fn get_liquidity_for_amount_a(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    amount_a: u128,
+) -> MathResult<u128> {
+    let (lower, upper) = ordered(sqrt_price_0, sqrt_price_1);
+    if lower == upper {
+        return Err(MathError::InvalidRange);
+    }
+
+    let product = U256::from(lower) * U256::from(upper);
+    let denominator = U256::from(upper - lower) << 64;
+    to_u128(mul_div(U256::from(amount_a), product, denominator, false)?)
+}
+
+/// Liquidity provided by `amount_b` of token B over a range, rounded down
+pub fn get_liquidity_for_amount_b(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    amount_b: u128,
+) -> MathResult<u128> {
+    let (lower, upper) = ordered(sqrt_price_0, sqrt_price_1);
+    if lower == upper {
+        return Err(MathError::InvalidRange);
+    }
+
+    to_u128((U256::from(amount_b) << 64) / U256::from(upper - lower))
+}
+
+/// Largest liquidity that can be minted from the given amounts at the current price
+pub fn get_liquidity_for_amounts(
+    sqrt_price_current: u128,
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    amount_a: u128,
+    amount_b: u128,
+) -> MathResult<u128> {
+    if sqrt_price_lower >= sqrt_price_upper {
+        return Err(MathError::InvalidRange);
+    }
+
+    if sqrt_price_current <= sqrt_price_lower {
+        get_liquidity_for_amount_a(sqrt_price_lower, sqrt_price_upper, amount_a)
+    } else if sqrt_price_current < sqrt_price_upper {
+        let from_a = get_liquidity_for_amount_a(sqrt_price_current, sqrt_price_upper, amount_a)?;
+        let from_b = get_liquidity_for_amount_b(sqrt_price_lower, sqrt_price_current, amount_b)?;
+        Ok(from_a.min(from_b))
+    } else {
+        get_liquidity_for_amount_b(sqrt_price_lower, sqrt_price_upper, amount_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: u128 = 1 << 64;
+
+    #[test]
+    fn deltas_at_unit_prices() {
+        // Between √P = 1 and √P = 2: ΔA = L * (2 - 1) / 2, ΔB = L * (2 - 1)
+        assert_eq!(get_delta_a(ONE, 2 * ONE, 1_000, false).unwrap(), 500);
+        assert_eq!(get_delta_b(ONE, 2 * ONE, 1_000, false).unwrap(), 1_000);
+        // Argument order doesn't matter
+        assert_eq!(get_delta_a(2 * ONE, ONE, 1_000, false).unwrap(), 500);
+    }
+
+    #[test]
+    fn deltas_round_in_requested_direction() {
+        // ΔA = 1001 / 2 = 500.5
+        assert_eq!(get_delta_a(ONE, 2 * ONE, 1_001, false).unwrap(), 500);
+        assert_eq!(get_delta_a(ONE, 2 * ONE, 1_001, true).unwrap(), 501);
+        // ΔB = 3 * 0.5 = 1.5
+        assert_eq!(get_delta_b(ONE, ONE + ONE / 2, 3, false).unwrap(), 1);
+        assert_eq!(get_delta_b(ONE, ONE + ONE / 2, 3, true).unwrap(), 2);
+    }
+
+    #[test]
+    fn amounts_depend_on_current_price_position() {
+        let (lower, upper) = (ONE, 4 * ONE);
+        let liquidity = 1_200;
+
+        let below = get_amounts_for_liquidity(ONE / 2, lower, upper, liquidity, false).unwrap();
+        assert_eq!(
+            below,
+            TokenAmounts {
+                amount_a: 900,
+                amount_b: 0
+            }
+        );
+
+        let inside = get_amounts_for_liquidity(2 * ONE, lower, upper, liquidity, false).unwrap();
+        assert_eq!(
+            inside,
+            TokenAmounts {
+                amount_a: 300,
+                amount_b: 1_200
+            }
+        );
+
+        let above = get_amounts_for_liquidity(8 * ONE, lower, upper, liquidity, false).unwrap();
+        assert_eq!(
+            above,
+            TokenAmounts {
+                amount_a: 0,
+                amount_b: 3_600
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_range_is_rejected() {
+        assert_eq!(
+            get_amounts_for_liquidity(ONE, 2 * ONE, ONE, 1, false),
+            Err(MathError::InvalidRange)
+        );
+    }
+
+    #[test]
+    fn liquidity_round_trips_through_amounts() {
+        let current = get_sqrt_price_at_tick(-23_028).unwrap();
+        let lower = get_sqrt_price_at_tick(-30_000).unwrap();
+        let upper = get_sqrt_price_at_tick(-10_000).unwrap();
+        let liquidity = 123_456_789_012_345u128;
+
+        let amounts = get_amounts_for_liquidity(current, lower, upper, liquidity, true).unwrap();
+        let minted =
+            get_liquidity_for_amounts(current, lower, upper, amounts.amount_a, amounts.amount_b)
+                .unwrap();
+
+        // Amounts are rounded up, so the minted liquidity can only be marginally larger
+        assert!(minted >= liquidity);
+        assert!(minted - liquidity <= liquidity / 1_000_000);
+    }
+
+    #[test]
+    fn single_sided_liquidity() {
+        let lower = get_sqrt_price_at_tick(0).unwrap();
+        let upper = get_sqrt_price_at_tick(100).unwrap();
+
+        let below = get_liquidity_for_amounts(lower - 1, lower, upper, 1_000_000, 0).unwrap();
+        assert!(below > 0);
+        let above = get_liquidity_for_amounts(upper, lower, upper, 1_000_000, 0).unwrap();
+        assert_eq!(above, 0);
+    }
+}
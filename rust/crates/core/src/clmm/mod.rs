@@ -0,0 +1,47 @@
+//! Concentrated liquidity math for Aptos CLMMs (TAPP, Hyperion, Thala).
+//!
+//! Prices are square roots encoded as Q64.64 fixed point numbers, ticks are `i32` and every
+//! computation is done with exact integer arithmetic. Floating point is only used when
+//! converting to and from human readable prices in [`price`].
+
+use std::fmt;
+
+pub mod distribution;
+pub mod fee_math;
+pub mod full_math;
+pub mod liquidity_math;
+pub mod price;
+pub mod tick_math;
+
+pub use primitive_types::U256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    /// Tick outside of `[MIN_TICK, MAX_TICK]`
+    TickOutOfBounds(i32),
+    /// Sqrt price outside of `[MIN_SQRT_PRICE, MAX_SQRT_PRICE]`
+    SqrtPriceOutOfBounds(u128),
+    /// Lower bound of a range isn't below its upper bound
+    InvalidRange,
+    /// Result doesn't fit in the target integer type
+    Overflow,
+    DivisionByZero,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MathError::TickOutOfBounds(tick) => write!(f, "tick {tick} is out of bounds"),
+            MathError::SqrtPriceOutOfBounds(price) => {
+                write!(f, "sqrt price {price} is out of bounds")
+            }
+            MathError::InvalidRange => write!(f, "lower bound must be below upper bound"),
+            MathError::Overflow => write!(f, "arithmetic overflow"),
+            MathError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
+
+pub type MathResult<T> = Result<T, MathError>;
@@ -0,0 +1,110 @@
+//! Human readable prices, scaled with the tokens' decimals.
+//!
+//! A price is the amount of token B paid for one whole token A, i.e. the raw on-chain price
+//! multiplied by `10^(decimals_a - decimals_b)`.
+
+use primitive_types::U256;
+
+use super::{
+    MathResult,
+    full_math::to_u128,
+    tick_math::{MAX_SQRT_PRICE, MIN_SQRT_PRICE, get_sqrt_price_at_tick, get_tick_at_sqrt_price},
+};
+
+const Q64: f64 = 18446744073709551616.0;
+
+fn decimal_factor(decimals_a: u8, decimals_b: u8) -> f64 {
+    10f64.powi(decimals_a as i32 - decimals_b as i32)
+}
+
+/// Exact raw price (token B per token A, in base units) as Q64.64
+pub fn sqrt_price_to_price_x64(sqrt_price: u128) -> MathResult<u128> {
+    let squared = U256::from(sqrt_price) * U256::from(sqrt_price);
+    to_u128(squared >> 64)
+}
+
+/// Price of one token A in token B
+pub fn sqrt_price_to_price(sqrt_price: u128, decimals_a: u8, decimals_b: u8) -> f64 {
+    let sqrt = sqrt_price as f64 / Q64;
+    sqrt * sqrt * decimal_factor(decimals_a, decimals_b)
+}
+
+/// Q64.64 sqrt price for a human price, clamped to the supported range
+pub fn price_to_sqrt_price(price: f64, decimals_a: u8, decimals_b: u8) -> u128 {
+    let raw = price / decimal_factor(decimals_a, decimals_b);
+    if !raw.is_finite() || raw <= 0.0 {
+        return MIN_SQRT_PRICE;
+    }
+
+    let sqrt_price = raw.sqrt() * Q64;
+    if sqrt_price >= MAX_SQRT_PRICE as f64 {
+        MAX_SQRT_PRICE
+    } else {
+        (sqrt_price as u128).max(MIN_SQRT_PRICE)
+    }
+}
+
+/// Price of one token A in token B at `tick`
+pub fn tick_to_price(tick: i32, decimals_a: u8, decimals_b: u8) -> MathResult<f64> {
+    Ok(sqrt_price_to_price(
+        get_sqrt_price_at_tick(tick)?,
+        decimals_a,
+        decimals_b,
+    ))
+}
+
+/// Greatest tick whose price is lower than or equal to `price`
+pub fn price_to_tick(price: f64, decimals_a: u8, decimals_b: u8) -> MathResult<i32> {
+    get_tick_at_sqrt_price(price_to_sqrt_price(price, decimals_a, decimals_b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clmm::tick_math::{MAX_TICK, MIN_TICK};
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            ((actual - expected) / expected).abs() < 1e-9,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn tick_zero_is_parity_before_scaling() {
+        assert_close(tick_to_price(0, 8, 8).unwrap(), 1.0);
+        // 1 unit of an 8 decimals token A against a 6 decimals token B
+        assert_close(tick_to_price(0, 8, 6).unwrap(), 100.0);
+        assert_close(tick_to_price(0, 6, 8).unwrap(), 0.01);
+    }
+
+    #[test]
+    fn follows_one_basis_point_per_tick() {
+        assert_close(tick_to_price(10_000, 6, 6).unwrap(), 1.0001f64.powi(10_000));
+        assert_close(
+            tick_to_price(-10_000, 6, 6).unwrap(),
+            1.0001f64.powi(-10_000),
+        );
+    }
+
+    #[test]
+    fn price_to_tick_inverts_tick_to_price() {
+        // APT (8 decimals) around 4.3 USDC (6 decimals)
+        let tick = price_to_tick(4.3, 8, 6).unwrap();
+        let price = tick_to_price(tick, 8, 6).unwrap();
+        let next = tick_to_price(tick + 1, 8, 6).unwrap();
+        assert!(price <= 4.3 && 4.3 < next);
+    }
+
+    #[test]
+    fn extreme_prices_are_clamped() {
+        assert_eq!(price_to_tick(0.0, 6, 6).unwrap(), MIN_TICK);
+        assert_eq!(price_to_tick(f64::MAX, 6, 6).unwrap(), MAX_TICK);
+    }
+
+    #[test]
+    fn exact_price_x64() {
+        assert_eq!(sqrt_price_to_price_x64(1 << 64).unwrap(), 1 << 64);
+        assert_eq!(sqrt_price_to_price_x64(2 << 64).unwrap(), 4 << 64);
+    }
+}
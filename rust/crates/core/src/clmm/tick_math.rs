@@ -0,0 +1,208 @@
+//! Tick ↔ sqrt price conversions.
+//!
+//! Uses the same constants as the Move implementations deployed on Aptos, so results match
+//! the on-chain pools bit for bit: `sqrt_price = sqrt(1.0001^tick) * 2^64`.
+
+use primitive_types::U256;
+
+use super::{MathError, MathResult, full_math::to_u128};
+
+pub const MIN_TICK: i32 = -443636;
+pub const MAX_TICK: i32 = 443636;
+
+/// Sqrt price at `MIN_TICK`, Q64.64
+pub const MIN_SQRT_PRICE: u128 = 4295048016;
+/// Sqrt price at `MAX_TICK`, Q64.64
+pub const MAX_SQRT_PRICE: u128 = 79226673515401279992447579055;
+
+/// Q64.64 sqrt prices of `1.0001^(-2^i / 2)`
+const NEGATIVE_TICK_FACTORS: [u128; 19] = [
+    18445821805675392311,
+    18444899583751176498,
+    18443055278223354162,
+    18439367220385604838,
+    18431993317065449817,
+    18417254355718160513,
+    18387811781193591352,
+    18329067761203520168,
+    18212142134806087854,
+    17980523815641551639,
+    17526086738831147013,
+    16651378430235024244,
+    15030750278693429944,
+    12247334978882834399,
+    8131365268884726200,
+    3584323654723342297,
+    696457651847595233,
+    26294789957452057,
+    37481735321082,
+];
+
+/// Q96 sqrt prices of `1.0001^(2^i / 2)`
+const POSITIVE_TICK_FACTORS: [u128; 19] = [
+    79232123823359799118286999567,
+    79236085330515764027303304731,
+    79244008939048815603706035061,
+    79259858533276714757314932305,
+    79291567232598584799939703904,
+    79355022692464371645785046466,
+    79482085999252804386437311141,
+    79736823300114093921829183326,
+    80248749790819932309965073892,
+    81282483887344747381513967011,
+    83390072131320151908154831281,
+    87770609709833776024991924138,
+    97234110755111693312479820773,
+    119332217159966728226237229890,
+    179736315981702064433883588727,
+    407748233172238350107850275304,
+    2098478828474011932436660412517,
+    55581415166113811149459800483533,
+    38992368544603139932233054999993551,
+];
+
+/// Q64.64 sqrt price at `tick`
+pub fn get_sqrt_price_at_tick(tick: i32) -> MathResult<u128> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(MathError::TickOutOfBounds(tick));
+    }
+
+    let abs_tick = tick.unsigned_abs();
+
+    if tick < 0 {
+        let mut ratio: u128 = if abs_tick & 1 != 0 {
+            NEGATIVE_TICK_FACTORS[0]
+        } else {
+            1 << 64
+        };
+        for (bit, factor) in NEGATIVE_TICK_FACTORS.iter().enumerate().skip(1) {
+            if abs_tick & (1 << bit) != 0 {
+                // Both operands are below 2^65, the product always fits in a u128
+                ratio = (ratio * factor) >> 64;
+            }
+        }
+        Ok(ratio)
+    } else {
+        let mut ratio = if abs_tick & 1 != 0 {
+            U256::from(POSITIVE_TICK_FACTORS[0])
+        } else {
+            U256::one() << 96
+        };
+        for (bit, factor) in POSITIVE_TICK_FACTORS.iter().enumerate().skip(1) {
+            if abs_tick & (1 << bit) != 0 {
+                ratio = (ratio * U256::from(*factor)) >> 96;
+            }
+        }
+        to_u128(ratio >> 32)
+    }
+}
+
+/// Greatest tick whose sqrt price is lower than or equal to `sqrt_price`
+pub fn get_tick_at_sqrt_price(sqrt_price: u128) -> MathResult<i32> {
+    if !(MIN_SQRT_PRICE..=MAX_SQRT_PRICE).contains(&sqrt_price) {
+        return Err(MathError::SqrtPriceOutOfBounds(sqrt_price));
+    }
+
+    // get_sqrt_price_at_tick is strictly increasing, binary search for the floor
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if get_sqrt_price_at_tick(mid)? <= sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    Ok(low)
+}
+
+/// Round `tick` down to the closest multiple of `tick_spacing`
+pub fn align_tick_down(tick: i32, tick_spacing: u32) -> i32 {
+    let spacing = tick_spacing.max(1) as i32;
+    tick.div_euclid(spacing) * spacing
+}
+
+/// Round `tick` up to the closest multiple of `tick_spacing`
+pub fn align_tick_up(tick: i32, tick_spacing: u32) -> i32 {
+    let spacing = tick_spacing.max(1) as i32;
+    let aligned = align_tick_down(tick, tick_spacing);
+    if aligned == tick {
+        aligned
+    } else {
+        aligned + spacing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn float_sqrt_price(tick: i32) -> f64 {
+        1.0001f64.powi(tick).sqrt() * 2f64.powi(64)
+    }
+
+    #[test]
+    fn bounds_match_on_chain_constants() {
+        assert_eq!(get_sqrt_price_at_tick(MIN_TICK).unwrap(), MIN_SQRT_PRICE);
+        assert_eq!(get_sqrt_price_at_tick(MAX_TICK).unwrap(), MAX_SQRT_PRICE);
+        assert_eq!(get_sqrt_price_at_tick(0).unwrap(), 1 << 64);
+    }
+
+    #[test]
+    fn out_of_bounds_ticks_are_rejected() {
+        assert_eq!(
+            get_sqrt_price_at_tick(MAX_TICK + 1),
+            Err(MathError::TickOutOfBounds(MAX_TICK + 1))
+        );
+        assert_eq!(
+            get_sqrt_price_at_tick(MIN_TICK - 1),
+            Err(MathError::TickOutOfBounds(MIN_TICK - 1))
+        );
+        assert!(get_tick_at_sqrt_price(MIN_SQRT_PRICE - 1).is_err());
+        assert!(get_tick_at_sqrt_price(MAX_SQRT_PRICE + 1).is_err());
+    }
+
+    #[test]
+    fn sqrt_price_matches_floating_point() {
+        for tick in [-200_000, -50_000, -1_000, -1, 1, 10, 1_000, 50_000, 200_000] {
+            let exact = get_sqrt_price_at_tick(tick).unwrap() as f64;
+            let expected = float_sqrt_price(tick);
+            assert!(
+                ((exact - expected) / expected).abs() < 1e-9,
+                "tick {tick}: {exact} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        assert_eq!(get_sqrt_price_at_tick(1).unwrap(), 18447666387855959850);
+        assert_eq!(get_sqrt_price_at_tick(-1).unwrap(), 18445821805675392311);
+    }
+
+    #[test]
+    fn tick_round_trips() {
+        for tick in [
+            MIN_TICK, -300_000, -12_345, -1, 0, 1, 6_789, 300_000, MAX_TICK,
+        ] {
+            let sqrt_price = get_sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(get_tick_at_sqrt_price(sqrt_price).unwrap(), tick);
+            if tick < MAX_TICK {
+                // Anything below the next tick still maps to the current one
+                let next = get_sqrt_price_at_tick(tick + 1).unwrap();
+                assert_eq!(get_tick_at_sqrt_price(next - 1).unwrap(), tick);
+            }
+        }
+    }
+
+    #[test]
+    fn aligns_ticks_to_spacing() {
+        assert_eq!(align_tick_down(125, 60), 120);
+        assert_eq!(align_tick_down(-125, 60), -180);
+        assert_eq!(align_tick_up(125, 60), 180);
+        assert_eq!(align_tick_up(-125, 60), -120);
+        assert_eq!(align_tick_up(120, 60), 120);
+        assert_eq!(align_tick_down(7, 0), 7);
+    }
+}
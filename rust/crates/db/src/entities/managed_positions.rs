@@ -1,4 +1,4 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.17
+//! `SeaORM` Entity for `managed_positions`, edited by hand in the sea-orm-codegen layout
 
 use super::sea_orm_active_enums::PositionStatus;
 use sea_orm::entity::prelude::*;
@@ -20,6 +20,7 @@ pub struct Model {
     pub status: PositionStatus,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    pub owner: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,9 +1,12 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.17
+//! `SeaORM` entity modules, edited by hand in the sea-orm-codegen layout
 
 pub mod prelude;
 
 pub mod chains;
+pub mod jobs;
 pub mod managed_positions;
+pub mod pool_prices;
+pub mod pool_snapshots;
 pub mod pools;
 pub mod positions;
 pub mod protocols;
@@ -12,3 +15,4 @@ pub mod tokens;
 pub mod user_balances;
 pub mod user_movements;
 pub mod users;
+pub mod vault_shares;
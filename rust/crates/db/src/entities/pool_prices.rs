@@ -0,0 +1,39 @@
+//! `SeaORM` Entity for `pool_prices`, written by hand in the sea-orm-codegen layout
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa :: ToSchema,
+)]
+#[sea_orm(table_name = "pool_prices")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub pool_id: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub interval: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub timestamp: DateTime,
+    #[sea_orm(column_type = "Double")]
+    pub price: f64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::pools::Entity",
+        from = "Column::PoolId",
+        to = "super::pools::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Pools,
+}
+
+impl Related<super::pools::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Pools.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
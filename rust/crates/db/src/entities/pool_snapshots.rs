@@ -0,0 +1,43 @@
+//! `SeaORM` Entity for `pool_snapshots`, written by hand in the sea-orm-codegen layout
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa :: ToSchema,
+)]
+#[sea_orm(table_name = "pool_snapshots")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub pool_id: String,
+    #[sea_orm(column_type = "Double")]
+    pub tvl: f64,
+    #[sea_orm(column_type = "Double")]
+    pub volume_day: f64,
+    #[sea_orm(column_type = "Double")]
+    pub trading_apr: f64,
+    #[sea_orm(column_type = "Double")]
+    pub bonus_apr: f64,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::pools::Entity",
+        from = "Column::PoolId",
+        to = "super::pools::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Pools,
+}
+
+impl Related<super::pools::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Pools.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
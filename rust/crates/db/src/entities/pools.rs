@@ -1,4 +1,4 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.17
+//! `SeaORM` Entity for `pools`, edited by hand in the sea-orm-codegen layout
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -36,8 +36,14 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(has_many = "super::managed_positions::Entity")]
     ManagedPositions,
+    #[sea_orm(has_many = "super::pool_prices::Entity")]
+    PoolPrices,
+    #[sea_orm(has_many = "super::pool_snapshots::Entity")]
+    PoolSnapshots,
     #[sea_orm(has_many = "super::positions::Entity")]
     Positions,
+    #[sea_orm(has_many = "super::vault_shares::Entity")]
+    VaultShares,
     #[sea_orm(
         belongs_to = "super::tokens::Entity",
         from = "Column::TokenA",
@@ -62,10 +68,28 @@ impl Related<super::managed_positions::Entity> for Entity {
     }
 }
 
+impl Related<super::pool_prices::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PoolPrices.def()
+    }
+}
+
+impl Related<super::pool_snapshots::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PoolSnapshots.def()
+    }
+}
+
 impl Related<super::positions::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Positions.def()
     }
 }
 
+impl Related<super::vault_shares::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VaultShares.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
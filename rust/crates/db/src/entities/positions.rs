@@ -1,4 +1,4 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.17
+//! `SeaORM` Entity for `positions`, edited by hand in the sea-orm-codegen layout
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -16,6 +16,7 @@ pub struct Model {
     pub tick_lower: i64,
     pub tick_upper: i64,
     pub liquidity: String,
+    pub address: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,7 +1,10 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.17
+//! `SeaORM` entity prelude, edited by hand in the sea-orm-codegen layout
 
 pub use super::chains::Entity as Chains;
+pub use super::jobs::Entity as Jobs;
 pub use super::managed_positions::Entity as ManagedPositions;
+pub use super::pool_prices::Entity as PoolPrices;
+pub use super::pool_snapshots::Entity as PoolSnapshots;
 pub use super::pools::Entity as Pools;
 pub use super::positions::Entity as Positions;
 pub use super::protocols::Entity as Protocols;
@@ -9,3 +12,4 @@ pub use super::tokens::Entity as Tokens;
 pub use super::user_balances::Entity as UserBalances;
 pub use super::user_movements::Entity as UserMovements;
 pub use super::users::Entity as Users;
+pub use super::vault_shares::Entity as VaultShares;
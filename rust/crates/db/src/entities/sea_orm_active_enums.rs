@@ -1,8 +1,52 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.17
+//! `SeaORM` active enums, edited by hand in the sea-orm-codegen layout
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    utoipa :: ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "job_kind")]
+pub enum JobKind {
+    #[sea_orm(string_value = "refresh_pool")]
+    RefreshPool,
+    #[sea_orm(string_value = "refresh_pools")]
+    RefreshPools,
+    #[sea_orm(string_value = "refresh_positions")]
+    RefreshPositions,
+    #[sea_orm(string_value = "refresh_tokens")]
+    RefreshTokens,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    utoipa :: ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "job_state")]
+pub enum JobState {
+    #[sea_orm(string_value = "dead")]
+    Dead,
+    #[sea_orm(string_value = "queued")]
+    Queued,
+    #[sea_orm(string_value = "running")]
+    Running,
+    #[sea_orm(string_value = "succeeded")]
+    Succeeded,
+}
 #[derive(
     Debug,
     Clone,
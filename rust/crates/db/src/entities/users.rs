@@ -1,4 +1,4 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.17
+//! `SeaORM` Entity for `users`, edited by hand in the sea-orm-codegen layout
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -25,6 +25,8 @@ pub enum Relation {
     UserBalances,
     #[sea_orm(has_many = "super::user_movements::Entity")]
     UserMovements,
+    #[sea_orm(has_many = "super::vault_shares::Entity")]
+    VaultShares,
 }
 
 impl Related<super::managed_positions::Entity> for Entity {
@@ -45,6 +47,12 @@ impl Related<super::user_movements::Entity> for Entity {
     }
 }
 
+impl Related<super::vault_shares::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VaultShares.def()
+    }
+}
+
 impl Related<super::tokens::Entity> for Entity {
     fn to() -> RelationDef {
         super::user_balances::Relation::Tokens.def()
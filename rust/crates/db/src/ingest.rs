@@ -0,0 +1,600 @@
+//! Batch upserts shared by the API and the scrapers.
+//!
+//! Every function runs inside a single transaction and reports how many rows were
+//! inserted, updated or deleted, so callers don't need to re-implement conflict handling.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::Hash,
+    ops::AddAssign,
+};
+
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, QuerySelect, TransactionTrait,
+    sea_query::OnConflict,
+    sqlx::types::chrono::Utc,
+};
+use serde::Serialize;
+
+use crate::entities::{
+    pool_prices::{self, Entity as PoolPrices},
+    pool_snapshots::{self, Entity as PoolSnapshots},
+    pools::{self, Entity as Pools},
+    positions::{self, Entity as Positions},
+    tokens::{self, Entity as Tokens},
+};
+
+/// Keeps multi-row inserts and `IN` lists well below Postgres' 65535 bind parameter limit.
+const BATCH_SIZE: usize = 1000;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct IngestStats {
+    pub inserted: u64,
+    pub updated: u64,
+    pub deleted: u64,
+}
+
+impl IngestStats {
+    /// Rows written by the upsert, whether they were new or not.
+    pub fn upserted(&self) -> u64 {
+        self.inserted + self.updated
+    }
+
+    /// Rows written or removed.
+    pub fn changed(&self) -> u64 {
+        self.upserted() + self.deleted
+    }
+}
+
+impl AddAssign for IngestStats {
+    fn add_assign(&mut self, other: Self) {
+        self.inserted += other.inserted;
+        self.updated += other.updated;
+        self.deleted += other.deleted;
+    }
+}
+
+impl fmt::Display for IngestStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} inserted, {} updated, {} deleted",
+            self.inserted, self.updated, self.deleted
+        )
+    }
+}
+
+/// Upsert pools, refreshing their market data when they already exist.
+///
+/// A `pool_snapshots` row is appended for every pool so the market data history is kept.
+/// A pool listed more than once (paginated sources can repeat one across pages) is written
+/// once, with its last listing.
+pub async fn upsert_pools(
+    db: &DatabaseConnection,
+    models: Vec<pools::ActiveModel>,
+) -> Result<IngestStats, DbErr> {
+    let models = last_per_id(models, |m| m.id.clone().take());
+    if models.is_empty() {
+        return Ok(IngestStats::default());
+    }
+
+    let ids: Vec<String> = models.iter().filter_map(|m| m.id.clone().take()).collect();
+    let total = models.len() as u64;
+
+    let now = Utc::now().naive_utc();
+    let snapshots: Vec<pool_snapshots::ActiveModel> =
+        models.iter().filter_map(|m| snapshot_of(m, now)).collect();
+
+    let txn = db.begin().await?;
+
+    let mut existing = 0;
+    for chunk in chunked(ids) {
+        existing += Pools::find()
+            .filter(pools::Column::Id.is_in(chunk))
+            .count(&txn)
+            .await?;
+    }
+
+    for chunk in chunked(models) {
+        Pools::insert_many(chunk)
+            .on_conflict(
+                OnConflict::column(pools::Column::Id)
+                    .update_columns([
+                        pools::Column::TradingApr,
+                        pools::Column::BonusApr,
+                        pools::Column::Tvl,
+                        pools::Column::VolumeDay,
+                        pools::Column::VolumeWeek,
+                        pools::Column::VolumeMonth,
+                        pools::Column::VolumePrevDay,
+                        pools::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&txn)
+            .await?;
+    }
+
+    for chunk in chunked(snapshots) {
+        PoolSnapshots::insert_many(chunk).exec(&txn).await?;
+    }
+
+    txn.commit().await?;
+
+    Ok(IngestStats {
+        inserted: total - existing,
+        updated: existing,
+        deleted: 0,
+    })
+}
+
+/// Snapshot of the market data carried by a pool model, if it is fully set
+fn snapshot_of(
+    model: &pools::ActiveModel,
+    created_at: sea_orm::prelude::DateTime,
+) -> Option<pool_snapshots::ActiveModel> {
+    Some(pool_snapshots::ActiveModel {
+        id: NotSet,
+        pool_id: Set(model.id.clone().take()?),
+        tvl: Set(model.tvl.clone().take()?),
+        volume_day: Set(model.volume_day.clone().take()?),
+        trading_apr: Set(model.trading_apr.clone().take()?),
+        bonus_apr: Set(model.bonus_apr.clone().take()?),
+        created_at: Set(created_at),
+    })
+}
+
+/// Replace the stored positions of a pool with the given set.
+///
+/// Positions are upserted on `(pool, index)` and any position of the pool missing from
+/// `models` is deleted, as it has been closed on-chain. An empty `models` therefore deletes
+/// every stored position of the pool: callers only get here once the chain lookup succeeded,
+/// so an empty list is a pool whose last position was closed. A batch listing an index twice
+/// is rejected before anything is written.
+pub async fn sync_positions(
+    db: &DatabaseConnection,
+    pool_id: &str,
+    models: Vec<positions::ActiveModel>,
+) -> Result<IngestStats, DbErr> {
+    let indexes: Vec<i32> = models
+        .iter()
+        .filter_map(|m| m.index.clone().take())
+        .collect();
+    if let Some(index) = first_duplicate(&indexes) {
+        return Err(DbErr::Custom(format!(
+            "Position {} of pool {} is listed more than once",
+            index, pool_id
+        )));
+    }
+
+    let txn = db.begin().await?;
+    let stats = replace_positions(&txn, pool_id, models).await?;
+    txn.commit().await?;
+
+    Ok(stats)
+}
+
+/// Replace the stored positions of a pool identified by their object address.
+///
+/// Some CLMMs make positions objects rather than numbering them within the pool. Their address
+/// is kept in `address` and each address gets the next free `index` of the pool the first time
+/// it is seen, keeping it afterwards. Closed positions are deleted as in [`sync_positions`],
+/// and a batch listing an address twice is rejected before anything is written.
+pub async fn sync_object_positions(
+    db: &DatabaseConnection,
+    pool_id: &str,
+    mut models: Vec<positions::ActiveModel>,
+) -> Result<IngestStats, DbErr> {
+    let mut addresses = Vec::with_capacity(models.len());
+    for model in &models {
+        match model.address.clone().take().flatten() {
+            Some(address) => addresses.push(address),
+            None => {
+                return Err(DbErr::Custom(format!(
+                    "A position of pool {} has no object address",
+                    pool_id
+                )));
+            }
+        }
+    }
+    if let Some(address) = first_duplicate(&addresses) {
+        return Err(DbErr::Custom(format!(
+            "Position {} of pool {} is listed more than once",
+            address, pool_id
+        )));
+    }
+
+    let txn = db.begin().await?;
+
+    let stored: Vec<(i32, Option<String>)> = Positions::find()
+        .select_only()
+        .column(positions::Column::Index)
+        .column(positions::Column::Address)
+        .filter(positions::Column::Pool.eq(pool_id))
+        .into_tuple()
+        .all(&txn)
+        .await?;
+    // Rows without an address predate the column: they are overwritten or deleted below
+    let known: HashMap<String, i32> = stored
+        .into_iter()
+        .filter_map(|(index, address)| Some((address?, index)))
+        .collect();
+    let exhausted = || DbErr::Custom(format!("Pool {} has run out of position indexes", pool_id));
+    let mut next = match known.values().max() {
+        Some(index) => index.checked_add(1).ok_or_else(exhausted)?,
+        None => 0,
+    };
+
+    for (model, address) in models.iter_mut().zip(addresses) {
+        let index = match known.get(&address) {
+            Some(&index) => index,
+            None => {
+                let index = next;
+                next = next.checked_add(1).ok_or_else(exhausted)?;
+                index
+            }
+        };
+        model.index = Set(index);
+    }
+
+    let stats = replace_positions(&txn, pool_id, models).await?;
+    txn.commit().await?;
+
+    Ok(stats)
+}
+
+/// Upsert `models`, whose indexes are distinct, and delete the other positions of the pool
+async fn replace_positions(
+    txn: &DatabaseTransaction,
+    pool_id: &str,
+    models: Vec<positions::ActiveModel>,
+) -> Result<IngestStats, DbErr> {
+    let indexes: Vec<i32> = models
+        .iter()
+        .filter_map(|m| m.index.clone().take())
+        .collect();
+
+    let stored = stored_position_indexes(txn, pool_id).await?;
+    let existing = indexes.iter().filter(|i| stored.contains(i)).count() as u64;
+    let fresh: HashSet<i32> = indexes.iter().copied().collect();
+    let closed: Vec<i32> = stored.difference(&fresh).copied().collect();
+
+    for chunk in chunked(models) {
+        Positions::insert_many(chunk)
+            .on_conflict(
+                OnConflict::columns([positions::Column::Pool, positions::Column::Index])
+                    .update_columns([
+                        positions::Column::TickLower,
+                        positions::Column::TickUpper,
+                        positions::Column::Liquidity,
+                        positions::Column::Address,
+                        positions::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(txn)
+            .await?;
+    }
+
+    let mut deleted = 0;
+    for chunk in chunked(closed) {
+        deleted += Positions::delete_many()
+            .filter(positions::Column::Pool.eq(pool_id))
+            .filter(positions::Column::Index.is_in(chunk))
+            .exec(txn)
+            .await?
+            .rows_affected;
+    }
+
+    Ok(IngestStats {
+        inserted: indexes.len() as u64 - existing,
+        updated: existing,
+        deleted,
+    })
+}
+
+async fn stored_position_indexes<C: ConnectionTrait>(
+    db: &C,
+    pool_id: &str,
+) -> Result<HashSet<i32>, DbErr> {
+    let indexes: Vec<i32> = Positions::find()
+        .select_only()
+        .column(positions::Column::Index)
+        .filter(positions::Column::Pool.eq(pool_id))
+        .into_tuple()
+        .all(db)
+        .await?;
+    Ok(indexes.into_iter().collect())
+}
+
+fn first_duplicate<T: Clone + Eq + Hash>(items: &[T]) -> Option<T> {
+    let mut seen = HashSet::with_capacity(items.len());
+    items.iter().find(|&item| !seen.insert(item)).cloned()
+}
+
+/// Upsert tokens. `about` is left untouched on existing rows since no source provides it.
+///
+/// As with pools, a token listed more than once is written once, with its last listing.
+pub async fn upsert_tokens(
+    db: &DatabaseConnection,
+    models: Vec<tokens::ActiveModel>,
+) -> Result<IngestStats, DbErr> {
+    let models = last_per_id(models, |m| m.id.clone().take());
+    if models.is_empty() {
+        return Ok(IngestStats::default());
+    }
+
+    let ids: Vec<String> = models.iter().filter_map(|m| m.id.clone().take()).collect();
+    let total = models.len() as u64;
+
+    let txn = db.begin().await?;
+
+    let mut existing = 0;
+    for chunk in chunked(ids) {
+        existing += Tokens::find()
+            .filter(tokens::Column::Id.is_in(chunk))
+            .count(&txn)
+            .await?;
+    }
+
+    for chunk in chunked(models) {
+        Tokens::insert_many(chunk)
+            .on_conflict(
+                OnConflict::column(tokens::Column::Id)
+                    .update_columns([
+                        tokens::Column::Symbol,
+                        tokens::Column::Name,
+                        tokens::Column::Logo,
+                        tokens::Column::Decimals,
+                        tokens::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&txn)
+            .await?;
+    }
+
+    txn.commit().await?;
+
+    Ok(IngestStats {
+        inserted: total - existing,
+        updated: existing,
+        deleted: 0,
+    })
+}
+
+/// Insert price points, skipping the ones already stored for the same pool, interval and
+/// timestamp.
+pub async fn insert_prices(
+    db: &DatabaseConnection,
+    models: Vec<pool_prices::ActiveModel>,
+) -> Result<IngestStats, DbErr> {
+    if models.is_empty() {
+        return Ok(IngestStats::default());
+    }
+
+    let txn = db.begin().await?;
+
+    let mut inserted = 0;
+    for chunk in chunked(models) {
+        inserted += PoolPrices::insert_many(chunk)
+            .on_conflict(
+                OnConflict::columns([
+                    pool_prices::Column::PoolId,
+                    pool_prices::Column::Interval,
+                    pool_prices::Column::Timestamp,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&txn)
+            .await?;
+    }
+
+    txn.commit().await?;
+
+    Ok(IngestStats {
+        inserted,
+        updated: 0,
+        deleted: 0,
+    })
+}
+
+/// Keep the last model of every id, in the order of those last occurrences.
+///
+/// Postgres refuses an `ON CONFLICT DO UPDATE` touching the same row twice in one statement.
+fn last_per_id<T>(models: Vec<T>, id: impl Fn(&T) -> Option<String>) -> Vec<T> {
+    let mut seen = HashSet::with_capacity(models.len());
+    let mut kept: Vec<T> = models
+        .into_iter()
+        .rev()
+        .filter(|m| id(m).is_none_or(|id| seen.insert(id)))
+        .collect();
+    kept.reverse();
+    kept
+}
+
+fn chunked<T>(mut items: Vec<T>) -> Vec<Vec<T>> {
+    let mut chunks = Vec::with_capacity(items.len().div_ceil(BATCH_SIZE));
+    while items.len() > BATCH_SIZE {
+        let rest = items.split_off(BATCH_SIZE);
+        chunks.push(items);
+        items = rest;
+    }
+    if !items.is_empty() {
+        chunks.push(items);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Value};
+
+    use super::*;
+
+    fn position(index: i32) -> positions::ActiveModel {
+        positions::ActiveModel {
+            index: Set(index),
+            pool: Set("0xpool".to_string()),
+            updated_at: Set(None),
+            tick_lower: Set(-60),
+            tick_upper: Set(60),
+            liquidity: Set("1000".to_string()),
+            address: Set(None),
+        }
+    }
+
+    fn object_position(address: &str) -> positions::ActiveModel {
+        positions::ActiveModel {
+            index: NotSet,
+            address: Set(Some(address.to_string())),
+            ..position(0)
+        }
+    }
+
+    fn stored_objects(rows: &[(i32, &str)]) -> Vec<BTreeMap<&'static str, Value>> {
+        rows.iter()
+            .map(|&(index, address)| {
+                BTreeMap::from([
+                    ("index", Value::Int(Some(index))),
+                    ("address", Value::String(Some(address.to_string()))),
+                ])
+            })
+            .collect()
+    }
+
+    fn stored(indexes: &[i32]) -> Vec<BTreeMap<&'static str, Value>> {
+        indexes
+            .iter()
+            .map(|&i| BTreeMap::from([("index", Value::Int(Some(i)))]))
+            .collect()
+    }
+
+    fn written(rows_affected: u64) -> MockExecResult {
+        MockExecResult {
+            last_insert_id: 0,
+            rows_affected,
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_positions_delete_every_stored_position() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([stored(&[1, 2])])
+            .append_exec_results([written(2)])
+            .into_connection();
+
+        let stats = sync_positions(&db, "0xpool", vec![]).await.unwrap();
+
+        assert_eq!(
+            stats,
+            IngestStats {
+                inserted: 0,
+                updated: 0,
+                deleted: 2
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn positions_are_upserted_and_closed_ones_deleted() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([stored(&[1, 2])])
+            .append_exec_results([written(2), written(1)])
+            .into_connection();
+
+        let stats = sync_positions(&db, "0xpool", vec![position(1), position(3)])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            stats,
+            IngestStats {
+                inserted: 1,
+                updated: 1,
+                deleted: 1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn duplicate_positions_are_rejected_before_writing() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+        let err = sync_positions(&db, "0xpool", vec![position(1), position(2), position(1)])
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("Position 1 of pool 0xpool"));
+        assert!(db.into_transaction_log().is_empty());
+    }
+
+    #[tokio::test]
+    async fn object_positions_keep_their_index_and_new_ones_get_the_next() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([stored_objects(&[(0, "0xa"), (1, "0xb")])])
+            .append_query_results([stored(&[0, 1])])
+            .append_exec_results([written(2), written(1)])
+            .into_connection();
+
+        let stats = sync_object_positions(
+            &db,
+            "0xpool",
+            vec![object_position("0xb"), object_position("0xc")],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            stats,
+            IngestStats {
+                inserted: 1,
+                updated: 1,
+                deleted: 1
+            }
+        );
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(log.contains("Int(Some(1))") && log.contains("Int(Some(2))"));
+    }
+
+    #[tokio::test]
+    async fn duplicate_object_positions_are_rejected_before_writing() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+
+        let err = sync_object_positions(
+            &db,
+            "0xpool",
+            vec![object_position("0xa"), object_position("0xa")],
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.to_string().contains("Position 0xa of pool 0xpool"));
+        assert!(db.into_transaction_log().is_empty());
+    }
+
+    #[test]
+    fn repeated_ids_keep_their_last_listing() {
+        let models = vec![("a", 1), ("b", 2), ("a", 3), ("c", 4), ("b", 5)];
+
+        let kept = last_per_id(models, |(id, _)| Some(id.to_string()));
+
+        assert_eq!(kept, [("a", 3), ("c", 4), ("b", 5)]);
+    }
+
+    #[test]
+    fn id_lists_are_chunked_below_the_bind_limit() {
+        let chunks = chunked((0..2 * BATCH_SIZE + 1).collect::<Vec<_>>());
+
+        assert_eq!(
+            chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+            [BATCH_SIZE, BATCH_SIZE, 1]
+        );
+    }
+}
@@ -1,7 +1,20 @@
 pub mod entities;
+pub mod ingest;
+pub mod query;
+pub mod queue;
 
-use sea_orm::{Database, DatabaseConnection};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
 
 pub async fn create_connection(database_url: &str) -> Result<DatabaseConnection, sea_orm::DbErr> {
     Database::connect(database_url).await
 }
+
+/// Connection pool holding at most `max_connections` connections
+pub async fn create_pool(
+    database_url: &str,
+    max_connections: u32,
+) -> Result<DatabaseConnection, sea_orm::DbErr> {
+    let mut options = ConnectOptions::new(database_url);
+    options.max_connections(max_connections);
+    Database::connect(options).await
+}
@@ -0,0 +1,46 @@
+//! Read queries shared by the API and the scrapers.
+
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+
+use crate::entities::pools::{self, Entity as Pools};
+
+/// IDs of the `limit` pools of `dex` with the highest TVL, highest first
+pub async fn top_pools_by_tvl(
+    db: &DatabaseConnection,
+    dex: &str,
+    limit: u64,
+) -> Result<Vec<String>, DbErr> {
+    Pools::find()
+        .select_only()
+        .column(pools::Column::Id)
+        .filter(pools::Column::Dex.eq(dex))
+        .order_by_desc(pools::Column::Tvl)
+        .order_by_asc(pools::Column::Id)
+        .limit(limit)
+        .into_tuple()
+        .all(db)
+        .await
+}
+
+/// Distinct token IDs traded in the stored pools of `dex`
+pub async fn pool_token_ids(db: &DatabaseConnection, dex: &str) -> Result<Vec<String>, DbErr> {
+    let pairs: Vec<(Option<String>, Option<String>)> = Pools::find()
+        .select_only()
+        .column(pools::Column::TokenA)
+        .column(pools::Column::TokenB)
+        .filter(pools::Column::Dex.eq(dex))
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let mut ids: Vec<String> = pairs
+        .into_iter()
+        .flat_map(|(a, b)| [a, b])
+        .flatten()
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    Ok(ids)
+}
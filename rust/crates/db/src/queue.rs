@@ -0,0 +1,312 @@
+//! Postgres backed queue of refresh jobs.
+//!
+//! Workers claim due jobs with `FOR UPDATE SKIP LOCKED`, so any number of them, in any number
+//! of processes, can share the `jobs` table without running a job twice. A job whose worker
+//! died keeps its `running` state until its lease expires, then it is claimed again, or marked
+//! dead if that was its last attempt. Outcomes are only recorded while the worker still holds
+//! the claim, so a worker whose lease ran out can't overwrite the run that took over.
+
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use sea_orm::{
+    ActiveEnum, ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, TransactionTrait,
+    sea_query::{Expr, ExprTrait, LockBehavior, LockType},
+};
+
+use crate::{
+    entities::{
+        jobs::{self, Entity as Jobs},
+        sea_orm_active_enums::{JobKind, JobState},
+    },
+    ingest::IngestStats,
+};
+
+/// Add a job, due immediately
+pub async fn enqueue(
+    db: &DatabaseConnection,
+    kind: JobKind,
+    target: Option<String>,
+    max_attempts: i32,
+) -> Result<jobs::Model, DbErr> {
+    let now = Utc::now().naive_utc();
+    jobs::ActiveModel {
+        id: NotSet,
+        kind: Set(kind),
+        target: Set(target),
+        state: Set(JobState::Queued),
+        attempts: Set(0),
+        max_attempts: Set(max_attempts),
+        last_error: Set(None),
+        result: Set(None),
+        run_at: Set(now),
+        locked_at: Set(None),
+        finished_at: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+}
+
+pub async fn find(db: &DatabaseConnection, id: i32) -> Result<Option<jobs::Model>, DbErr> {
+    Jobs::find_by_id(id).one(db).await
+}
+
+/// Take the oldest due job and mark it running, counting the attempt.
+///
+/// Running jobs locked for longer than `lease` are taken over as well, unless that run was
+/// their last attempt: those are marked dead.
+pub async fn claim(db: &DatabaseConnection, lease: Duration) -> Result<Option<jobs::Model>, DbErr> {
+    let now = Utc::now().naive_utc();
+    let expired = now
+        .checked_sub_signed(time_delta(lease))
+        .unwrap_or(NaiveDateTime::MIN);
+    let out_of_attempts =
+        Expr::col(jobs::Column::Attempts).gte(Expr::col(jobs::Column::MaxAttempts));
+
+    let txn = db.begin().await?;
+
+    Jobs::update_many()
+        .col_expr(jobs::Column::State, ActiveEnum::as_enum(&JobState::Dead))
+        .col_expr(
+            jobs::Column::LastError,
+            Expr::value("Lease expired on the last attempt"),
+        )
+        .col_expr(jobs::Column::LockedAt, Expr::value(None::<NaiveDateTime>))
+        .col_expr(jobs::Column::FinishedAt, Expr::value(now))
+        .col_expr(jobs::Column::UpdatedAt, Expr::value(now))
+        .filter(jobs::Column::State.eq(JobState::Running))
+        .filter(jobs::Column::LockedAt.lt(expired))
+        .filter(out_of_attempts.clone())
+        .exec(&txn)
+        .await?;
+
+    let due = Jobs::find()
+        .filter(
+            Condition::any()
+                .add(
+                    Condition::all()
+                        .add(jobs::Column::State.eq(JobState::Queued))
+                        .add(jobs::Column::RunAt.lte(now)),
+                )
+                .add(
+                    Condition::all()
+                        .add(jobs::Column::State.eq(JobState::Running))
+                        .add(jobs::Column::LockedAt.lt(expired))
+                        .add(out_of_attempts.not()),
+                ),
+        )
+        .order_by_asc(jobs::Column::RunAt)
+        .order_by_asc(jobs::Column::Id)
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .one(&txn)
+        .await?;
+
+    let Some(job) = due else {
+        txn.commit().await?;
+        return Ok(None);
+    };
+
+    let attempts = job.attempts + 1;
+    let mut job: jobs::ActiveModel = job.into();
+    job.state = Set(JobState::Running);
+    job.attempts = Set(attempts);
+    job.locked_at = Set(Some(now));
+    job.updated_at = Set(now);
+    let job = job.update(&txn).await?;
+
+    txn.commit().await?;
+    Ok(Some(job))
+}
+
+/// Record a successful run, false if the claim was lost and nothing was written
+pub async fn succeed(
+    db: &DatabaseConnection,
+    job: jobs::Model,
+    stats: IngestStats,
+) -> Result<bool, DbErr> {
+    let now = Utc::now().naive_utc();
+    let result = serde_json::to_value(stats).map_err(|e| DbErr::Custom(e.to_string()))?;
+    let claim = Claim::of(&job);
+
+    let mut job: jobs::ActiveModel = job.into();
+    job.state = Set(JobState::Succeeded);
+    job.last_error = Set(None);
+    job.result = Set(Some(result));
+    job.locked_at = Set(None);
+    job.finished_at = Set(Some(now));
+    job.updated_at = Set(now);
+    record(db, claim, job).await
+}
+
+/// Record a failed run, retried after `retry_in` or dead once out of attempts. False if the
+/// claim was lost and nothing was written.
+pub async fn fail(
+    db: &DatabaseConnection,
+    job: jobs::Model,
+    error: String,
+    retry_in: Duration,
+) -> Result<bool, DbErr> {
+    let now = Utc::now().naive_utc();
+    let dead = job.attempts >= job.max_attempts;
+    let claim = Claim::of(&job);
+
+    let mut job: jobs::ActiveModel = job.into();
+    job.last_error = Set(Some(error));
+    job.locked_at = Set(None);
+    job.updated_at = Set(now);
+    if dead {
+        job.state = Set(JobState::Dead);
+        job.finished_at = Set(Some(now));
+    } else {
+        job.state = Set(JobState::Queued);
+        job.run_at = Set(now
+            .checked_add_signed(time_delta(retry_in))
+            .unwrap_or(NaiveDateTime::MAX));
+    }
+    record(db, claim, job).await
+}
+
+/// What a worker holds once it claimed a job
+struct Claim {
+    id: i32,
+    locked_at: Option<NaiveDateTime>,
+}
+
+impl Claim {
+    fn of(job: &jobs::Model) -> Self {
+        Self {
+            id: job.id,
+            locked_at: job.locked_at,
+        }
+    }
+}
+
+/// Write the outcome of a run only if the job is still running under the same claim
+///
+/// `locked_at` is compared as read back by [`claim`], at the precision the database stores.
+async fn record(
+    db: &DatabaseConnection,
+    claim: Claim,
+    outcome: jobs::ActiveModel,
+) -> Result<bool, DbErr> {
+    let Some(locked_at) = claim.locked_at else {
+        return Ok(false);
+    };
+
+    let updated = Jobs::update_many()
+        .set(outcome)
+        .filter(jobs::Column::Id.eq(claim.id))
+        .filter(jobs::Column::State.eq(JobState::Running))
+        .filter(jobs::Column::LockedAt.eq(locked_at))
+        .exec(db)
+        .await?;
+
+    Ok(updated.rows_affected == 1)
+}
+
+fn time_delta(duration: Duration) -> TimeDelta {
+    TimeDelta::from_std(duration).unwrap_or(TimeDelta::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction};
+
+    use super::*;
+
+    fn job(locked_at: Option<NaiveDateTime>) -> jobs::Model {
+        let now = NaiveDateTime::default();
+        jobs::Model {
+            id: 3,
+            kind: JobKind::RefreshPools,
+            target: None,
+            state: JobState::Running,
+            attempts: 1,
+            max_attempts: 5,
+            last_error: None,
+            result: None,
+            run_at: now,
+            locked_at,
+            finished_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn written(rows_affected: u64) -> MockExecResult {
+        MockExecResult {
+            last_insert_id: 0,
+            rows_affected,
+        }
+    }
+
+    fn statements(log: &[Transaction]) -> Vec<String> {
+        log.iter()
+            .flat_map(|txn| txn.statements())
+            .map(|statement| statement.sql.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn outcomes_need_the_claim_to_still_hold() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([written(1), written(0)])
+            .into_connection();
+        let claimed = Some(NaiveDateTime::default());
+
+        assert!(
+            succeed(&db, job(claimed), IngestStats::default())
+                .await
+                .unwrap()
+        );
+        // Taken over by another worker once the lease expired
+        assert!(
+            !fail(&db, job(claimed), "boom".into(), Duration::ZERO)
+                .await
+                .unwrap()
+        );
+        // Never claimed, nothing to fence on
+        assert!(
+            !succeed(&db, job(None), IngestStats::default())
+                .await
+                .unwrap()
+        );
+
+        let statements = statements(&db.into_transaction_log());
+        assert_eq!(statements.len(), 2);
+        for sql in statements {
+            assert!(sql.starts_with(r#"UPDATE "jobs""#));
+            assert!(sql.contains(r#""id" = $"#));
+            assert!(sql.contains(r#""state" = CAST($"#));
+            assert!(sql.contains(r#""locked_at" = $"#));
+        }
+    }
+
+    #[tokio::test]
+    async fn expired_last_attempts_are_dead_instead_of_claimed() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([written(1)])
+            .append_query_results([Vec::<jobs::Model>::new()])
+            .into_connection();
+
+        assert_eq!(claim(&db, Duration::from_secs(600)).await.unwrap(), None);
+
+        let statements = statements(&db.into_transaction_log());
+        let dead = statements
+            .iter()
+            .find(|sql| sql.starts_with(r#"UPDATE "jobs""#))
+            .unwrap();
+        assert!(dead.contains(r#""attempts" >= "max_attempts""#));
+        let due = statements
+            .iter()
+            .find(|sql| sql.starts_with("SELECT"))
+            .unwrap();
+        assert!(due.contains(r#"NOT "attempts" >= "max_attempts""#));
+        assert!(due.ends_with("FOR UPDATE SKIP LOCKED"));
+    }
+}
@@ -0,0 +1,790 @@
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, bail};
+use liquidity_core::address::AccountAddress;
+use liquidity_core::clmm::{
+    liquidity_math::get_amounts_for_ticks, tick_math::get_sqrt_price_at_tick,
+};
+use serde_json::Value;
+use tapp::transactions::{RouterCall, RouterEvent, TappTransactionBuilder};
+
+use crate::{
+    fullnode::{CommittedTransaction, Fullnode, Simulation},
+    signer::LocalSigner,
+    transaction::{Payload, RawTransaction},
+};
+
+const BPS: u128 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutorConfig {
+    /// Build and simulate only, nothing is submitted
+    pub dry_run: bool,
+    /// Largest price move since planning, and shortfall on the withdrawn amounts, in basis
+    /// points
+    pub max_slippage_bps: u32,
+    pub max_gas_amount: u64,
+    /// Lifetime of a submitted transaction
+    pub expiration: Duration,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            max_slippage_bps: 50,
+            max_gas_amount: 20_000,
+            expiration: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Position to move, with what the plan assumed about it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebalanceOrder {
+    pub pool_id: String,
+    /// On-chain index of the position
+    pub position: u64,
+    pub liquidity: u128,
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+    /// Tick the plan was made at
+    pub planned_tick: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// Dry run: the close was simulated and nothing was submitted. The reopening can't be
+    /// simulated before the close lands, as the account doesn't hold the tokens yet.
+    Simulated { close: Simulation },
+    Executed {
+        close: CommittedTransaction,
+        open: CommittedTransaction,
+        /// Index of the new position, when the open transaction's router event reports it
+        position: Option<u64>,
+    },
+    /// The close was submitted but the position wasn't reopened: the funds may be on the
+    /// signer's account and the closed index must not be acted on again
+    Stranded {
+        close_hash: String,
+        /// Set once the reopening was submitted, it may or may not have landed
+        open_hash: Option<String>,
+        error: String,
+    },
+}
+
+/// Moves positions by closing them and opening a new one over the target range
+pub struct Executor<F> {
+    fullnode: F,
+    signer: LocalSigner,
+    builder: TappTransactionBuilder,
+    config: ExecutorConfig,
+}
+
+impl<F: Fullnode> Executor<F> {
+    pub fn new(
+        fullnode: F,
+        signer: LocalSigner,
+        builder: TappTransactionBuilder,
+        config: ExecutorConfig,
+    ) -> Self {
+        Self {
+            fullnode,
+            signer,
+            builder,
+            config,
+        }
+    }
+
+    pub fn signer(&self) -> &LocalSigner {
+        &self.signer
+    }
+
+    pub fn config(&self) -> &ExecutorConfig {
+        &self.config
+    }
+
+    /// Close the position then reopen it over the target range, simulating each transaction
+    /// before it is submitted
+    ///
+    /// The close fails on-chain if it returns less than the amounts expected at `current_tick`
+    /// minus the slippage. The reopening deposits at most what the close returned, as reported by
+    /// its router event, or those minimums without one. What doesn't fit the new
+    /// range stays on the account.
+    ///
+    /// Errors leave the position untouched. Once the close is submitted, failures are returned
+    /// as [`Outcome::Stranded`] so the caller records them.
+    pub async fn execute(&self, order: &RebalanceOrder, current_tick: i32) -> Result<Outcome> {
+        let bps = self.config.max_slippage_bps;
+        check_price_move(order.planned_tick, current_tick, bps)?;
+
+        let expected = get_amounts_for_ticks(
+            get_sqrt_price_at_tick(current_tick)?,
+            order.from.0,
+            order.from.1,
+            order.liquidity,
+            false,
+        )?;
+        let minimums = (
+            min_out(expected.amount_a, bps)?,
+            min_out(expected.amount_b, bps)?,
+        );
+        let close =
+            self.builder
+                .close_position(&order.pool_id, order.position, minimums.0, minimums.1)?;
+        // Checked now so an invalid target range fails before anything is closed
+        self.builder.open_position(
+            &order.pool_id,
+            order.to.0,
+            order.to.1,
+            minimums.0,
+            minimums.1,
+        )?;
+
+        let chain_id = self.fullnode.chain_id().await?;
+        let gas_unit_price = self.fullnode.gas_unit_price().await?;
+        let sequence_number = self
+            .fullnode
+            .sequence_number(&self.signer.address())
+            .await?;
+
+        let close = self.raw_transaction(close, sequence_number, gas_unit_price, chain_id)?;
+        let simulation = self.simulate(&close).await?;
+        if self.config.dry_run {
+            return Ok(Outcome::Simulated { close: simulation });
+        }
+        let close_hash = self.send(&close).await?;
+        let close = match self.confirm(&close, &close_hash).await {
+            Ok(close) => close,
+            // Failed on-chain, nothing moved
+            Err(e) if e.is::<Reverted>() => return Err(e),
+            Err(e) => return Ok(stranded(close_hash, None, e)),
+        };
+
+        let closed = self.builder.event_type(RouterEvent::PositionClosed);
+        let (amount_a, amount_b) =
+            withdrawn(&close.events, &closed, &order.pool_id, order.position).unwrap_or(minimums);
+        let reopen = async {
+            let open = self.builder.open_position(
+                &order.pool_id,
+                order.to.0,
+                order.to.1,
+                amount_a,
+                amount_b,
+            )?;
+            let open = self.raw_transaction(open, sequence_number + 1, gas_unit_price, chain_id)?;
+            self.simulate(&open).await?;
+            Ok(open)
+        };
+        let open: RawTransaction = match reopen.await {
+            Ok(open) => open,
+            Err(e) => return Ok(stranded(close.hash, None, e)),
+        };
+        let open_hash = match self.send(&open).await {
+            Ok(hash) => hash,
+            Err(e) => return Ok(stranded(close.hash, None, e)),
+        };
+        let open = match self.confirm(&open, &open_hash).await {
+            Ok(open) => open,
+            Err(e) => return Ok(stranded(close.hash, Some(open_hash), e)),
+        };
+
+        let opened = self.builder.event_type(RouterEvent::PositionOpened);
+        Ok(Outcome::Executed {
+            position: opened_position(&open.events, &opened, &order.pool_id),
+            close,
+            open,
+        })
+    }
+
+    fn raw_transaction(
+        &self,
+        call: RouterCall,
+        sequence_number: u64,
+        gas_unit_price: u64,
+        chain_id: u8,
+    ) -> Result<RawTransaction> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+        Ok(RawTransaction {
+            sender: self.signer.address(),
+            sequence_number,
+            payload: Payload(call),
+            max_gas_amount: self.config.max_gas_amount,
+            gas_unit_price,
+            expiration_timestamp_secs: (now + self.config.expiration).as_secs(),
+            chain_id,
+        })
+    }
+
+    async fn simulate(&self, raw: &RawTransaction) -> Result<Simulation> {
+        let function = raw.payload.0.function_id();
+        let simulation = self
+            .fullnode
+            .simulate(&self.signer.sign_for_simulation(raw)?)
+            .await?;
+
+        if !simulation.success {
+            bail!("Simulation of {function} failed: {}", simulation.vm_status);
+        }
+        if simulation.gas_used > raw.max_gas_amount {
+            bail!(
+                "Simulation of {function} used {} gas, above the {} limit",
+                simulation.gas_used,
+                raw.max_gas_amount
+            );
+        }
+
+        Ok(simulation)
+    }
+
+    /// Sign and submit, returning the transaction hash
+    async fn send(&self, raw: &RawTransaction) -> Result<String> {
+        self.fullnode.submit(&self.signer.sign(raw)?).await
+    }
+
+    /// Wait for a submitted transaction, failing with [`Reverted`] if it didn't execute
+    async fn confirm(&self, raw: &RawTransaction, hash: &str) -> Result<CommittedTransaction> {
+        let transaction = self.fullnode.wait_for_transaction(hash).await?;
+
+        if !transaction.success {
+            return Err(Reverted {
+                hash: hash.to_string(),
+                function: raw.payload.0.function_id().to_string(),
+                vm_status: transaction.vm_status,
+            }
+            .into());
+        }
+
+        Ok(transaction)
+    }
+}
+
+/// A committed transaction that failed, so changed nothing but its sender's sequence number
+#[derive(Debug)]
+struct Reverted {
+    hash: String,
+    function: String,
+    vm_status: String,
+}
+
+impl fmt::Display for Reverted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Transaction {} ({}) failed: {}",
+            self.hash, self.function, self.vm_status
+        )
+    }
+}
+
+impl std::error::Error for Reverted {}
+
+fn stranded(close_hash: String, open_hash: Option<String>, error: anyhow::Error) -> Outcome {
+    Outcome::Stranded {
+        close_hash,
+        open_hash,
+        error: format!("{error:#}"),
+    }
+}
+
+/// Refuse to act on a plan made at a price more than `max_bps` away from the current one
+fn check_price_move(planned_tick: i32, current_tick: i32, max_bps: u32) -> Result<()> {
+    let ticks = (i64::from(current_tick) - i64::from(planned_tick)).abs();
+    let moved_bps = (1.0001f64.powf(ticks as f64) - 1.0) * BPS as f64;
+
+    if moved_bps > f64::from(max_bps) {
+        bail!(
+            "Price moved {moved_bps:.0} bps since planning (tick {planned_tick} -> {current_tick}), above the {max_bps} bps limit"
+        );
+    }
+    Ok(())
+}
+
+/// `amount` less the slippage, as the on-chain minimum
+fn min_out(amount: u128, max_bps: u32) -> Result<u64> {
+    let tolerated = BPS.saturating_sub(u128::from(max_bps));
+    Ok(u64::try_from(amount * tolerated / BPS)?)
+}
+
+/// Data of the only event of type `event_type` about `pool`
+///
+/// Other events of the transaction, fee collection or coin transfers, may carry similar fields
+/// and are never read. Several matching events are ambiguous and read as none.
+fn router_event<'a>(events: &'a [Value], event_type: &str, pool: &str) -> Option<&'a Value> {
+    let pool: AccountAddress = pool.parse().ok()?;
+    let mut matching = events.iter().filter(|event| {
+        event.get("type").and_then(Value::as_str) == Some(event_type)
+            && event_pool(event).is_some_and(|address| address == pool)
+    });
+
+    let event = matching.next()?;
+    if matching.next().is_some() {
+        return None;
+    }
+    event.get("data")
+}
+
+/// Pool of an event, reported as an address or as an `Object<Pool>`
+fn event_pool(event: &Value) -> Option<AccountAddress> {
+    let pool = event.get("data")?.get("pool")?;
+    let address = pool.get("inner").unwrap_or(pool).as_str()?;
+    address.parse().ok()
+}
+
+fn u64_field(data: &Value, field: &str) -> Option<u64> {
+    data.get(field)?.as_str()?.parse().ok()
+}
+
+/// Amounts the close of `position` reports as withdrawn
+fn withdrawn(events: &[Value], event_type: &str, pool: &str, position: u64) -> Option<(u64, u64)> {
+    let data = router_event(events, event_type, pool)?;
+    if u64_field(data, "position_idx")? != position {
+        return None;
+    }
+    Some((u64_field(data, "amount_a")?, u64_field(data, "amount_b")?))
+}
+
+/// Index of the position the open reports
+fn opened_position(events: &[Value], event_type: &str, pool: &str) -> Option<u64> {
+    u64_field(router_event(events, event_type, pool)?, "position_idx")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use db::entities::{managed_positions, sea_orm_active_enums::PositionStatus};
+    use rebalancer::{Recommendation, Trigger};
+    use sea_orm::sqlx::types::chrono::{NaiveDateTime, Utc};
+    use serde_json::json;
+
+    use super::*;
+    use crate::keeper::{Opened, moved, order_for};
+
+    const KEY: &str = "0x9bf49a6a0755f953811fce125f2683d50429c3bb49e074147e0089a52eae155f";
+    const SEQUENCE_NUMBER: u64 = 41;
+    const POOL: &str = "0xabc";
+    const OPENED: &str = "0x0000000000000000000000000000000000000000000000000000000000000001::router::OpenPositionEvent";
+    const CLOSED: &str = "0x0000000000000000000000000000000000000000000000000000000000000001::router::ClosePositionEvent";
+
+    /// Fullnode answering from canned outcomes and recording what it receives
+    #[derive(Default)]
+    struct MockFullnode {
+        /// Simulation results, in call order, successful once exhausted
+        simulations: RefCell<Vec<bool>>,
+        failed_commit: bool,
+        /// Whether waiting for each transaction errors, in call order, fine once exhausted
+        lost: RefCell<Vec<bool>>,
+        /// Amounts reported by the events of every committed transaction
+        withdrawn: Option<(u64, u64)>,
+        simulated: RefCell<Vec<Vec<u8>>>,
+        submitted: RefCell<Vec<Vec<u8>>>,
+    }
+
+    impl Fullnode for MockFullnode {
+        async fn chain_id(&self) -> Result<u8> {
+            Ok(1)
+        }
+
+        async fn sequence_number(&self, _account: &AccountAddress) -> Result<u64> {
+            Ok(SEQUENCE_NUMBER)
+        }
+
+        async fn gas_unit_price(&self) -> Result<u64> {
+            Ok(100)
+        }
+
+        async fn simulate(&self, signed_transaction: &[u8]) -> Result<Simulation> {
+            self.simulated
+                .borrow_mut()
+                .push(signed_transaction.to_vec());
+            let mut simulations = self.simulations.borrow_mut();
+            let success = if simulations.is_empty() {
+                true
+            } else {
+                simulations.remove(0)
+            };
+
+            Ok(Simulation {
+                success,
+                vm_status: if success {
+                    "Executed successfully"
+                } else {
+                    "ABORTED"
+                }
+                .into(),
+                gas_used: 1_000,
+            })
+        }
+
+        async fn submit(&self, signed_transaction: &[u8]) -> Result<String> {
+            let mut submitted = self.submitted.borrow_mut();
+            submitted.push(signed_transaction.to_vec());
+            Ok(format!("0x{}", submitted.len()))
+        }
+
+        async fn wait_for_transaction(&self, hash: &str) -> Result<CommittedTransaction> {
+            let mut lost = self.lost.borrow_mut();
+            if !lost.is_empty() && lost.remove(0) {
+                bail!("Transaction {hash} is still pending");
+            }
+
+            // Fee collection reports the same fields and must not be read as the close
+            let mut events = vec![
+                json!({
+                    "type": "0x1::router::CollectFeeEvent",
+                    "data": { "pool": POOL, "position_idx": "7", "amount_a": "1", "amount_b": "1" },
+                }),
+                json!({
+                    "type": OPENED,
+                    "data": { "pool": { "inner": POOL }, "position_idx": "12" },
+                }),
+            ];
+            if let Some((a, b)) = self.withdrawn {
+                events.push(json!({
+                    "type": CLOSED,
+                    "data": {
+                        "pool": POOL,
+                        "position_idx": "7",
+                        "amount_a": a.to_string(),
+                        "amount_b": b.to_string(),
+                    },
+                }));
+            }
+
+            Ok(CommittedTransaction {
+                hash: hash.to_string(),
+                success: !self.failed_commit,
+                vm_status: "Executed successfully".into(),
+                events,
+            })
+        }
+    }
+
+    fn executor(fullnode: MockFullnode, dry_run: bool) -> Executor<MockFullnode> {
+        Executor::new(
+            fullnode,
+            LocalSigner::from_hex(KEY).unwrap(),
+            TappTransactionBuilder::with_router("0x1".parse().unwrap()),
+            ExecutorConfig {
+                dry_run,
+                ..ExecutorConfig::default()
+            },
+        )
+    }
+
+    fn order() -> RebalanceOrder {
+        RebalanceOrder {
+            pool_id: POOL.into(),
+            position: 7,
+            liquidity: 1_000_000_000,
+            from: (-100, 100),
+            to: (200, 400),
+            planned_tick: 300,
+        }
+    }
+
+    fn managed(now: NaiveDateTime) -> managed_positions::Model {
+        managed_positions::Model {
+            id: 1,
+            user_id: 1,
+            pool_id: POOL.into(),
+            position_id: "7".into(),
+            tick_lower: -100,
+            tick_upper: 100,
+            liquidity: "1000000000".into(),
+            status: PositionStatus::Active,
+            created_at: now,
+            updated_at: now,
+            owner: None,
+        }
+    }
+
+    fn sequence_number(signed: &[u8]) -> u64 {
+        u64::from_le_bytes(signed[32..40].try_into().unwrap())
+    }
+
+    fn function_name(signed: &[u8], name: &str) -> bool {
+        signed.windows(name.len()).any(|w| w == name.as_bytes())
+    }
+
+    /// Whether the transaction's packed router args carry `value` as a `u64`
+    fn u64_argument(signed: &[u8], value: u64) -> bool {
+        let argument = value.to_le_bytes();
+        signed.windows(argument.len()).any(|w| w == argument)
+    }
+
+    #[tokio::test]
+    async fn dry_run_only_simulates_the_close() {
+        let executor = executor(MockFullnode::default(), true);
+
+        let outcome = executor.execute(&order(), 300).await.unwrap();
+        assert!(matches!(outcome, Outcome::Simulated { close } if close.success));
+
+        let simulated = executor.fullnode.simulated.borrow();
+        assert_eq!(simulated.len(), 1);
+        assert!(function_name(&simulated[0], "close_position"));
+        assert!(executor.fullnode.submitted.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn closes_then_reopens_with_consecutive_sequence_numbers() {
+        let executor = executor(MockFullnode::default(), false);
+
+        let outcome = executor.execute(&order(), 300).await.unwrap();
+        let Outcome::Executed {
+            close,
+            open,
+            position,
+        } = outcome
+        else {
+            panic!("expected an executed rebalance");
+        };
+        assert_eq!(close.hash, "0x1");
+        assert_eq!(open.hash, "0x2");
+        assert_eq!(position, Some(12));
+
+        let submitted = executor.fullnode.submitted.borrow();
+        assert_eq!(submitted.len(), 2);
+        assert!(function_name(&submitted[0], "close_position"));
+        assert!(function_name(&submitted[1], "open_position"));
+        assert_eq!(sequence_number(&submitted[0]), SEQUENCE_NUMBER);
+        assert_eq!(sequence_number(&submitted[1]), SEQUENCE_NUMBER + 1);
+
+        // Each transaction was simulated, with a blank signature, before being submitted
+        let simulated = executor.fullnode.simulated.borrow();
+        assert_eq!(simulated.len(), 2);
+        assert!(
+            simulated[0][simulated[0].len() - 64..]
+                .iter()
+                .all(|b| *b == 0)
+        );
+        assert_ne!(simulated[0], submitted[0]);
+    }
+
+    #[tokio::test]
+    async fn reopening_is_capped_at_what_the_close_returned() {
+        let fullnode = MockFullnode {
+            withdrawn: Some((1_234_567, 7_654_321)),
+            ..MockFullnode::default()
+        };
+        let executor = executor(fullnode, false);
+
+        executor.execute(&order(), 300).await.unwrap();
+        let submitted = executor.fullnode.submitted.borrow();
+        assert!(u64_argument(&submitted[1], 1_234_567));
+        assert!(u64_argument(&submitted[1], 7_654_321));
+    }
+
+    #[tokio::test]
+    async fn reopening_falls_back_to_the_close_minimums() {
+        let executor = executor(MockFullnode::default(), false);
+
+        executor.execute(&order(), 300).await.unwrap();
+        let order = order();
+        let expected = get_amounts_for_ticks(
+            get_sqrt_price_at_tick(300).unwrap(),
+            order.from.0,
+            order.from.1,
+            order.liquidity,
+            false,
+        )
+        .unwrap();
+        let submitted = executor.fullnode.submitted.borrow();
+        for amount in [expected.amount_a, expected.amount_b] {
+            let minimum = min_out(amount, 50).unwrap();
+            assert!(u64_argument(&submitted[0], minimum));
+            assert!(u64_argument(&submitted[1], minimum));
+        }
+    }
+
+    #[tokio::test]
+    async fn second_rebalance_closes_the_reopened_liquidity() {
+        let executor = executor(MockFullnode::default(), false);
+        let now = Utc::now().naive_utc();
+        let managed = managed(now);
+        let recommend = |managed: &managed_positions::Model, to| Recommendation {
+            position_id: managed.id,
+            pool_id: POOL.into(),
+            current_tick: 300,
+            trigger: Trigger::OutOfRange,
+            from: (managed.tick_lower as i32, managed.tick_upper as i32),
+            to,
+        };
+
+        let first = order_for(&managed, &recommend(&managed, (200, 400))).unwrap();
+        let Outcome::Executed { position, .. } = executor.execute(&first, 300).await.unwrap()
+        else {
+            panic!("expected an executed rebalance");
+        };
+        let opened = Opened {
+            position: position.unwrap(),
+            liquidity: Some(3_000_000_000),
+        };
+        let managed = moved(managed, &first, Some(opened), now);
+        assert_eq!(managed.status, PositionStatus::Active);
+
+        let second = order_for(&managed, &recommend(&managed, (250, 350))).unwrap();
+        assert_eq!(second.position, 12);
+        assert_eq!(second.liquidity, 3_000_000_000);
+        assert_eq!(second.from, first.to);
+        executor.execute(&second, 300).await.unwrap();
+
+        // The second close expects what the reopened position holds, not the first one
+        let expected = get_amounts_for_ticks(
+            get_sqrt_price_at_tick(300).unwrap(),
+            200,
+            400,
+            3_000_000_000,
+            false,
+        )
+        .unwrap();
+        let submitted = executor.fullnode.submitted.borrow();
+        assert!(function_name(&submitted[2], "close_position"));
+        for amount in [expected.amount_a, expected.amount_b] {
+            assert!(u64_argument(&submitted[2], min_out(amount, 50).unwrap()));
+        }
+    }
+
+    #[test]
+    fn unknown_liquidity_closes_the_managed_position() {
+        let now = Utc::now().naive_utc();
+        let managed = managed(now);
+        let opened = Opened {
+            position: 12,
+            liquidity: None,
+        };
+
+        let moved = moved(managed.clone(), &order(), Some(opened), now);
+        assert_eq!(moved.status, PositionStatus::Closed);
+        assert_eq!(moved.position_id, "12");
+        assert_eq!(moved.liquidity, managed.liquidity);
+    }
+
+    #[tokio::test]
+    async fn failed_simulation_submits_nothing() {
+        let fullnode = MockFullnode {
+            simulations: RefCell::new(vec![false]),
+            ..MockFullnode::default()
+        };
+        let executor = executor(fullnode, false);
+
+        let error = executor.execute(&order(), 300).await.unwrap_err();
+        assert!(error.to_string().contains("ABORTED"));
+        assert!(executor.fullnode.submitted.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_reopening_reports_the_close() {
+        let fullnode = MockFullnode {
+            simulations: RefCell::new(vec![true, false]),
+            ..MockFullnode::default()
+        };
+        let executor = executor(fullnode, false);
+
+        let outcome = executor.execute(&order(), 300).await.unwrap();
+        let Outcome::Stranded {
+            close_hash,
+            open_hash,
+            error,
+        } = outcome
+        else {
+            panic!("expected a stranded rebalance");
+        };
+        assert_eq!(close_hash, "0x1");
+        assert_eq!(open_hash, None);
+        assert!(error.contains("ABORTED"));
+        assert_eq!(executor.fullnode.submitted.borrow().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_transactions_after_the_close_are_stranded() {
+        // The close may have landed
+        let fullnode = MockFullnode {
+            lost: RefCell::new(vec![true]),
+            ..MockFullnode::default()
+        };
+        let executor = executor(fullnode, false);
+        assert!(matches!(
+            executor.execute(&order(), 300).await.unwrap(),
+            Outcome::Stranded { close_hash, open_hash: None, .. } if close_hash == "0x1"
+        ));
+
+        // The reopening may have landed
+        let fullnode = MockFullnode {
+            lost: RefCell::new(vec![false, true]),
+            ..MockFullnode::default()
+        };
+        let executor = executor(fullnode, false);
+        assert!(matches!(
+            executor.execute(&order(), 300).await.unwrap(),
+            Outcome::Stranded { close_hash, open_hash: Some(open_hash), .. }
+                if close_hash == "0x1" && open_hash == "0x2"
+        ));
+    }
+
+    #[tokio::test]
+    async fn reverted_close_is_an_error() {
+        let fullnode = MockFullnode {
+            failed_commit: true,
+            ..MockFullnode::default()
+        };
+        let executor = executor(fullnode, false);
+
+        assert!(executor.execute(&order(), 300).await.is_err());
+        assert_eq!(executor.fullnode.submitted.borrow().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn price_move_beyond_slippage_touches_nothing() {
+        let executor = executor(MockFullnode::default(), false);
+
+        // 60 ticks is about 60 bps, above the default 50
+        let error = executor.execute(&order(), 360).await.unwrap_err();
+        assert!(error.to_string().contains("Price moved"));
+        assert!(executor.fullnode.simulated.borrow().is_empty());
+
+        assert!(executor.execute(&order(), 340).await.is_ok());
+    }
+
+    #[test]
+    fn minimum_amounts_apply_the_slippage() {
+        assert_eq!(min_out(10_000, 50).unwrap(), 9_950);
+        assert_eq!(min_out(10_000, 0).unwrap(), 10_000);
+        assert_eq!(min_out(10_000, 20_000).unwrap(), 0);
+        assert!(min_out(u128::MAX / BPS, 0).is_err());
+    }
+
+    #[test]
+    fn reads_the_opened_position_from_its_router_event() {
+        let events = vec![
+            json!({ "type": "0x1::other::OpenPositionEvent", "data": { "pool": POOL, "position_idx": "9" } }),
+            json!({ "type": OPENED, "data": { "pool": "0xdef", "position_idx": "8" } }),
+            json!({ "type": OPENED, "data": { "pool": POOL, "position_idx": "3" } }),
+        ];
+        assert_eq!(opened_position(&events, OPENED, POOL), Some(3));
+        assert_eq!(opened_position(&events[..2], OPENED, POOL), None);
+        assert_eq!(opened_position(&[], OPENED, POOL), None);
+
+        // Two opens in the same pool can't be told apart
+        let twice = [events[2].clone(), events[2].clone()];
+        assert_eq!(opened_position(&twice, OPENED, POOL), None);
+    }
+
+    #[test]
+    fn reads_the_withdrawn_amounts_of_the_closed_position() {
+        let closed = |position: &str| {
+            json!({
+                "type": CLOSED,
+                "data": { "pool": POOL, "position_idx": position, "amount_a": "3", "amount_b": "4" },
+            })
+        };
+        assert_eq!(withdrawn(&[closed("7")], CLOSED, POOL, 7), Some((3, 4)));
+        assert_eq!(withdrawn(&[closed("6")], CLOSED, POOL, 7), None);
+        assert_eq!(withdrawn(&[], CLOSED, POOL, 7), None);
+    }
+}
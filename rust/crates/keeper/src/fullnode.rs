@@ -0,0 +1,185 @@
+//! The fullnode calls the keeper relies on, behind a trait so tests can swap in a mock.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use liquidity_core::address::AccountAddress;
+use reqwest::{Client, header::CONTENT_TYPE};
+use serde::Deserialize;
+use serde_json::Value;
+
+const SIGNED_TRANSACTION_BCS: &str = "application/x.aptos.signed_transaction+bcs";
+/// `wait_by_hash` long polls for a few seconds, retried until the transaction lands
+const WAIT_ATTEMPTS: usize = 10;
+
+/// Outcome of a simulated transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: u64,
+}
+
+/// Outcome of a transaction included in a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommittedTransaction {
+    pub hash: String,
+    pub success: bool,
+    pub vm_status: String,
+    pub events: Vec<Value>,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Fullnode {
+    async fn chain_id(&self) -> Result<u8>;
+    async fn sequence_number(&self, account: &AccountAddress) -> Result<u64>;
+    async fn gas_unit_price(&self) -> Result<u64>;
+    /// Run signed transaction bytes without committing them
+    async fn simulate(&self, signed_transaction: &[u8]) -> Result<Simulation>;
+    /// Submit signed transaction bytes and return the transaction hash
+    async fn submit(&self, signed_transaction: &[u8]) -> Result<String>;
+    async fn wait_for_transaction(&self, hash: &str) -> Result<CommittedTransaction>;
+}
+
+/// Fullnode REST API
+pub struct RestFullnode {
+    client: Client,
+    base_url: String,
+}
+
+#[derive(Deserialize)]
+struct LedgerInfo {
+    chain_id: u8,
+}
+
+#[derive(Deserialize)]
+struct AccountInfo {
+    sequence_number: String,
+}
+
+#[derive(Deserialize)]
+struct GasEstimate {
+    gas_estimate: u64,
+}
+
+#[derive(Deserialize)]
+struct PendingTransaction {
+    hash: String,
+}
+
+#[derive(Deserialize)]
+struct UserTransaction {
+    #[serde(rename = "type")]
+    kind: String,
+    hash: String,
+    #[serde(default)]
+    success: bool,
+    #[serde(default)]
+    vm_status: String,
+    #[serde(default)]
+    gas_used: String,
+    #[serde(default)]
+    events: Vec<Value>,
+}
+
+impl RestFullnode {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn get<T: for<'de> Deserialize<'de>>(&self, path: &str) -> Result<T> {
+        let response = self
+            .client
+            .get(format!("{}{path}", self.base_url))
+            .send()
+            .await?;
+
+        Self::parse(response).await
+    }
+
+    async fn post_bcs<T: for<'de> Deserialize<'de>>(&self, path: &str, body: &[u8]) -> Result<T> {
+        let response = self
+            .client
+            .post(format!("{}{path}", self.base_url))
+            .header(CONTENT_TYPE, SIGNED_TRANSACTION_BCS)
+            .body(body.to_vec())
+            .send()
+            .await?;
+
+        Self::parse(response).await
+    }
+
+    async fn parse<T: for<'de> Deserialize<'de>>(response: reqwest::Response) -> Result<T> {
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("Fullnode responded {status}: {body}");
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+impl Fullnode for RestFullnode {
+    async fn chain_id(&self) -> Result<u8> {
+        let info: LedgerInfo = self.get("/").await?;
+        Ok(info.chain_id)
+    }
+
+    async fn sequence_number(&self, account: &AccountAddress) -> Result<u64> {
+        let info: AccountInfo = self.get(&format!("/accounts/{account}")).await?;
+        info.sequence_number
+            .parse()
+            .context("Invalid sequence number")
+    }
+
+    async fn gas_unit_price(&self) -> Result<u64> {
+        let estimate: GasEstimate = self.get("/estimate_gas_price").await?;
+        Ok(estimate.gas_estimate)
+    }
+
+    async fn simulate(&self, signed_transaction: &[u8]) -> Result<Simulation> {
+        let transactions: Vec<UserTransaction> = self
+            .post_bcs("/transactions/simulate", signed_transaction)
+            .await?;
+        let transaction = transactions
+            .into_iter()
+            .next()
+            .context("Empty simulation response")?;
+
+        Ok(Simulation {
+            success: transaction.success,
+            vm_status: transaction.vm_status,
+            gas_used: transaction.gas_used.parse().unwrap_or_default(),
+        })
+    }
+
+    async fn submit(&self, signed_transaction: &[u8]) -> Result<String> {
+        let pending: PendingTransaction =
+            self.post_bcs("/transactions", signed_transaction).await?;
+        Ok(pending.hash)
+    }
+
+    async fn wait_for_transaction(&self, hash: &str) -> Result<CommittedTransaction> {
+        for _ in 0..WAIT_ATTEMPTS {
+            let transaction: UserTransaction = self
+                .get(&format!("/transactions/wait_by_hash/{hash}"))
+                .await?;
+
+            if transaction.kind != "pending_transaction" {
+                return Ok(CommittedTransaction {
+                    hash: transaction.hash,
+                    success: transaction.success,
+                    vm_status: transaction.vm_status,
+                    events: transaction.events,
+                });
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+
+        bail!("Transaction {hash} is still pending")
+    }
+}
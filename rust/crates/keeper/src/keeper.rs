@@ -0,0 +1,390 @@
+use std::{fmt, path::PathBuf, time::Duration};
+
+use anyhow::{Context, Result, bail};
+use db::entities::{
+    managed_positions::{self, Entity as ManagedPositions},
+    sea_orm_active_enums::{MovementType, PositionStatus},
+    user_movements,
+};
+use rebalancer::{Planner, Recommendation};
+use scraper_common::shutdown_signal;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    DatabaseConnection, DatabaseTransaction, EntityTrait, TransactionTrait,
+    sqlx::types::chrono::{NaiveDateTime, Utc},
+};
+use serde_json::json;
+use tapp::TappChainClient;
+use tokio::sync::watch;
+
+use crate::{
+    executor::{Executor, Outcome, RebalanceOrder},
+    fullnode::{CommittedTransaction, Fullnode},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CycleStats {
+    pub planned: usize,
+    pub executed: usize,
+    pub simulated: usize,
+    pub failed: usize,
+}
+
+impl fmt::Display for CycleStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} planned, {} executed, {} simulated, {} failed",
+            self.planned, self.executed, self.simulated, self.failed
+        )
+    }
+}
+
+/// Polls the planner and executes its recommendations
+pub struct Keeper<F> {
+    database: DatabaseConnection,
+    planner: Planner,
+    chain_client: TappChainClient,
+    executor: Executor<F>,
+    /// Nothing is executed while this file exists
+    kill_switch: Option<PathBuf>,
+}
+
+impl<F: Fullnode> Keeper<F> {
+    pub fn new(
+        database: DatabaseConnection,
+        planner: Planner,
+        chain_client: TappChainClient,
+        executor: Executor<F>,
+        kill_switch: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            database,
+            planner,
+            chain_client,
+            executor,
+            kill_switch,
+        }
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.kill_switch.as_ref().is_some_and(|path| path.exists())
+    }
+
+    /// Run a cycle every `interval` until interrupted
+    ///
+    /// Ctrl+C and SIGTERM are listened for from the start, so a signal arriving mid-cycle lets
+    /// the running rebalance finish, and no other starts, before the keeper stops.
+    pub async fn run(&self, interval: Duration) -> Result<()> {
+        let (stop_tx, mut stop) = watch::channel(false);
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            stop_tx.send_replace(true);
+        });
+
+        loop {
+            if self.is_halted() {
+                println!("Kill switch is set, skipping cycle.");
+            } else {
+                match self.cycle_until(&stop).await {
+                    Ok(stats) => println!("Cycle: {stats}"),
+                    Err(e) => eprintln!("Cycle failed: {e:#}"),
+                }
+            }
+
+            tokio::select! {
+                _ = stop.wait_for(|stop| *stop) => {
+                    println!("Stopping keeper.");
+                    return Ok(());
+                }
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    }
+
+    /// Execute every current recommendation, one position at a time
+    ///
+    /// A failing position is reported and doesn't stop the others. The kill switch is checked
+    /// again before each position.
+    pub async fn cycle(&self) -> Result<CycleStats> {
+        self.cycle_until(&watch::channel(false).1).await
+    }
+
+    /// [`Keeper::cycle`], stopping before the next position once `stop` is set
+    async fn cycle_until(&self, stop: &watch::Receiver<bool>) -> Result<CycleStats> {
+        let owner = self.executor.signer().address();
+        let plan = self.planner.plan(None, Some(&owner)).await?;
+        let mut stats = CycleStats {
+            planned: plan.len(),
+            ..CycleStats::default()
+        };
+
+        for recommendation in &plan {
+            if self.is_halted() {
+                println!("Kill switch is set, stopping cycle.");
+                break;
+            }
+            if *stop.borrow() {
+                println!("Shutting down, stopping cycle.");
+                break;
+            }
+
+            match self.rebalance(recommendation).await {
+                Ok(Outcome::Simulated { close }) => {
+                    println!(
+                        "[dry run] {recommendation}: close simulated, {} gas",
+                        close.gas_used
+                    );
+                    stats.simulated += 1;
+                }
+                Ok(Outcome::Executed { open, .. }) => {
+                    println!("{recommendation}: executed in {}", open.hash);
+                    stats.executed += 1;
+                }
+                Ok(Outcome::Stranded {
+                    close_hash, error, ..
+                }) => {
+                    eprintln!(
+                        "{recommendation}: closed in {close_hash} but not reopened, marked closed: {error}"
+                    );
+                    stats.failed += 1;
+                }
+                Err(e) => {
+                    eprintln!("{recommendation}: {e:#}");
+                    stats.failed += 1;
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+
+    async fn rebalance(&self, recommendation: &Recommendation) -> Result<Outcome> {
+        let managed = ManagedPositions::find_by_id(recommendation.position_id)
+            .one(&self.database)
+            .await?
+            .with_context(|| {
+                format!("Managed position {} not found", recommendation.position_id)
+            })?;
+        let order = order_for(&managed, recommendation)?;
+
+        // Read again right before acting, the guard compares it to the planned tick
+        let current_tick = self
+            .chain_client
+            .get_current_tick_index(&order.pool_id)
+            .await?;
+        let outcome = self
+            .executor
+            .execute(&order, i32::try_from(current_tick)?)
+            .await?;
+
+        match &outcome {
+            Outcome::Simulated { .. } => {}
+            Outcome::Executed {
+                close,
+                open,
+                position,
+            } => {
+                let opened = match position {
+                    Some(position) => Some(Opened {
+                        position: *position,
+                        liquidity: self.liquidity_of(&order.pool_id, *position).await,
+                    }),
+                    None => None,
+                };
+                record(&self.database, managed, &order, close, open, opened).await?;
+            }
+            Outcome::Stranded {
+                close_hash,
+                open_hash,
+                error,
+            } => {
+                let tx_info = json!({
+                    "pool_id": order.pool_id,
+                    "close_tx_hash": close_hash,
+                    "open_tx_hash": open_hash,
+                    "from": { "position": order.position, "tick_lower": order.from.0, "tick_upper": order.from.1 },
+                    "error": error,
+                    "funds_on": self.executor.signer().address().to_string(),
+                });
+                record_stranded(&self.database, managed, close_hash, tx_info).await?;
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Liquidity of a freshly opened position, `None` when it can't be read
+    async fn liquidity_of(&self, pool_id: &str, position: u64) -> Option<u128> {
+        match self.chain_client.get_position(pool_id, position).await {
+            Ok(onchain) => onchain.liquidity.parse().ok(),
+            Err(e) => {
+                eprintln!("Reading position {position} of {pool_id} failed: {e:#}");
+                None
+            }
+        }
+    }
+}
+
+/// The new position, as far as the keeper could read it back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Opened {
+    pub position: u64,
+    /// Read from chain after the open, `None` when the read failed
+    pub liquidity: Option<u128>,
+}
+
+/// Order moving a managed position as recommended
+pub(crate) fn order_for(
+    managed: &managed_positions::Model,
+    recommendation: &Recommendation,
+) -> Result<RebalanceOrder> {
+    Ok(RebalanceOrder {
+        pool_id: managed.pool_id.clone(),
+        position: managed
+            .position_id
+            .parse()
+            .context("Invalid position index")?,
+        liquidity: managed.liquidity.parse().context("Invalid liquidity")?,
+        from: recommendation.from,
+        to: recommendation.to,
+        planned_tick: recommendation.current_tick,
+    })
+}
+
+/// The managed position once moved by `order`
+///
+/// Without the new index and liquidity it can't be acted on again, it is marked closed so the
+/// planner stops picking it. The index and range it is known to have moved to are kept.
+pub(crate) fn moved(
+    mut managed: managed_positions::Model,
+    order: &RebalanceOrder,
+    opened: Option<Opened>,
+    now: NaiveDateTime,
+) -> managed_positions::Model {
+    managed.updated_at = now;
+    match opened {
+        Some(Opened {
+            position,
+            liquidity,
+        }) => {
+            managed.position_id = position.to_string();
+            managed.tick_lower = order.to.0.into();
+            managed.tick_upper = order.to.1.into();
+            match liquidity {
+                Some(liquidity) => managed.liquidity = liquidity.to_string(),
+                None => managed.status = PositionStatus::Closed,
+            }
+        }
+        None => managed.status = PositionStatus::Closed,
+    }
+    managed
+}
+
+/// Store the move as a rebalance movement and point the managed position at its new range,
+/// liquidity included, in one transaction
+///
+/// An error is returned after storing when the new position couldn't be fully read, it then
+/// needs to be recorded by hand.
+async fn record(
+    database: &DatabaseConnection,
+    managed: managed_positions::Model,
+    order: &RebalanceOrder,
+    close: &CommittedTransaction,
+    open: &CommittedTransaction,
+    opened: Option<Opened>,
+) -> Result<()> {
+    let now = Utc::now().naive_utc();
+    let tx_info = json!({
+        "pool_id": order.pool_id,
+        "close_tx_hash": close.hash,
+        "open_tx_hash": open.hash,
+        "from": { "position": order.position, "tick_lower": order.from.0, "tick_upper": order.from.1 },
+        "to": {
+            "position": opened.map(|o| o.position),
+            "tick_lower": order.to.0,
+            "tick_upper": order.to.1,
+            "liquidity": opened.and_then(|o| o.liquidity).map(|l| l.to_string()),
+        },
+    });
+
+    let txn = database.begin().await?;
+    insert_movement(&txn, managed.user_id, &open.hash, tx_info, now).await?;
+    let id = managed.id;
+    update_managed(&txn, managed.clone(), moved(managed, order, opened, now)).await?;
+    txn.commit().await?;
+
+    match opened {
+        Some(Opened {
+            liquidity: Some(_), ..
+        }) => Ok(()),
+        Some(Opened { position, .. }) => bail!(
+            "Managed position {id} moved to index {position} in {} but its liquidity couldn't be read, it was marked closed and needs to be updated by hand",
+            open.hash
+        ),
+        None => bail!(
+            "Managed position {id} moved in {} but its new index wasn't found, it was marked closed and the new position needs to be recorded by hand",
+            open.hash
+        ),
+    }
+}
+
+/// Record a close that wasn't followed by a reopening, and mark the managed position closed so
+/// the burnt index isn't acted on again
+async fn record_stranded(
+    database: &DatabaseConnection,
+    managed: managed_positions::Model,
+    close_hash: &str,
+    tx_info: serde_json::Value,
+) -> Result<()> {
+    let now = Utc::now().naive_utc();
+    let txn = database.begin().await?;
+    insert_movement(&txn, managed.user_id, close_hash, tx_info, now).await?;
+    let mut closed = managed.clone();
+    closed.status = PositionStatus::Closed;
+    closed.updated_at = now;
+    update_managed(&txn, managed, closed).await?;
+    txn.commit().await?;
+
+    Ok(())
+}
+
+async fn insert_movement(
+    txn: &DatabaseTransaction,
+    user_id: i32,
+    tx_hash: &str,
+    tx_info: serde_json::Value,
+    now: NaiveDateTime,
+) -> Result<()> {
+    user_movements::ActiveModel {
+        id: NotSet,
+        user_id: Set(user_id),
+        tx_hash: Set(tx_hash.to_string()),
+        tx_info: Set(Some(tx_info.to_string())),
+        movement_type: Set(MovementType::Rebalance),
+        created_at: Set(now),
+    }
+    .insert(txn)
+    .await?;
+
+    Ok(())
+}
+
+/// Write the fields the keeper changes, from `before` to `after`
+async fn update_managed(
+    txn: &DatabaseTransaction,
+    before: managed_positions::Model,
+    after: managed_positions::Model,
+) -> Result<()> {
+    let mut model: managed_positions::ActiveModel = before.into();
+    model.position_id = Set(after.position_id);
+    model.tick_lower = Set(after.tick_lower);
+    model.tick_upper = Set(after.tick_upper);
+    model.liquidity = Set(after.liquidity);
+    model.status = Set(after.status);
+    model.updated_at = Set(after.updated_at);
+    model.update(txn).await?;
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+//! Executes the rebalancer's recommendations on-chain with a local signing key.
+//!
+//! [`executor`] turns a recommendation into signed TAPP router calls, simulated before they
+//! are submitted through a [`fullnode::Fullnode`]. [`keeper`] runs it in a loop and records
+//! each move in the database.
+
+pub mod executor;
+pub mod fullnode;
+pub mod keeper;
+pub mod signer;
+pub mod transaction;
+
+pub use executor::{Executor, ExecutorConfig, Outcome, RebalanceOrder};
+pub use fullnode::{Fullnode, RestFullnode};
+pub use keeper::Keeper;
+pub use signer::LocalSigner;
@@ -0,0 +1,124 @@
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use config::Settings;
+use keeper::{Executor, ExecutorConfig, Keeper, LocalSigner, RestFullnode};
+use rebalancer::{Planner, RebalanceConfig};
+use tapp::{
+    TappChainClient,
+    transactions::{LAYOUT_VERIFIED, TappTransactionBuilder},
+    types::Network,
+};
+
+/// Move managed positions on-chain following the rebalancer's plan
+#[derive(Parser)]
+#[command(name = "keeper")]
+struct Cli {
+    /// File holding the hex encoded Ed25519 private key, read from KEEPER_PRIVATE_KEY if unset
+    #[arg(long)]
+    key_file: Option<PathBuf>,
+    /// Seconds between two cycles
+    #[arg(long, default_value_t = 300)]
+    interval_secs: u64,
+    /// Run a single cycle and exit
+    #[arg(long)]
+    once: bool,
+    /// Simulate the transactions without submitting them, required until the router layouts
+    /// are verified
+    #[arg(long)]
+    dry_run: bool,
+    /// Nothing is executed while this file exists
+    #[arg(long)]
+    kill_switch: Option<PathBuf>,
+    /// Largest price move since planning, and shortfall on withdrawn amounts, in basis points
+    #[arg(long, default_value_t = 50)]
+    max_slippage_bps: u32,
+    #[arg(long, default_value_t = 20_000)]
+    max_gas_amount: u64,
+    /// Overrides the configured fullnode
+    #[arg(long)]
+    fullnode_url: Option<String>,
+    /// Drift from the range center triggering a move, as a fraction of the half width
+    #[arg(long, default_value_t = 0.8)]
+    drift: f64,
+    /// Move positions that haven't moved for this many hours
+    #[arg(long)]
+    max_age_hours: Option<u64>,
+}
+
+impl Cli {
+    fn rebalance_config(&self) -> RebalanceConfig {
+        RebalanceConfig {
+            drift_threshold: Some(self.drift),
+            max_age: self.max_age_hours.map(|h| Duration::from_secs(h * 3600)),
+            ..RebalanceConfig::default()
+        }
+    }
+
+    fn executor_config(&self) -> ExecutorConfig {
+        ExecutorConfig {
+            dry_run: self.dry_run,
+            max_slippage_bps: self.max_slippage_bps,
+            max_gas_amount: self.max_gas_amount,
+            ..ExecutorConfig::default()
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    if !cli.dry_run && !LAYOUT_VERIFIED {
+        bail!(
+            "Router payloads haven't been checked against captured transactions yet, run with --dry-run"
+        );
+    }
+    let settings = Settings::load()?;
+    println!("{settings}");
+
+    let signer = LocalSigner::load(cli.key_file.as_deref())?;
+    println!(
+        "Starting keeper for {}{}.",
+        signer.address(),
+        if cli.dry_run { " (dry run)" } else { "" }
+    );
+
+    let connection =
+        db::create_pool(&settings.database.url, settings.database.max_connections).await?;
+    let network = Network::try_from(settings.network)?;
+    let fullnode_url = cli
+        .fullnode_url
+        .as_deref()
+        .unwrap_or(&settings.rpc.fullnode_url);
+
+    let planner = Planner::new(
+        connection.clone(),
+        TappChainClient::from_network(network, fullnode_url)?,
+        cli.rebalance_config(),
+    );
+    let executor = Executor::new(
+        RestFullnode::new(fullnode_url),
+        signer,
+        TappTransactionBuilder::new(network),
+        cli.executor_config(),
+    );
+    let keeper = Keeper::new(
+        connection,
+        planner,
+        TappChainClient::from_network(network, fullnode_url)?,
+        executor,
+        cli.kill_switch.clone(),
+    );
+
+    if cli.once {
+        if keeper.is_halted() {
+            println!("Kill switch is set, nothing to do.");
+        } else {
+            println!("Cycle: {}", keeper.cycle().await?);
+        }
+        return Ok(());
+    }
+
+    keeper.run(Duration::from_secs(cli.interval_secs)).await
+}
@@ -0,0 +1,156 @@
+use std::{fmt, path::Path};
+
+use anyhow::{Context, Result, bail};
+use ed25519_dalek::{Signer, SigningKey};
+use liquidity_core::address::AccountAddress;
+use sha3::{Digest, Sha3_256};
+
+use crate::transaction::RawTransaction;
+
+/// Environment variable holding the key when no key file is given
+pub const PRIVATE_KEY_ENV: &str = "KEEPER_PRIVATE_KEY";
+
+/// Scheme byte appended to the public key when deriving a legacy Ed25519 account address.
+/// SingleKey accounts use another scheme and aren't supported.
+const ED25519_SCHEME: u8 = 0;
+/// Prefix of AIP-80 formatted private keys
+const AIP80_PREFIX: &str = "ed25519-priv-";
+
+/// Key of the legacy Ed25519 account the keeper signs for
+pub struct LocalSigner {
+    key: SigningKey,
+    address: AccountAddress,
+}
+
+impl LocalSigner {
+    pub fn new(key: SigningKey) -> Self {
+        let mut hasher = Sha3_256::new();
+        hasher.update(key.verifying_key().as_bytes());
+        hasher.update([ED25519_SCHEME]);
+
+        Self {
+            key,
+            address: AccountAddress(hasher.finalize().into()),
+        }
+    }
+
+    /// Parse a hex encoded key, with or without `0x` and the AIP-80 `ed25519-priv-` prefix
+    pub fn from_hex(encoded: &str) -> Result<Self> {
+        let encoded = encoded.trim();
+        let encoded = encoded.strip_prefix(AIP80_PREFIX).unwrap_or(encoded);
+        let encoded = encoded.strip_prefix("0x").unwrap_or(encoded);
+
+        let mut bytes = [0u8; 32];
+        if hex::decode_to_slice(encoded, &mut bytes).is_err() {
+            bail!("Private key must be 32 hex encoded bytes");
+        }
+        Ok(Self::new(SigningKey::from_bytes(&bytes)))
+    }
+
+    /// Key stored in `key_file`, or in [`PRIVATE_KEY_ENV`] when no file is given
+    pub fn load(key_file: Option<&Path>) -> Result<Self> {
+        let encoded = match key_file {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Reading key file {}", path.display()))?,
+            None => std::env::var(PRIVATE_KEY_ENV)
+                .with_context(|| format!("Either pass a key file or set {PRIVATE_KEY_ENV}"))?,
+        };
+
+        Self::from_hex(&encoded)
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.key.verifying_key().to_bytes()
+    }
+
+    /// Signed transaction bytes, ready to be submitted
+    pub fn sign(&self, raw: &RawTransaction) -> Result<Vec<u8>> {
+        let signature = self.key.sign(&raw.signing_message()?);
+        raw.signed_bytes(&self.public_key(), &signature.to_bytes())
+    }
+
+    /// Transaction bytes for the simulate endpoint, which rejects valid signatures
+    pub fn sign_for_simulation(&self, raw: &RawTransaction) -> Result<Vec<u8>> {
+        raw.signed_bytes(&self.public_key(), &[0; 64])
+    }
+}
+
+impl fmt::Debug for LocalSigner {
+    /// Only the address, the key must never end up in logs
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalSigner")
+            .field("address", &self.address.to_string())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier};
+
+    const KEY: &str = "0x9bf49a6a0755f953811fce125f2683d50429c3bb49e074147e0089a52eae155f";
+
+    #[test]
+    fn parses_plain_and_aip80_keys() {
+        let plain = LocalSigner::from_hex(KEY).unwrap();
+        let aip80 = LocalSigner::from_hex(&format!("ed25519-priv-{KEY}\n")).unwrap();
+        assert_eq!(plain.address(), aip80.address());
+
+        assert!(LocalSigner::from_hex("0x1234").is_err());
+        assert!(LocalSigner::from_hex(&KEY.replace('9', "z")).is_err());
+    }
+
+    #[test]
+    fn derives_the_legacy_ed25519_address() {
+        let signer = LocalSigner::from_hex(KEY).unwrap();
+
+        let mut preimage = signer.public_key().to_vec();
+        preimage.push(0);
+        assert_eq!(
+            signer.address().0.as_slice(),
+            Sha3_256::digest(&preimage).as_slice()
+        );
+    }
+
+    #[test]
+    fn signature_verifies_against_the_signing_message() {
+        let signer = LocalSigner::from_hex(KEY).unwrap();
+        let call = tapp::transactions::TappTransactionBuilder::with_router("0x1".parse().unwrap())
+            .collect_fees("0x2", 1)
+            .unwrap();
+        let raw = RawTransaction {
+            sender: signer.address(),
+            sequence_number: 0,
+            payload: crate::transaction::Payload(call),
+            max_gas_amount: 1_000,
+            gas_unit_price: 100,
+            expiration_timestamp_secs: 60,
+            chain_id: 1,
+        };
+
+        let signed = signer.sign(&raw).unwrap();
+        let signature = Signature::from_slice(&signed[signed.len() - 64..]).unwrap();
+        signer
+            .key
+            .verifying_key()
+            .verify(&raw.signing_message().unwrap(), &signature)
+            .unwrap();
+
+        let simulated = signer.sign_for_simulation(&raw).unwrap();
+        assert_eq!(simulated.len(), signed.len());
+        assert!(simulated[signed.len() - 64..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn debug_does_not_leak_the_key() {
+        let signer = LocalSigner::from_hex(KEY).unwrap();
+        let debug = format!("{signer:?}");
+        assert!(!debug.contains(&KEY[2..]));
+        assert!(debug.contains(&signer.address().to_string()));
+    }
+}
@@ -0,0 +1,122 @@
+//! Raw and signed Aptos transactions, BCS encoded offline.
+
+use anyhow::Result;
+use liquidity_core::address::AccountAddress;
+use serde::{Serialize, Serializer};
+use sha3::{Digest, Sha3_256};
+use tapp::transactions::RouterCall;
+
+/// Salt prepended to every signed raw transaction
+const RAW_TRANSACTION_SALT: &[u8] = b"APTOS::RawTransaction";
+
+/// Variant of `TransactionPayload::EntryFunction`
+const ENTRY_FUNCTION_VARIANT: u32 = 2;
+/// Variant of `TransactionAuthenticator::Ed25519`
+const ED25519_AUTHENTICATOR_VARIANT: u8 = 0;
+
+/// Router call wrapped as a `TransactionPayload`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payload(pub RouterCall);
+
+impl Serialize for Payload {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_variant(
+            "TransactionPayload",
+            ENTRY_FUNCTION_VARIANT,
+            "EntryFunction",
+            &self.0.entry_function,
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RawTransaction {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub payload: Payload,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    pub expiration_timestamp_secs: u64,
+    pub chain_id: u8,
+}
+
+impl RawTransaction {
+    /// Bytes an Ed25519 key signs: the hashed salt followed by the encoded transaction
+    pub fn signing_message(&self) -> Result<Vec<u8>> {
+        let mut message = Sha3_256::digest(RAW_TRANSACTION_SALT).to_vec();
+        message.extend(bcs::to_bytes(self)?);
+        Ok(message)
+    }
+
+    /// Encoded `SignedTransaction`, as accepted by the fullnode's BCS endpoints
+    pub fn signed_bytes(&self, public_key: &[u8; 32], signature: &[u8; 64]) -> Result<Vec<u8>> {
+        let mut bytes = bcs::to_bytes(self)?;
+        bytes.push(ED25519_AUTHENTICATOR_VARIANT);
+        bytes.extend(bcs::to_bytes(public_key.as_slice())?);
+        bytes.extend(bcs::to_bytes(signature.as_slice())?);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tapp::transactions::TappTransactionBuilder;
+
+    fn raw() -> RawTransaction {
+        let call = TappTransactionBuilder::with_router("0x1".parse().unwrap())
+            .collect_fees("0x2", 1)
+            .unwrap();
+
+        RawTransaction {
+            sender: "0x3".parse().unwrap(),
+            sequence_number: 4,
+            payload: Payload(call),
+            max_gas_amount: 5,
+            gas_unit_price: 6,
+            expiration_timestamp_secs: 7,
+            chain_id: 1,
+        }
+    }
+
+    #[test]
+    fn embeds_the_entry_function_payload() {
+        let raw = raw();
+        let bytes = bcs::to_bytes(&raw).unwrap();
+
+        let payload = raw.payload.0.payload_bytes().unwrap();
+        assert_eq!(&bytes[..32], raw.sender.0.as_slice());
+        assert_eq!(&bytes[32..40], 4u64.to_le_bytes().as_slice());
+        assert_eq!(&bytes[40..40 + payload.len()], payload.as_slice());
+
+        let tail = &bytes[40 + payload.len()..];
+        assert_eq!(tail.len(), 8 * 3 + 1);
+        assert_eq!(tail[24], 1);
+    }
+
+    #[test]
+    fn signs_the_salted_transaction() {
+        let raw = raw();
+        let message = raw.signing_message().unwrap();
+
+        assert_eq!(
+            &message[..32],
+            Sha3_256::digest(b"APTOS::RawTransaction").as_slice()
+        );
+        assert_eq!(&message[32..], bcs::to_bytes(&raw).unwrap().as_slice());
+    }
+
+    #[test]
+    fn appends_the_ed25519_authenticator() {
+        let raw = raw();
+        let signed = raw.signed_bytes(&[1; 32], &[2; 64]).unwrap();
+        let unsigned_len = bcs::to_bytes(&raw).unwrap().len();
+
+        let authenticator = &signed[unsigned_len..];
+        assert_eq!(authenticator[0], 0);
+        assert_eq!(authenticator[1], 32);
+        assert_eq!(&authenticator[2..34], [1; 32].as_slice());
+        assert_eq!(authenticator[34], 64);
+        assert_eq!(&authenticator[35..], [2; 64].as_slice());
+    }
+}
@@ -0,0 +1,31 @@
+//! Schema migrations for every table the Rust services use.
+//!
+//! The baseline migrations create the tables with `IF NOT EXISTS`, so they also adopt a
+//! database created by the app's Drizzle schema before these migrations owned it. Entities in
+//! `db` are generated from a migrated database with `scripts/generate-entities.sh`, and the
+//! app's `schema.ts` mirrors the tables by hand for its queries, it is never pushed.
+
+pub use sea_orm_migration::prelude::*;
+
+mod m20261018_000001_create_market_tables;
+mod m20261018_000002_create_user_tables;
+mod m20261018_000003_create_jobs_table;
+mod m20261018_000004_create_vault_shares_table;
+mod m20261018_000005_add_position_addresses;
+mod m20261018_000006_add_managed_position_owners;
+
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20261018_000001_create_market_tables::Migration),
+            Box::new(m20261018_000002_create_user_tables::Migration),
+            Box::new(m20261018_000003_create_jobs_table::Migration),
+            Box::new(m20261018_000004_create_vault_shares_table::Migration),
+            Box::new(m20261018_000005_add_position_addresses::Migration),
+            Box::new(m20261018_000006_add_managed_position_owners::Migration),
+        ]
+    }
+}
@@ -0,0 +1,314 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tokens::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Tokens::Id)
+                            .string_len(66)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Tokens::Symbol).text().not_null())
+                    .col(ColumnDef::new(Tokens::Name).text())
+                    .col(ColumnDef::new(Tokens::About).text())
+                    .col(ColumnDef::new(Tokens::Logo).text())
+                    .col(ColumnDef::new(Tokens::Decimals).integer().not_null())
+                    .col(
+                        ColumnDef::new(Tokens::UpdatedAt)
+                            .timestamp()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Pools::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Pools::Id).string().not_null().primary_key())
+                    .col(ColumnDef::new(Pools::TokenA).string())
+                    .col(ColumnDef::new(Pools::TokenB).string())
+                    .col(ColumnDef::new(Pools::Fee).decimal().not_null())
+                    .col(ColumnDef::new(Pools::Dex).string().not_null())
+                    .col(ColumnDef::new(Pools::PositionIndex).integer().default(0))
+                    .col(double_zero(Pools::TradingApr))
+                    .col(double_zero(Pools::BonusApr))
+                    .col(double_zero(Pools::Tvl))
+                    .col(double_zero(Pools::VolumeDay))
+                    .col(double_zero(Pools::VolumeWeek))
+                    .col(double_zero(Pools::VolumeMonth))
+                    .col(double_zero(Pools::VolumePrevDay))
+                    .col(
+                        ColumnDef::new(Pools::UpdatedAt)
+                            .timestamp()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("pools_token_a_tokens_id_fk")
+                            .from(Pools::Table, Pools::TokenA)
+                            .to(Tokens::Table, Tokens::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("pools_token_b_tokens_id_fk")
+                            .from(Pools::Table, Pools::TokenB)
+                            .to(Tokens::Table, Tokens::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Positions::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Positions::Index).integer().not_null())
+                    .col(ColumnDef::new(Positions::Pool).string().not_null())
+                    .col(
+                        ColumnDef::new(Positions::UpdatedAt)
+                            .timestamp()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Positions::TickLower)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Positions::TickUpper)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Positions::Liquidity).string().not_null())
+                    .primary_key(Index::create().col(Positions::Index).col(Positions::Pool))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("positions_pool_pools_id_fk")
+                            .from(Positions::Table, Positions::Pool)
+                            .to(Pools::Table, Pools::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PoolSnapshots::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PoolSnapshots::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PoolSnapshots::PoolId).string().not_null())
+                    .col(ColumnDef::new(PoolSnapshots::Tvl).double().not_null())
+                    .col(ColumnDef::new(PoolSnapshots::VolumeDay).double().not_null())
+                    .col(
+                        ColumnDef::new(PoolSnapshots::TradingApr)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PoolSnapshots::BonusApr).double().not_null())
+                    .col(
+                        ColumnDef::new(PoolSnapshots::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("pool_snapshots_pool_id_pools_id_fk")
+                            .from(PoolSnapshots::Table, PoolSnapshots::PoolId)
+                            .to(Pools::Table, Pools::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("pool_snapshots_pool_id_created_at_idx")
+                    .table(PoolSnapshots::Table)
+                    .col(PoolSnapshots::PoolId)
+                    .col(PoolSnapshots::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PoolPrices::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(PoolPrices::PoolId).string().not_null())
+                    .col(ColumnDef::new(PoolPrices::Interval).string().not_null())
+                    .col(ColumnDef::new(PoolPrices::Timestamp).timestamp().not_null())
+                    .col(ColumnDef::new(PoolPrices::Price).double().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(PoolPrices::PoolId)
+                            .col(PoolPrices::Interval)
+                            .col(PoolPrices::Timestamp),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("pool_prices_pool_id_pools_id_fk")
+                            .from(PoolPrices::Table, PoolPrices::PoolId)
+                            .to(Pools::Table, Pools::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Chains::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Chains::Id).string().not_null().primary_key())
+                    .col(ColumnDef::new(Chains::Name).string().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Protocols::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Protocols::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Protocols::Name).string().not_null())
+                    .col(ColumnDef::new(Protocols::Url).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(Protocols::Table)
+                    .table(Chains::Table)
+                    .table(PoolPrices::Table)
+                    .table(PoolSnapshots::Table)
+                    .table(Positions::Table)
+                    .table(Pools::Table)
+                    .table(Tokens::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// `double precision NOT NULL DEFAULT 0`, the pools' market metrics
+fn double_zero(column: Pools) -> ColumnDef {
+    ColumnDef::new(column)
+        .double()
+        .not_null()
+        .default(0.0)
+        .to_owned()
+}
+
+#[derive(DeriveIden)]
+pub(crate) enum Tokens {
+    Table,
+    Id,
+    Symbol,
+    Name,
+    About,
+    Logo,
+    Decimals,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub(crate) enum Pools {
+    Table,
+    Id,
+    TokenA,
+    TokenB,
+    Fee,
+    Dex,
+    PositionIndex,
+    TradingApr,
+    BonusApr,
+    Tvl,
+    VolumeDay,
+    VolumeWeek,
+    VolumeMonth,
+    VolumePrevDay,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub(crate) enum Positions {
+    Table,
+    Index,
+    Pool,
+    UpdatedAt,
+    TickLower,
+    TickUpper,
+    Liquidity,
+}
+
+#[derive(DeriveIden)]
+enum PoolSnapshots {
+    Table,
+    Id,
+    PoolId,
+    Tvl,
+    VolumeDay,
+    TradingApr,
+    BonusApr,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PoolPrices {
+    Table,
+    PoolId,
+    Interval,
+    Timestamp,
+    Price,
+}
+
+#[derive(DeriveIden)]
+enum Chains {
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum Protocols {
+    Table,
+    Id,
+    Name,
+    Url,
+}
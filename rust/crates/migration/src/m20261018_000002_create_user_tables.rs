@@ -0,0 +1,282 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20261018_000001_create_market_tables::{Pools, Tokens};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // Postgres has no CREATE TYPE IF NOT EXISTS
+        db.execute_unprepared(
+            "DO $$ BEGIN
+                CREATE TYPE movement_type AS ENUM ('deposit', 'withdraw', 'rebalance');
+            EXCEPTION WHEN duplicate_object THEN NULL;
+            END $$;",
+        )
+        .await?;
+        db.execute_unprepared(
+            "DO $$ BEGIN
+                CREATE TYPE position_status AS ENUM ('active', 'closed');
+            EXCEPTION WHEN duplicate_object THEN NULL;
+            END $$;",
+        )
+        .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Users::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Users::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Users::Index)
+                            .integer()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Users::Address)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Users::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ManagedPositions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ManagedPositions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ManagedPositions::UserId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ManagedPositions::PoolId).string().not_null())
+                    .col(
+                        ColumnDef::new(ManagedPositions::PositionId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ManagedPositions::TickLower)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ManagedPositions::TickUpper)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ManagedPositions::Liquidity)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ManagedPositions::Status)
+                            .custom(PositionStatus::Enum)
+                            .not_null()
+                            .default("active"),
+                    )
+                    .col(
+                        ColumnDef::new(ManagedPositions::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ManagedPositions::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("managed_positions_user_id_users_id_fk")
+                            .from(ManagedPositions::Table, ManagedPositions::UserId)
+                            .to(Users::Table, Users::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("managed_positions_pool_id_pools_id_fk")
+                            .from(ManagedPositions::Table, ManagedPositions::PoolId)
+                            .to(Pools::Table, Pools::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserBalances::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(UserBalances::UserId).integer().not_null())
+                    .col(ColumnDef::new(UserBalances::TokenId).string().not_null())
+                    .col(ColumnDef::new(UserBalances::Amount).string().not_null())
+                    .col(
+                        ColumnDef::new(UserBalances::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(UserBalances::UserId)
+                            .col(UserBalances::TokenId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("user_balances_user_id_users_id_fk")
+                            .from(UserBalances::Table, UserBalances::UserId)
+                            .to(Users::Table, Users::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("user_balances_token_id_tokens_id_fk")
+                            .from(UserBalances::Table, UserBalances::TokenId)
+                            .to(Tokens::Table, Tokens::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserMovements::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UserMovements::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(UserMovements::UserId).integer().not_null())
+                    .col(ColumnDef::new(UserMovements::TxHash).string().not_null())
+                    .col(ColumnDef::new(UserMovements::TxInfo).text())
+                    .col(
+                        ColumnDef::new(UserMovements::MovementType)
+                            .custom(MovementType::Enum)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(UserMovements::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("user_movements_user_id_users_id_fk")
+                            .from(UserMovements::Table, UserMovements::UserId)
+                            .to(Users::Table, Users::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(UserMovements::Table)
+                    .table(UserBalances::Table)
+                    .table(ManagedPositions::Table)
+                    .table(Users::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("DROP TYPE IF EXISTS movement_type, position_status")
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum MovementType {
+    #[sea_orm(iden = "movement_type")]
+    Enum,
+}
+
+#[derive(DeriveIden)]
+enum PositionStatus {
+    #[sea_orm(iden = "position_status")]
+    Enum,
+}
+
+#[derive(DeriveIden)]
+pub(crate) enum Users {
+    Table,
+    Id,
+    Index,
+    Address,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub(crate) enum ManagedPositions {
+    Table,
+    Id,
+    UserId,
+    PoolId,
+    PositionId,
+    TickLower,
+    TickUpper,
+    Liquidity,
+    Status,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum UserBalances {
+    Table,
+    UserId,
+    TokenId,
+    Amount,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum UserMovements {
+    Table,
+    Id,
+    UserId,
+    TxHash,
+    TxInfo,
+    MovementType,
+    CreatedAt,
+}
@@ -0,0 +1,136 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "DO $$ BEGIN
+                CREATE TYPE job_kind AS ENUM (
+                    'refresh_pools', 'refresh_pool', 'refresh_positions', 'refresh_tokens'
+                );
+            EXCEPTION WHEN duplicate_object THEN NULL;
+            END $$;",
+        )
+        .await?;
+        db.execute_unprepared(
+            "DO $$ BEGIN
+                CREATE TYPE job_state AS ENUM ('queued', 'running', 'succeeded', 'dead');
+            EXCEPTION WHEN duplicate_object THEN NULL;
+            END $$;",
+        )
+        .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Jobs::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Jobs::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Jobs::Kind).custom(JobKind::Enum).not_null())
+                    .col(ColumnDef::new(Jobs::Target).string())
+                    .col(
+                        ColumnDef::new(Jobs::State)
+                            .custom(JobState::Enum)
+                            .not_null()
+                            .default("queued"),
+                    )
+                    .col(
+                        ColumnDef::new(Jobs::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(Jobs::MaxAttempts).integer().not_null())
+                    .col(ColumnDef::new(Jobs::LastError).text())
+                    .col(ColumnDef::new(Jobs::Result).json_binary())
+                    .col(
+                        ColumnDef::new(Jobs::RunAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(Jobs::LockedAt).timestamp())
+                    .col(ColumnDef::new(Jobs::FinishedAt).timestamp())
+                    .col(
+                        ColumnDef::new(Jobs::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Jobs::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Workers look up the next due job of a state
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("jobs_state_run_at_idx")
+                    .table(Jobs::Table)
+                    .col(Jobs::State)
+                    .col(Jobs::RunAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Jobs::Table).if_exists().to_owned())
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("DROP TYPE IF EXISTS job_kind, job_state")
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum JobKind {
+    #[sea_orm(iden = "job_kind")]
+    Enum,
+}
+
+#[derive(DeriveIden)]
+enum JobState {
+    #[sea_orm(iden = "job_state")]
+    Enum,
+}
+
+#[derive(DeriveIden)]
+enum Jobs {
+    Table,
+    Id,
+    Kind,
+    Target,
+    State,
+    Attempts,
+    MaxAttempts,
+    LastError,
+    Result,
+    RunAt,
+    LockedAt,
+    FinishedAt,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20261018_000001_create_market_tables::Pools, m20261018_000002_create_user_tables::Users,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Shares aren't tokens, keeping them out of `tokens` and `user_balances` keeps them out
+        // of the token listings and wallet balances
+        manager
+            .create_table(
+                Table::create()
+                    .table(VaultShares::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(VaultShares::UserId).integer().not_null())
+                    .col(ColumnDef::new(VaultShares::PoolId).string().not_null())
+                    .col(ColumnDef::new(VaultShares::Shares).string().not_null())
+                    .col(
+                        ColumnDef::new(VaultShares::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(VaultShares::UserId)
+                            .col(VaultShares::PoolId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("vault_shares_user_id_users_id_fk")
+                            .from(VaultShares::Table, VaultShares::UserId)
+                            .to(Users::Table, Users::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("vault_shares_pool_id_pools_id_fk")
+                            .from(VaultShares::Table, VaultShares::PoolId)
+                            .to(Pools::Table, Pools::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The share supply of a vault sums its rows
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("vault_shares_pool_id_idx")
+                    .table(VaultShares::Table)
+                    .col(VaultShares::PoolId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(VaultShares::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum VaultShares {
+    Table,
+    UserId,
+    PoolId,
+    Shares,
+    UpdatedAt,
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20261018_000001_create_market_tables::Positions;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Hyperion positions are objects: the address identifies them and `index` is only
+        // assigned on first sight. Numbered positions (TAPP, Thala) leave it NULL.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Positions::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(PositionAddress::Address).string_len(66),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("positions_pool_address_idx")
+                    .table(Positions::Table)
+                    .col(Positions::Pool)
+                    .col(PositionAddress::Address)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .if_exists()
+                    .name("positions_pool_address_idx")
+                    .table(Positions::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Positions::Table)
+                    .drop_column(PositionAddress::Address)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PositionAddress {
+    Address,
+}
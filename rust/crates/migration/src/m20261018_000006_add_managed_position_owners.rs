@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20261018_000002_create_user_tables::ManagedPositions;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Account holding the position on-chain. A vault is valued from the positions its own
+        // account holds, rows left NULL are not part of any vault.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ManagedPositions::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(ManagedPositionOwner::Owner).string_len(66),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("managed_positions_pool_owner_idx")
+                    .table(ManagedPositions::Table)
+                    .col(ManagedPositions::PoolId)
+                    .col(ManagedPositionOwner::Owner)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .if_exists()
+                    .name("managed_positions_pool_owner_idx")
+                    .table(ManagedPositions::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ManagedPositions::Table)
+                    .drop_column(ManagedPositionOwner::Owner)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ManagedPositionOwner {
+    Owner,
+}
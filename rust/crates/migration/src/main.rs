@@ -0,0 +1,57 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use config::Settings;
+use migration::{Migrator, MigratorTrait};
+
+/// Apply or revert the database schema migrations
+#[derive(Parser)]
+#[command(name = "migrate")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Apply pending migrations
+    Up {
+        /// Only apply this many, all of them by default
+        #[arg(long)]
+        steps: Option<u32>,
+    },
+    /// Revert applied migrations, newest first
+    Down {
+        #[arg(long, default_value_t = 1)]
+        steps: u32,
+    },
+    /// List the migrations and whether they are applied
+    Status,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let settings = Settings::load()?;
+    println!("{settings}");
+
+    let connection =
+        db::create_pool(&settings.database.url, settings.database.max_connections).await?;
+
+    match cli.command {
+        Command::Up { steps } => {
+            Migrator::up(&connection, steps).await?;
+            println!("Migrations applied.");
+        }
+        Command::Down { steps } => {
+            Migrator::down(&connection, Some(steps)).await?;
+            println!("Reverted {steps} migration(s).");
+        }
+        Command::Status => {
+            for migration in Migrator::get_migration_with_status(&connection).await? {
+                println!("{:<48} {}", migration.name(), migration.status());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use std::{fmt, time::Duration};
+
+use liquidity_core::{
+    analytics::volatility::{log_returns, std_dev},
+    clmm::tick_math::{MAX_TICK, MIN_TICK, align_tick_down, align_tick_up},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RebalanceConfig {
+    /// Move positions whose range no longer contains the current tick
+    pub out_of_range: bool,
+    /// Move positions whose current tick is this far from the center, as a fraction of the
+    /// half width (1.0 is the edge of the range)
+    pub drift_threshold: Option<f64>,
+    /// Move positions that haven't moved for this long
+    pub max_age: Option<Duration>,
+    /// Standard deviations covered by the proposed range
+    pub sigma_k: f64,
+    /// Hours the proposed range should hold for, scales the volatility
+    pub horizon_hours: f64,
+    /// Narrowest half width proposed, in ticks
+    pub min_half_width: i32,
+}
+
+impl Default for RebalanceConfig {
+    fn default() -> Self {
+        Self {
+            out_of_range: true,
+            drift_threshold: Some(0.8),
+            max_age: None,
+            sigma_k: 2.0,
+            horizon_hours: 24.0 * 7.0,
+            min_half_width: 10,
+        }
+    }
+}
+
+/// Range of a managed position, as stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedRange {
+    pub id: i32,
+    pub pool_id: String,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    /// Time since the position last moved
+    pub age: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    OutOfRange,
+    /// Distance to the center as a fraction of the half width
+    Drift(f64),
+    Age(Duration),
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trigger::OutOfRange => write!(f, "out of range"),
+            Trigger::Drift(drift) => write!(f, "drifted {:.0}% from center", drift * 100.0),
+            Trigger::Age(age) => write!(f, "unchanged for {}h", age.as_secs() / 3600),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    pub position_id: i32,
+    pub pool_id: String,
+    pub current_tick: i32,
+    pub trigger: Trigger,
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+}
+
+impl fmt::Display for Recommendation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "position {} on {}: {} at tick {}, move [{}, {}] -> [{}, {}]",
+            self.position_id,
+            self.pool_id,
+            self.trigger,
+            self.current_tick,
+            self.from.0,
+            self.from.1,
+            self.to.0,
+            self.to.1
+        )
+    }
+}
+
+/// First trigger hit by `position`, checked in order: out of range, drift, age
+pub fn trigger(
+    position: &ManagedRange,
+    current_tick: i32,
+    config: &RebalanceConfig,
+) -> Option<Trigger> {
+    let in_range = current_tick >= position.tick_lower && current_tick < position.tick_upper;
+    if !in_range {
+        return config.out_of_range.then_some(Trigger::OutOfRange);
+    }
+
+    if let Some(threshold) = config.drift_threshold {
+        let drift = drift(position.tick_lower, position.tick_upper, current_tick);
+        if drift >= threshold {
+            return Some(Trigger::Drift(drift));
+        }
+    }
+
+    match config.max_age {
+        Some(max_age) if position.age >= max_age => Some(Trigger::Age(position.age)),
+        _ => None,
+    }
+}
+
+/// Distance of `tick` to the center of the range, as a fraction of its half width
+pub fn drift(tick_lower: i32, tick_upper: i32, tick: i32) -> f64 {
+    let half_width = (tick_upper as f64 - tick_lower as f64) / 2.0;
+    if half_width <= 0.0 {
+        return f64::INFINITY;
+    }
+
+    let center = (tick_lower as f64 + tick_upper as f64) / 2.0;
+    (tick as f64 - center).abs() / half_width
+}
+
+/// Standard deviation of the hourly log returns of chronologically ordered hourly prices,
+/// `None` when the prices don't move or are too few
+pub fn hourly_volatility(prices: &[f64]) -> Option<f64> {
+    let volatility = std_dev(&log_returns(prices));
+    (volatility > 0.0).then_some(volatility)
+}
+
+/// Half width, in ticks, covering `sigma_k` standard deviations of the price over the horizon
+///
+/// `volatility` is the standard deviation of hourly log returns, see [`hourly_volatility`].
+/// It is scaled to the horizon by the square root of its length in hours.
+pub fn volatility_half_width(volatility: f64, config: &RebalanceConfig) -> i32 {
+    let sigma = volatility * config.horizon_hours.max(0.0).sqrt();
+    // One tick is a 0.01% price move, so a log price move of x spans x / ln(1.0001) ticks
+    let ticks = config.sigma_k * sigma / 1.0001f64.ln();
+
+    (ticks.ceil() as i32).max(config.min_half_width)
+}
+
+/// Range centered on `current_tick`, aligned on the pool's `tick_spacing`.
+///
+/// Without volatility the width of the current range is kept.
+pub fn propose_range(
+    position: &ManagedRange,
+    current_tick: i32,
+    volatility: Option<f64>,
+    tick_spacing: u32,
+    config: &RebalanceConfig,
+) -> (i32, i32) {
+    let half_width = match volatility {
+        Some(volatility) if volatility > 0.0 => volatility_half_width(volatility, config),
+        _ => ((position.tick_upper - position.tick_lower) / 2).max(config.min_half_width),
+    };
+
+    let lower = align_tick_down(current_tick.saturating_sub(half_width), tick_spacing);
+    let upper = align_tick_up(current_tick.saturating_add(half_width), tick_spacing);
+
+    (
+        lower.max(align_tick_up(MIN_TICK, tick_spacing)),
+        upper.min(align_tick_down(MAX_TICK, tick_spacing)),
+    )
+}
+
+/// Recommendation for `position` if one of the triggers is hit
+pub fn evaluate(
+    position: &ManagedRange,
+    current_tick: i32,
+    volatility: Option<f64>,
+    tick_spacing: u32,
+    config: &RebalanceConfig,
+) -> Option<Recommendation> {
+    let trigger = trigger(position, current_tick, config)?;
+    let to = propose_range(position, current_tick, volatility, tick_spacing, config);
+
+    // Moving to the same range would only burn gas
+    if to == (position.tick_lower, position.tick_upper) {
+        return None;
+    }
+
+    Some(Recommendation {
+        position_id: position.id,
+        pool_id: position.pool_id.clone(),
+        current_tick,
+        trigger,
+        from: (position.tick_lower, position.tick_upper),
+        to,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(tick_lower: i32, tick_upper: i32) -> ManagedRange {
+        ManagedRange {
+            id: 1,
+            pool_id: "0xpool".to_string(),
+            tick_lower,
+            tick_upper,
+            age: Duration::from_secs(3600),
+        }
+    }
+
+    #[test]
+    fn out_of_range_comes_first() {
+        let config = RebalanceConfig::default();
+        assert_eq!(
+            trigger(&position(-100, 100), 100, &config),
+            Some(Trigger::OutOfRange)
+        );
+        assert_eq!(
+            trigger(&position(-100, 100), -101, &config),
+            Some(Trigger::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn drift_threshold() {
+        let config = RebalanceConfig::default();
+        assert_eq!(trigger(&position(-100, 100), 50, &config), None);
+        assert_eq!(
+            trigger(&position(-100, 100), -90, &config),
+            Some(Trigger::Drift(0.9))
+        );
+    }
+
+    #[test]
+    fn age_threshold() {
+        let config = RebalanceConfig {
+            max_age: Some(Duration::from_secs(1800)),
+            ..Default::default()
+        };
+        assert_eq!(
+            trigger(&position(-100, 100), 0, &config),
+            Some(Trigger::Age(Duration::from_secs(3600)))
+        );
+    }
+
+    #[test]
+    fn disabled_triggers() {
+        let config = RebalanceConfig {
+            out_of_range: false,
+            drift_threshold: None,
+            ..Default::default()
+        };
+        assert_eq!(trigger(&position(-100, 100), 500, &config), None);
+        assert_eq!(trigger(&position(-100, 100), 99, &config), None);
+    }
+
+    #[test]
+    fn range_sized_from_volatility() {
+        let config = RebalanceConfig {
+            sigma_k: 1.0,
+            horizon_hours: 100.0,
+            ..Default::default()
+        };
+        // 1% an hour over 100 hours is a 0.1 log move, just over 1000 ticks
+        assert_eq!(volatility_half_width(0.01, &config), 1001);
+        assert_eq!(volatility_half_width(0.0, &config), config.min_half_width);
+    }
+
+    #[test]
+    fn range_sized_from_hourly_prices() {
+        // Alternating 1% moves: hourly log returns of +/- ln(1.01), a std dev of ln(1.01)
+        let prices: Vec<f64> = (0..169)
+            .map(|i| if i % 2 == 0 { 100.0 } else { 101.0 })
+            .collect();
+        let volatility = hourly_volatility(&prices).unwrap();
+        assert!((volatility - 1.01f64.ln()).abs() < 1e-12);
+
+        // 2 sigma over a week: 2 * ln(1.01) * sqrt(168) / ln(1.0001) ticks
+        let config = RebalanceConfig::default();
+        assert_eq!(volatility_half_width(volatility, &config), 2580);
+
+        let flat = vec![100.0; 24];
+        assert_eq!(hourly_volatility(&flat), None);
+        assert_eq!(hourly_volatility(&[100.0]), None);
+    }
+
+    #[test]
+    fn proposal_is_centered_and_aligned() {
+        let config = RebalanceConfig::default();
+        let (lower, upper) = propose_range(&position(-100, 100), 1_000, None, 60, &config);
+
+        assert_eq!((lower, upper), (900, 1140));
+        assert_eq!(lower % 60, 0);
+        assert_eq!(upper % 60, 0);
+    }
+
+    #[test]
+    fn evaluate_recommends_a_move() {
+        let config = RebalanceConfig::default();
+        let recommendation = evaluate(&position(-100, 100), 150, None, 1, &config).unwrap();
+
+        assert_eq!(recommendation.trigger, Trigger::OutOfRange);
+        assert_eq!(recommendation.to, (50, 250));
+        assert!(evaluate(&position(-100, 100), 0, None, 1, &config).is_none());
+    }
+}
@@ -0,0 +1,10 @@
+//! Decides when managed positions should move and where to.
+//!
+//! [`engine`] holds the pure decision logic, [`planner`] feeds it with the managed positions,
+//! current ticks and price history stored in the database.
+
+pub mod engine;
+pub mod planner;
+
+pub use engine::{RebalanceConfig, Recommendation, Trigger};
+pub use planner::Planner;
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Parser;
+use config::Settings;
+use liquidity_core::address::AccountAddress;
+use rebalancer::{Planner, RebalanceConfig};
+use tapp::{TappChainClient, types::Network};
+
+/// Print the rebalance plan of the active managed positions. Nothing is sent on-chain.
+#[derive(Parser)]
+#[command(name = "rebalancer")]
+struct Cli {
+    /// Only plan the positions of this pool
+    #[arg(long)]
+    pool: Option<String>,
+    /// Only plan the positions held by this account
+    #[arg(long)]
+    owner: Option<AccountAddress>,
+    /// Drift from the range center triggering a move, as a fraction of the half width
+    #[arg(long, default_value_t = 0.8)]
+    drift: f64,
+    /// Disable the drift trigger
+    #[arg(long)]
+    no_drift: bool,
+    /// Keep positions that are out of range
+    #[arg(long)]
+    ignore_out_of_range: bool,
+    /// Move positions that haven't moved for this many hours
+    #[arg(long)]
+    max_age_hours: Option<u64>,
+    /// Standard deviations covered by the proposed ranges
+    #[arg(long, default_value_t = 2.0)]
+    k: f64,
+    /// Hours the proposed ranges should hold for
+    #[arg(long, default_value_t = 168.0)]
+    horizon_hours: f64,
+    /// Narrowest proposed half width, in ticks
+    #[arg(long, default_value_t = 10)]
+    min_half_width: i32,
+}
+
+impl Cli {
+    fn config(&self) -> RebalanceConfig {
+        RebalanceConfig {
+            out_of_range: !self.ignore_out_of_range,
+            drift_threshold: (!self.no_drift).then_some(self.drift),
+            max_age: self.max_age_hours.map(|h| Duration::from_secs(h * 3600)),
+            sigma_k: self.k,
+            horizon_hours: self.horizon_hours,
+            min_half_width: self.min_half_width,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let settings = Settings::load()?;
+    println!("{settings}");
+
+    let connection =
+        db::create_pool(&settings.database.url, settings.database.max_connections).await?;
+
+    let planner = Planner::new(
+        connection,
+        TappChainClient::from_network(
+            Network::try_from(settings.network)?,
+            &settings.rpc.fullnode_url,
+        )?,
+        cli.config(),
+    );
+
+    let plan = planner
+        .plan(cli.pool.as_deref(), cli.owner.as_ref())
+        .await?;
+
+    if plan.is_empty() {
+        println!("No position needs to move.");
+    }
+    for recommendation in &plan {
+        println!("{recommendation}");
+    }
+
+    Ok(())
+}
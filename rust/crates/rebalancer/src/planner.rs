@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Result, anyhow, bail};
+use db::entities::{
+    managed_positions::{self, Entity as ManagedPositions},
+    pool_prices::{self, Entity as PoolPrices},
+    pools::Entity as Pools,
+    sea_orm_active_enums::PositionStatus,
+};
+use liquidity_core::address::AccountAddress;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    sqlx::types::chrono::Utc,
+};
+use tapp::TappChainClient;
+
+use crate::engine::{self, ManagedRange, RebalanceConfig, Recommendation};
+
+/// Only TAPP pools are read and moved, through `TappChainClient`
+const DEX: &str = "tapp";
+
+/// Hourly prices the volatility is measured on
+const VOLATILITY_POINTS: u64 = 168;
+
+pub struct Planner {
+    database: DatabaseConnection,
+    chain_client: TappChainClient,
+    config: RebalanceConfig,
+}
+
+impl Planner {
+    pub fn new(
+        database: DatabaseConnection,
+        chain_client: TappChainClient,
+        config: RebalanceConfig,
+    ) -> Self {
+        Self {
+            database,
+            chain_client,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &RebalanceConfig {
+        &self.config
+    }
+
+    /// Recommendations for every active managed position, or only those of `pool_id` and
+    /// `owner`
+    ///
+    /// A keeper passes its own address as `owner`, it can't move positions held by other
+    /// accounts. Pools that aren't TAPP pools, or whose tick spacing or current tick can't be
+    /// read, are reported and skipped.
+    pub async fn plan(
+        &self,
+        pool_id: Option<&str>,
+        owner: Option<&AccountAddress>,
+    ) -> Result<Vec<Recommendation>> {
+        let mut query = ManagedPositions::find()
+            .filter(managed_positions::Column::Status.eq(PositionStatus::Active));
+        if let Some(pool_id) = pool_id {
+            query = query.filter(managed_positions::Column::PoolId.eq(pool_id));
+        }
+        if let Some(owner) = owner {
+            query = query.filter(managed_positions::Column::Owner.eq(owner.to_string()));
+        }
+
+        let now = Utc::now().naive_utc();
+        let mut by_pool: BTreeMap<String, Vec<ManagedRange>> = BTreeMap::new();
+        for position in query.all(&self.database).await? {
+            by_pool
+                .entry(position.pool_id.clone())
+                .or_default()
+                .push(ManagedRange {
+                    id: position.id,
+                    pool_id: position.pool_id,
+                    tick_lower: i32::try_from(position.tick_lower)?,
+                    tick_upper: i32::try_from(position.tick_upper)?,
+                    age: (now - position.updated_at).to_std().unwrap_or_default(),
+                });
+        }
+
+        let mut recommendations = Vec::new();
+        for (pool_id, positions) in by_pool {
+            let (tick_spacing, current_tick) = match self.pool_state(&pool_id).await {
+                Ok(state) => state,
+                Err(e) => {
+                    eprintln!("Skipping pool {pool_id}: {e}");
+                    continue;
+                }
+            };
+            let volatility = self.volatility(&pool_id).await?;
+
+            recommendations.extend(positions.iter().filter_map(|p| {
+                engine::evaluate(p, current_tick, volatility, tick_spacing, &self.config)
+            }));
+        }
+
+        Ok(recommendations)
+    }
+
+    /// Tick spacing and current tick of a TAPP pool
+    async fn pool_state(&self, pool_id: &str) -> Result<(u32, i32)> {
+        let pool = Pools::find_by_id(pool_id)
+            .one(&self.database)
+            .await?
+            .ok_or_else(|| anyhow!("pool has not been scraped"))?;
+        if pool.dex != DEX {
+            bail!("{} pools are not supported, only {} ones", pool.dex, DEX);
+        }
+        let tick_spacing = self.chain_client.get_tick_spacing(pool_id).await?;
+
+        let tick = self.chain_client.get_current_tick_index(pool_id).await?;
+        Ok((tick_spacing, i32::try_from(tick)?))
+    }
+
+    /// Standard deviation of the stored hourly log returns, if there are enough prices
+    async fn volatility(&self, pool_id: &str) -> Result<Option<f64>> {
+        let mut prices: Vec<f64> = PoolPrices::find()
+            .filter(pool_prices::Column::PoolId.eq(pool_id))
+            .filter(pool_prices::Column::Interval.eq("1h"))
+            .order_by_desc(pool_prices::Column::Timestamp)
+            .limit(VOLATILITY_POINTS)
+            .all(&self.database)
+            .await?
+            .into_iter()
+            .map(|p| p.price)
+            .collect();
+        prices.reverse();
+
+        Ok(engine::hourly_volatility(&prices))
+    }
+}
@@ -0,0 +1,17 @@
+//! Aptos SDK network settings pointed at the configured fullnode.
+
+use anyhow::{Context, Result};
+use aptos_rust_sdk::client::config::AptosNetwork;
+use url::Url;
+
+/// `network` with its REST API served from `fullnode_url`, the `rpc.fullnode_url` setting
+pub fn with_fullnode_url(network: AptosNetwork, fullnode_url: &str) -> Result<AptosNetwork> {
+    let rest_url =
+        Url::parse(fullnode_url).with_context(|| format!("Invalid fullnode URL {fullnode_url}"))?;
+
+    Ok(AptosNetwork::new(
+        network.name(),
+        rest_url,
+        network.indexer_url().cloned(),
+    ))
+}
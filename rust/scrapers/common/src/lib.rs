@@ -1,4 +1,12 @@
+pub mod fullnode;
+pub mod shutdown;
+pub mod watch;
+
 use clap::{Parser, Subcommand};
+use db::ingest::IngestStats;
+
+pub use shutdown::shutdown_signal;
+pub use watch::WatchArgs;
 
 
 #[derive(Parser)]
@@ -16,25 +24,70 @@ pub enum Commands {
     Pool {id: String},
     // Gets positions for a specific ppol
     Positions { pool_id: String},
-    Tokens
+    Tokens,
+    // Stores the price chart of a pool, resuming from the last stored point
+    Prices {
+        pool_id: String,
+        /// Chart resolution, e.g. 1h, 4h or 1d
+        #[arg(long, default_value = "1h")]
+        interval: String,
+        /// Unix timestamp (seconds) to fetch from instead of the last stored point
+        #[arg(long)]
+        since: Option<i64>,
+    },
+    /// Keeps running, scraping each kind of data on its own interval until SIGTERM or Ctrl+C
+    Watch(WatchArgs),
 }
 
 #[allow(async_fn_in_trait)]
 pub trait Scraper {
-    async fn scrape_pools(&self) -> anyhow::Result<()>;
-    async fn scrape_pool(&self, id: &str) -> anyhow::Result<()>;
-    async fn scrape_positions(&self, pool_id: &str) -> anyhow::Result<()>;
-    async fn scrape_tokens(&self) -> anyhow::Result<()>;
+    async fn scrape_pools(&self) -> anyhow::Result<IngestStats>;
+    async fn scrape_pool(&self, id: &str) -> anyhow::Result<IngestStats>;
+    async fn scrape_positions(&self, pool_id: &str) -> anyhow::Result<IngestStats>;
+    async fn scrape_tokens(&self) -> anyhow::Result<IngestStats>;
+
+    /// IDs of the `limit` pools of this DEX with the highest TVL, whose positions and prices
+    /// `watch` refreshes when no `--pool` is given
+    async fn top_pools(&self, _limit: u64) -> anyhow::Result<Vec<String>> {
+        anyhow::bail!("This scraper can't list its pools, pass them with --pool")
+    }
+
+    async fn scrape_prices(
+        &self,
+        _pool_id: &str,
+        _interval: &str,
+        _since: Option<i64>,
+    ) -> anyhow::Result<IngestStats> {
+        anyhow::bail!("This scraper does not provide price charts")
+    }
 }
 
 pub async fn run<S: Scraper>(scraper: S) {
     let cli = Cli::parse();
     
+    // Scrapes return their stats and leave printing to the caller, `watch` reports per cycle
     let result = match cli.command {
-        Commands::Pools => scraper.scrape_pools().await,
-        Commands::Pool { id } => scraper.scrape_pool(&id).await,
-        Commands::Positions { pool_id } => scraper.scrape_positions(&pool_id).await,
-        Commands::Tokens => scraper.scrape_tokens().await,
+        Commands::Pools => scraper
+            .scrape_pools()
+            .await
+            .map(|stats| println!("Pools: {stats}")),
+        Commands::Pool { id } => scraper
+            .scrape_pool(&id)
+            .await
+            .map(|stats| println!("Pool {id}: {stats}")),
+        Commands::Positions { pool_id } => scraper
+            .scrape_positions(&pool_id)
+            .await
+            .map(|stats| println!("Positions for pool {pool_id}: {stats}")),
+        Commands::Tokens => scraper
+            .scrape_tokens()
+            .await
+            .map(|stats| println!("Tokens: {stats}")),
+        Commands::Prices { pool_id, interval, since } => scraper
+            .scrape_prices(&pool_id, &interval, since)
+            .await
+            .map(|stats| println!("Prices for pool {pool_id} ({interval}): {stats}")),
+        Commands::Watch(args) => watch::watch(&scraper, args).await,
     };
 
     if let Err(e) = result {
@@ -0,0 +1,197 @@
+//! `watch` keeps a scraper running, each kind of data refreshed on its own interval.
+//!
+//! A cycle that fails is logged and retried at the next tick, so a flaky RPC or API never stops
+//! the process. Cycles of one kind never overlap, a slow one delays the next. SIGTERM or Ctrl+C
+//! stops the loops once the cycles in flight are done.
+
+use std::{future::Future, time::Duration};
+
+use anyhow::{Result, bail};
+use clap::Args;
+use config::schedule::{format_duration, parse_duration};
+use db::ingest::IngestStats;
+use tokio::{
+    sync::watch,
+    time::{Instant, MissedTickBehavior},
+};
+
+use crate::{Scraper, shutdown_signal};
+
+#[derive(Args, Debug, Clone)]
+pub struct WatchArgs {
+    /// Interval between pool scrapes, e.g. 60s, 5m or 1h
+    #[arg(long, value_parser = parse_interval)]
+    pub pools: Option<Duration>,
+    /// Interval between position scrapes of the watched pools
+    #[arg(long, value_parser = parse_interval)]
+    pub positions: Option<Duration>,
+    /// Interval between token scrapes
+    #[arg(long, value_parser = parse_interval)]
+    pub tokens: Option<Duration>,
+    /// Interval between price chart updates of the watched pools
+    #[arg(long, value_parser = parse_interval)]
+    pub prices: Option<Duration>,
+    /// Chart resolution used by --prices
+    #[arg(long, default_value = "1h")]
+    pub price_interval: String,
+    /// Pool to watch positions and prices of, repeatable. The top pools by TVL by default
+    #[arg(long = "pool")]
+    pub pool_ids: Vec<String>,
+    /// How many of the top pools by TVL to watch when no --pool is given
+    #[arg(long, default_value_t = 10)]
+    pub top: u64,
+}
+
+/// A duration above zero, `tokio::time::interval` panics on zero
+fn parse_interval(s: &str) -> Result<Duration> {
+    let interval = parse_duration(s)?;
+    if interval.is_zero() {
+        bail!("interval {s} must be above zero");
+    }
+    Ok(interval)
+}
+
+pub async fn watch<S: Scraper>(scraper: &S, args: WatchArgs) -> Result<()> {
+    if args.pools.is_none()
+        && args.positions.is_none()
+        && args.tokens.is_none()
+        && args.prices.is_none()
+    {
+        bail!("Nothing to watch, pass at least one of --pools, --positions, --tokens or --prices");
+    }
+
+    let args = &args;
+    let price_interval = args.price_interval.as_str();
+    let (stop, stopped) = watch::channel(false);
+    let pools = async {
+        if let Some(period) = args.pools {
+            every("pools", period, stopped.clone(), || scraper.scrape_pools()).await
+        }
+    };
+    let positions = async {
+        if let Some(period) = args.positions {
+            every("positions", period, stopped.clone(), || async {
+                for_each_pool(scraper, args, |id| async move {
+                    scraper.scrape_positions(&id).await
+                })
+                .await
+            })
+            .await
+        }
+    };
+    let tokens = async {
+        if let Some(period) = args.tokens {
+            every("tokens", period, stopped.clone(), || scraper.scrape_tokens()).await
+        }
+    };
+    let prices = async {
+        if let Some(period) = args.prices {
+            every("prices", period, stopped.clone(), || async {
+                for_each_pool(scraper, args, |id| async move {
+                    scraper.scrape_prices(&id, price_interval, None).await
+                })
+                .await
+            })
+            .await
+        }
+    };
+
+    // The loops only return once `stop` is sent
+    let loops = async {
+        tokio::join!(pools, positions, tokens, prices);
+    };
+    tokio::pin!(loops);
+    tokio::select! {
+        _ = &mut loops => {}
+        _ = shutdown_signal() => {
+            println!("[watch] shutting down once the running cycles are done");
+            stop.send_replace(true);
+            loops.await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `cycle` every `period` until `stopped` turns true, logging every outcome and the rows it
+/// changed
+async fn every<F, Fut>(name: &str, period: Duration, mut stopped: watch::Receiver<bool>, cycle: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<IngestStats>>,
+{
+    println!("[watch] {name} every {}", format_duration(period));
+
+    let mut ticks = tokio::time::interval(period);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let (mut cycles, mut failures) = (0u64, 0u64);
+
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = stopped.wait_for(|stop| *stop) => return,
+        }
+
+        cycles += 1;
+        let started = Instant::now();
+        match cycle().await {
+            Ok(stats) => println!(
+                "[watch] {name} #{cycles} done in {:.1?}, {stats} ({failures} failed so far)",
+                started.elapsed()
+            ),
+            Err(e) => {
+                failures += 1;
+                eprintln!(
+                    "[watch] {name} #{cycles} failed after {:.1?} ({failures} failed so far): {e}",
+                    started.elapsed()
+                );
+            }
+        }
+    }
+}
+
+/// Run `scrape` for every watched pool, failing once all are done if any of them failed
+async fn for_each_pool<S, F, Fut>(scraper: &S, args: &WatchArgs, scrape: F) -> Result<IngestStats>
+where
+    S: Scraper,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<IngestStats>>,
+{
+    let pool_ids = if args.pool_ids.is_empty() {
+        scraper.top_pools(args.top).await?
+    } else {
+        args.pool_ids.clone()
+    };
+
+    let mut stats = IngestStats::default();
+    let mut failed = Vec::new();
+    for pool_id in &pool_ids {
+        match scrape(pool_id.clone()).await {
+            Ok(pool_stats) => stats += pool_stats,
+            Err(e) => failed.push(format!("{pool_id}: {e}")),
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!(
+            "{} of {} pools failed ({} rows changed): {}",
+            failed.len(),
+            pool_ids.len(),
+            stats.changed(),
+            failed.join("; ")
+        );
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_must_be_above_zero() {
+        assert_eq!(parse_interval("5m").unwrap(), Duration::from_secs(300));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("0m").is_err());
+    }
+}
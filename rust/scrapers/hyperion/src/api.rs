@@ -1,4 +1,4 @@
-use crate::types::{Network, PoolResponse, PositionResponse};
+use crate::types::{HyperionToken, Network, PoolResponse, PositionResponse};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -10,9 +10,13 @@ pub struct HyperionGraphQLClient {
 
 impl HyperionGraphQLClient {
     pub fn new(network: Network) -> Self {
+        Self::with_url(network.graphql_url())
+    }
+
+    pub fn with_url(graphql_url: &str) -> Self {
         Self {
             client: reqwest::Client::new(),
-            graphql_url: network.graphql_url().to_string(),
+            graphql_url: graphql_url.to_string(),
         }
     }
 
@@ -87,6 +91,29 @@ impl HyperionGraphQLClient {
         Ok(result.pools)
     }
 
+    /// Fetch all tokens listed on Hyperion
+    pub async fn fetch_all_tokens(&self) -> Result<Vec<HyperionToken>> {
+        let query = r#"
+            query GetAllTokens {
+                tokens {
+                    address
+                    symbol
+                    decimals
+                    name
+                    logo_url
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct TokensData {
+            tokens: Vec<HyperionToken>,
+        }
+
+        let result: TokensData = self.query(query, json!({})).await?;
+        Ok(result.tokens)
+    }
+
     /// Fetch pool by ID
     pub async fn fetch_pool_by_id(&self, pool_id: &str) -> Result<PoolResponse> {
         let query = r#"
@@ -0,0 +1,87 @@
+use anyhow::Result;
+
+use config::Settings;
+use db::{
+    self,
+    entities::{pools, positions, tokens},
+    ingest::{self, IngestStats},
+    query,
+};
+use hyperion::HyperionGraphQLClient;
+use scraper_common::{Scraper, run};
+use sea_orm::DatabaseConnection;
+
+struct HyperionScraper {
+    api_client: HyperionGraphQLClient,
+    database_connection: DatabaseConnection,
+}
+
+impl Scraper for HyperionScraper {
+    async fn scrape_pools(&self) -> anyhow::Result<IngestStats> {
+        let pools = self.api_client.fetch_all_pools().await?;
+
+        let models = pools
+            .into_iter()
+            .map(|p| p.to_active_model())
+            .collect::<Result<Vec<pools::ActiveModel>>>()?;
+
+        let stats = ingest::upsert_pools(&self.database_connection, models).await?;
+
+        Ok(stats)
+    }
+
+    async fn scrape_pool(&self, id: &str) -> anyhow::Result<IngestStats> {
+        let pool = self.api_client.fetch_pool_by_id(id).await?;
+
+        let stats = ingest::upsert_pools(&self.database_connection, vec![pool.to_active_model()?])
+            .await?;
+
+        Ok(stats)
+    }
+
+    async fn scrape_positions(&self, id: &str) -> anyhow::Result<IngestStats> {
+        let positions = self.api_client.fetch_positions_by_pool(id).await?;
+
+        let models = positions
+            .into_iter()
+            .map(|p| p.to_active_model(id))
+            .collect::<Result<Vec<positions::ActiveModel>>>()?;
+
+        let stats = ingest::sync_object_positions(&self.database_connection, id, models).await?;
+
+        Ok(stats)
+    }
+
+    async fn scrape_tokens(&self) -> anyhow::Result<IngestStats> {
+        let tokens = self.api_client.fetch_all_tokens().await?;
+
+        let token_models: Vec<tokens::ActiveModel> =
+            tokens.iter().map(|t| t.to_active_model()).collect();
+
+        let stats = ingest::upsert_tokens(&self.database_connection, token_models).await?;
+
+        Ok(stats)
+    }
+
+    async fn top_pools(&self, limit: u64) -> anyhow::Result<Vec<String>> {
+        Ok(query::top_pools_by_tvl(&self.database_connection, hyperion::types::DEX, limit).await?)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    println!("Starting Hyperion scrapper.");
+    let settings = Settings::load()?;
+    println!("{settings}");
+
+    let connection =
+        db::create_pool(&settings.database.url, settings.database.max_connections).await?;
+
+    let scraper = HyperionScraper {
+        api_client: HyperionGraphQLClient::with_url(&settings.apis.hyperion_url),
+        database_connection: connection,
+    };
+    run(scraper).await;
+
+    Ok(())
+}
@@ -30,11 +30,11 @@ impl HyperionClient {
         }
     }
 
-    pub fn from_network(network: Network) -> Self {
-        let builder = AptosClientBuilder::new(network.to_aptos_network());
+    pub fn from_network(network: Network, fullnode_url: &str) -> Result<Self> {
+        let builder = AptosClientBuilder::new(network.to_aptos_network(fullnode_url)?);
         let aptos_client = builder.build();
 
-        HyperionClient::new(aptos_client, network)
+        Ok(HyperionClient::new(aptos_client, network))
     }
 
     pub fn network(&self) -> Network {
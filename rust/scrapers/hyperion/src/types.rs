@@ -1,6 +1,16 @@
 use aptos_rust_sdk::client::config::AptosNetwork;
+use db::entities::{pools, positions, tokens};
+use rust_decimal::Decimal;
+use scraper_common::fullnode::with_fullnode_url;
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    sqlx::types::chrono::Utc,
+};
 use serde::{Deserialize, Serialize};
 
+/// Value stored in the `dex` column for Hyperion pools
+pub const DEX: &str = "hyperion";
+
 /// Position data from Hyperion CLMM
 /// Maps to pool_v3::Info resource
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +63,28 @@ pub struct PoolResponse {
     pub apr: Option<f64>,
 }
 
+impl PoolResponse {
+    /// Hyperion only reports 24h volume and a single APR, the remaining metrics are zeroed.
+    pub fn to_active_model(self) -> anyhow::Result<pools::ActiveModel> {
+        Ok(pools::ActiveModel {
+            position_index: Set(None),
+            id: Set(self.pool_id),
+            dex: Set(DEX.to_string()),
+            fee: Set(self.fee_tier.parse::<Decimal>()?),
+            trading_apr: Set(self.apr.unwrap_or_default()),
+            bonus_apr: Set(0.0),
+            tvl: Set(self.tvl.unwrap_or_default()),
+            volume_day: Set(self.volume_24h.unwrap_or_default()),
+            volume_week: Set(0.0),
+            volume_month: Set(0.0),
+            volume_prev_day: Set(0.0),
+            token_a: Set(Some(self.token_a)),
+            token_b: Set(Some(self.token_b)),
+            updated_at: Set(Some(Utc::now().naive_utc())),
+        })
+    }
+}
+
 /// GraphQL response for positions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionResponse {
@@ -64,6 +96,38 @@ pub struct PositionResponse {
     pub tick_upper: i32,
 }
 
+impl PositionResponse {
+    /// Model of the position in `pool_id`, the pool it was fetched for. The index is assigned
+    /// from the address by `ingest::sync_object_positions`.
+    pub fn to_active_model(self, pool_id: &str) -> anyhow::Result<positions::ActiveModel> {
+        let address = position_address(&self.position_id)?;
+
+        Ok(positions::ActiveModel {
+            pool: Set(pool_id.to_string()),
+            index: NotSet,
+            address: Set(Some(address)),
+            tick_lower: Set(self.tick_lower.into()),
+            tick_upper: Set(self.tick_upper.into()),
+            liquidity: Set(self.liquidity),
+            updated_at: Set(Some(Utc::now().naive_utc())),
+        })
+    }
+}
+
+/// Address of a Hyperion position, as stored in `positions.address`.
+///
+/// TAPP positions are numbered within their pool, Hyperion ones are objects identified by their
+/// address. It is kept whole in the long form (`0x` and 64 lowercase hex digits) so the same
+/// object always maps to the same row.
+pub fn position_address(position_id: &str) -> anyhow::Result<String> {
+    let hex = position_id.strip_prefix("0x").unwrap_or(position_id);
+    if hex.is_empty() || hex.len() > 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        anyhow::bail!("Position id {} is not an object address", position_id);
+    }
+
+    Ok(format!("0x{:0>64}", hex.to_ascii_lowercase()))
+}
+
 /// Token information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HyperionToken {
@@ -74,6 +138,20 @@ pub struct HyperionToken {
     pub logo_url: Option<String>,
 }
 
+impl HyperionToken {
+    pub fn to_active_model(&self) -> tokens::ActiveModel {
+        tokens::ActiveModel {
+            id: Set(self.address.clone()),
+            symbol: Set(self.symbol.clone()),
+            name: Set(self.name.clone()),
+            about: Set(None),
+            logo: Set(self.logo_url.clone()),
+            decimals: Set(self.decimals.into()),
+            updated_at: Set(Some(Utc::now().naive_utc())),
+        }
+    }
+}
+
 // Network configuration
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Network {
@@ -82,11 +160,13 @@ pub enum Network {
 }
 
 impl Network {
-    pub fn to_aptos_network(&self) -> AptosNetwork {
-        match self {
+    /// SDK settings of the network, reading the chain from `fullnode_url`
+    pub fn to_aptos_network(&self, fullnode_url: &str) -> anyhow::Result<AptosNetwork> {
+        let network = match self {
             Network::Mainnet => AptosNetwork::mainnet(),
             Network::Testnet => AptosNetwork::testnet(),
-        }
+        };
+        with_fullnode_url(network, fullnode_url)
     }
 
     pub fn graphql_url(&self) -> &'static str {
@@ -96,3 +176,63 @@ impl Network {
         }
     }
 }
+
+impl From<config::Network> for Network {
+    fn from(network: config::Network) -> Self {
+        match network {
+            config::Network::Mainnet => Network::Mainnet,
+            config::Network::Testnet => Network::Testnet,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_are_indexed_by_object_address() {
+        let response = r#"{
+            "positions": [{
+                "position_id": "0x6f1b1a2b9c0e4e8ad2d4f0b5c3a7e9d1f2c4b6a8e0d2f4a6c8e0b2d4f6a8c0e2",
+                "pool_id": "0x925660b8618394809f89f8002e2926600c775221f43bf1919782b297a79400d8",
+                "owner": "0x1f0e2d3c4b5a69788796a5b4c3d2e1f00f1e2d3c4b5a69788796a5b4c3d2e1f0",
+                "liquidity": "123456789",
+                "tick_lower": -443580,
+                "tick_upper": 443580
+            }]
+        }"#;
+
+        #[derive(Deserialize)]
+        struct PositionsData {
+            positions: Vec<PositionResponse>,
+        }
+        let data: PositionsData = serde_json::from_str(response).unwrap();
+        let pool = "0x925660b8618394809f89f8002e2926600c775221f43bf1919782b297a79400d8";
+        let model = data.positions[0].clone().to_active_model(pool).unwrap();
+
+        assert_eq!(
+            model.address.unwrap().unwrap(),
+            "0x6f1b1a2b9c0e4e8ad2d4f0b5c3a7e9d1f2c4b6a8e0d2f4a6c8e0b2d4f6a8c0e2"
+        );
+        assert_eq!(model.pool.unwrap(), pool);
+        assert!(model.index.is_not_set());
+        assert_eq!(model.tick_lower.unwrap(), -443580);
+        assert_eq!(model.liquidity.unwrap(), "123456789");
+    }
+
+    #[test]
+    fn position_addresses_are_kept_whole_in_long_form() {
+        let long = format!("0x{}", "f".repeat(64));
+        assert_eq!(position_address(&long).unwrap(), long);
+        assert_eq!(position_address("0xA").unwrap(), format!("0x{:0>64}", "a"));
+        assert_eq!(position_address("12").unwrap(), format!("0x{:0>64}", "12"));
+        assert_ne!(
+            position_address("0x100000000").unwrap(),
+            position_address("0x1").unwrap()
+        );
+        assert!(position_address("0x").is_err());
+        assert!(position_address("0xnot-hex").is_err());
+        assert!(position_address(&format!("0x{}", "1".repeat(65))).is_err());
+    }
+}
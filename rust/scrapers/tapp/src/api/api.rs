@@ -1,9 +1,17 @@
-use anyhow::{Result, anyhow};
+use std::{collections::HashSet, future::Future, marker::PhantomData};
+
+use anyhow::{Result, anyhow, bail};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 
 const TAPP_API_BASE_URL: &str = "https://api.tapp.exchange/v1";
 
+/// Largest `pageSize` requested, the size TAPP's own web app pages by. Larger sizes are
+/// undocumented: if the server capped them, a full page would look like the last one.
+pub const MAX_PAGE_SIZE: usize = 30;
+
+/// Safety net against an endpoint that ignores `page` and keeps returning full pages.
+const MAX_PAGES: usize = 1000;
+
 use super::models::*;
 
 #[derive(Debug, Deserialize)]
@@ -42,9 +50,13 @@ struct Params<T> {
 
 impl TappHttpClient {
     pub fn new() -> Self {
+        Self::with_base_url(TAPP_API_BASE_URL)
+    }
+
+    pub fn with_base_url(base_url: &str) -> Self {
         Self {
             client: reqwest::Client::new(),
-            base_url: TAPP_API_BASE_URL.to_string(),
+            base_url: base_url.to_string(),
             request_id: std::sync::atomic::AtomicU64::new(0),
         }
     }
@@ -76,16 +88,21 @@ impl TappHttpClient {
         Ok(json_response.result.data)
     }
     pub async fn get_all_tokens(&self) -> Result<Vec<TappApiToken>> {
-        // TODO: there currently are only 92 tokens defined on tapp. We'll need to iterate in the very near future
-        self.get_token_list(TokenListQuery {
+        self.token_pages(TokenListQuery {
             start_time: Some(0),
             end_time: Some(0),
             keyword: None,
             page: Some(1),
-            page_size: Some(100),
+            page_size: Some(MAX_PAGE_SIZE as u32),
         })
+        .collect_all()
         .await
     }
+
+    /// Iterate over the token list page by page, starting at the query's page.
+    pub fn token_pages(&self, query: TokenListQuery) -> Pages<'_, TokenListQuery, TappApiToken> {
+        Pages::new(self, "public/token", query)
+    }
     /**  Get token list from TAPP API
      *  It seems tapp's using Fungible Assets with no regards for the older coin type. Careful
      */
@@ -99,24 +116,194 @@ impl TappHttpClient {
             .await
     }
 
+    /// Fetch a single page of pools
     pub async fn get_pools(&self, query: PoolsQuery) -> Result<Vec<Pool>> {
         self.query("public/pool", Params { query }).await
     }
 
+    /// Fetch every pool matching the query, following pages until the last one
+    pub async fn get_all_pools(&self, query: PoolsQuery) -> Result<Vec<Pool>> {
+        self.pool_pages(query).collect_all().await
+    }
+
+    /// Iterate over the pool list page by page, starting at the query's page.
+    pub fn pool_pages(&self, query: PoolsQuery) -> Pages<'_, PoolsQuery, Pool> {
+        Pages::new(self, "public/pool", query)
+    }
+
     pub async fn get_pool(&self, id: &str) -> Result<Pool> {
-        // We're using get_pools since it returns more info than just querying public/pool_stats
-        let pools = self
-            .get_pools(PoolsQuery {
-                pool_type: PoolType::Clmm,
-                page: 1,
-                page_size: 100,
-            })
-            .await?;
+        // We're using the pool list since it returns more info than just querying public/pool_stats
+        let mut pages = self.pool_pages(PoolsQuery::all_clmm_pools());
 
-        pools
-            .into_iter()
-            .find(|pool| pool.pool_id == id)
-            .ok_or_else(|| anyhow!("Pool not found: {}", id))
+        while let Some(pools) = pages.next_page().await? {
+            if let Some(pool) = pools.into_iter().find(|pool| pool.pool_id == id) {
+                return Ok(pool);
+            }
+        }
+
+        Err(anyhow!("Pool not found: {}", id))
+    }
+}
+
+/// Transport a [`Pages`] cursor fetches its pages through
+pub trait PageSource {
+    fn fetch_page<Q, T>(
+        &self,
+        method: &str,
+        query: &Q,
+    ) -> impl Future<Output = Result<Vec<T>>> + Send
+    where
+        Q: Serialize + Sync,
+        T: for<'de> Deserialize<'de> + Send;
+}
+
+impl PageSource for TappHttpClient {
+    fn fetch_page<Q, T>(
+        &self,
+        method: &str,
+        query: &Q,
+    ) -> impl Future<Output = Result<Vec<T>>> + Send
+    where
+        Q: Serialize + Sync,
+        T: for<'de> Deserialize<'de> + Send,
+    {
+        self.query(method, Params { query })
+    }
+}
+
+/// Items listed by a paginated endpoint, identified across pages
+pub trait PageItem {
+    fn key(&self) -> &str;
+}
+
+impl PageItem for TappApiToken {
+    fn key(&self) -> &str {
+        &self.addr
+    }
+}
+
+impl PageItem for Pool {
+    fn key(&self) -> &str {
+        &self.pool_id
+    }
+}
+
+/// Queries that can be walked page by page
+pub trait Paginated: Serialize {
+    /// Page size requested by the caller, if any
+    fn page_size(&self) -> Option<usize>;
+    /// Page the caller wants to start from, if any
+    fn page(&self) -> Option<usize>;
+    fn set_page(&mut self, page: usize, page_size: usize);
+}
+
+impl Paginated for TokenListQuery {
+    fn page_size(&self) -> Option<usize> {
+        self.page_size.map(|size| size as usize)
+    }
+
+    fn page(&self) -> Option<usize> {
+        self.page.map(|page| page as usize)
+    }
+
+    fn set_page(&mut self, page: usize, page_size: usize) {
+        self.page = Some(page as u32);
+        self.page_size = Some(page_size as u32);
+    }
+}
+
+impl Paginated for PoolsQuery {
+    fn page_size(&self) -> Option<usize> {
+        Some(self.page_size)
+    }
+
+    fn page(&self) -> Option<usize> {
+        Some(self.page)
+    }
+
+    fn set_page(&mut self, page: usize, page_size: usize) {
+        self.page = page;
+        self.page_size = page_size;
+    }
+}
+
+/// Cursor over a paginated TAPP endpoint.
+///
+/// Pages are fetched lazily and iteration stops after the first page shorter than the page
+/// size, which is clamped to [`MAX_PAGE_SIZE`]. Lists are ordered by live figures such as TVL,
+/// so an item can move across a page boundary between two requests: items already yielded
+/// are skipped when they show up again.
+pub struct Pages<'a, Q, T, S = TappHttpClient> {
+    client: &'a S,
+    method: &'static str,
+    query: Q,
+    page: usize,
+    page_size: usize,
+    exhausted: bool,
+    seen: HashSet<String>,
+    _item: PhantomData<T>,
+}
+
+impl<'a, Q, T, S> Pages<'a, Q, T, S>
+where
+    Q: Paginated + Sync,
+    T: PageItem + for<'de> Deserialize<'de> + Send,
+    S: PageSource,
+{
+    fn new(client: &'a S, method: &'static str, query: Q) -> Self {
+        let page_size = query
+            .page_size()
+            .unwrap_or(MAX_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let page = query.page().unwrap_or(1).max(1);
+
+        Self {
+            client,
+            method,
+            query,
+            page,
+            page_size,
+            exhausted: false,
+            seen: HashSet::new(),
+            _item: PhantomData,
+        }
+    }
+
+    /// Fetch the next page with unseen items, or `None` once the list is exhausted
+    pub async fn next_page(&mut self) -> Result<Option<Vec<T>>> {
+        while !self.exhausted {
+            if self.page > MAX_PAGES {
+                bail!(
+                    "{} returned more than {} full pages",
+                    self.method,
+                    MAX_PAGES
+                );
+            }
+
+            self.query.set_page(self.page, self.page_size);
+            let items: Vec<T> = self.client.fetch_page(self.method, &self.query).await?;
+
+            self.page += 1;
+            self.exhausted = items.len() < self.page_size;
+
+            let fresh: Vec<T> = items
+                .into_iter()
+                .filter(|item| self.seen.insert(item.key().to_string()))
+                .collect();
+            if !fresh.is_empty() {
+                return Ok(Some(fresh));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Drain every remaining page into a single list
+    pub async fn collect_all(mut self) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            items.extend(page);
+        }
+        Ok(items)
     }
 }
 
@@ -128,8 +315,141 @@ impl Default for TappHttpClient {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Mutex;
+
     use super::*;
 
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item(String);
+
+    impl PageItem for Item {
+        fn key(&self) -> &str {
+            &self.0
+        }
+    }
+
+    /// Serves canned pages by number and records the page sizes it was asked for
+    struct FakeSource {
+        pages: Vec<Vec<String>>,
+        requested: Mutex<Vec<(usize, usize)>>,
+    }
+
+    impl FakeSource {
+        fn new<S: ToString>(pages: Vec<Vec<S>>) -> Self {
+            Self {
+                pages: pages
+                    .into_iter()
+                    .map(|page| page.iter().map(ToString::to_string).collect())
+                    .collect(),
+                requested: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn requested(&self) -> Vec<(usize, usize)> {
+            self.requested.lock().unwrap().clone()
+        }
+    }
+
+    impl PageSource for FakeSource {
+        fn fetch_page<Q, T>(
+            &self,
+            _method: &str,
+            query: &Q,
+        ) -> impl Future<Output = Result<Vec<T>>> + Send
+        where
+            Q: Serialize + Sync,
+            T: for<'de> Deserialize<'de> + Send,
+        {
+            let query = serde_json::to_value(query).unwrap();
+            let page = query["page"].as_u64().unwrap() as usize;
+            let page_size = query["pageSize"].as_u64().unwrap() as usize;
+            self.requested.lock().unwrap().push((page, page_size));
+
+            let items = self.pages.get(page - 1).cloned().unwrap_or_default();
+            let items: Result<Vec<T>> =
+                serde_json::from_value(serde_json::json!(items)).map_err(Into::into);
+            async move { items }
+        }
+    }
+
+    fn pools_query(page_size: usize) -> PoolsQuery {
+        PoolsQuery {
+            pool_type: PoolType::Clmm,
+            page: 1,
+            page_size,
+        }
+    }
+
+    async fn collect(source: &FakeSource, page_size: usize) -> Result<Vec<String>> {
+        let pages: Pages<'_, PoolsQuery, Item, FakeSource> =
+            Pages::new(source, "public/pool", pools_query(page_size));
+        Ok(pages
+            .collect_all()
+            .await?
+            .into_iter()
+            .map(|i| i.0)
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn a_short_page_ends_the_walk() {
+        let source = FakeSource::new(vec![vec!["a", "b"], vec!["c"], vec!["d"]]);
+
+        let items = collect(&source, 2).await.unwrap();
+
+        assert_eq!(items, ["a", "b", "c"]);
+        assert_eq!(source.requested(), [(1, 2), (2, 2)]);
+    }
+
+    #[tokio::test]
+    async fn an_empty_last_page_ends_the_walk() {
+        let source = FakeSource::new(vec![vec!["a", "b"], vec!["c", "d"]]);
+
+        let items = collect(&source, 2).await.unwrap();
+
+        assert_eq!(items, ["a", "b", "c", "d"]);
+        assert_eq!(source.requested(), [(1, 2), (2, 2), (3, 2)]);
+    }
+
+    #[tokio::test]
+    async fn page_sizes_are_clamped() {
+        let source = FakeSource::new(Vec::<Vec<&str>>::new());
+
+        collect(&source, 500).await.unwrap();
+        collect(&source, 0).await.unwrap();
+
+        assert_eq!(source.requested(), [(1, MAX_PAGE_SIZE), (1, 1)]);
+    }
+
+    #[tokio::test]
+    async fn items_shifted_across_pages_are_yielded_once() {
+        // "b" slid from page 1 to page 2 between the two requests
+        let source = FakeSource::new(vec![vec!["a", "b"], vec!["b", "c"], vec!["d"]]);
+
+        let items = collect(&source, 2).await.unwrap();
+
+        assert_eq!(items, ["a", "b", "c", "d"]);
+    }
+
+    #[tokio::test]
+    async fn a_page_of_repeats_does_not_end_the_walk() {
+        let source = FakeSource::new(vec![vec!["a", "b"], vec!["b", "a"], vec!["c"]]);
+
+        let items = collect(&source, 2).await.unwrap();
+
+        assert_eq!(items, ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn endless_full_pages_are_cut_off() {
+        let source = FakeSource::new((0..=MAX_PAGES).map(|page| vec![page]).collect());
+
+        let err = collect(&source, 1).await.unwrap_err();
+
+        assert!(err.to_string().contains("more than 1000 full pages"));
+        assert_eq!(source.requested().len(), MAX_PAGES);
+    }
+
     #[tokio::test]
     async fn test_get_token_list() {
         let client = TappHttpClient::new();
@@ -146,4 +466,16 @@ mod tests {
         let tokens = result.unwrap();
         assert!(!tokens.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_get_all_tokens_has_no_duplicates() {
+        let client = TappHttpClient::new();
+
+        let tokens = client.get_all_tokens().await.unwrap();
+        let mut addresses: Vec<&str> = tokens.iter().map(|t| t.addr.as_str()).collect();
+        addresses.sort_unstable();
+        addresses.dedup();
+
+        assert_eq!(addresses.len(), tokens.len());
+    }
 }
@@ -1,6 +1,11 @@
-use db::entities::pools::{self, Entity};
+use std::str::FromStr;
+
+use db::entities::{pool_prices, pools, tokens};
 use rust_decimal::Decimal;
-use sea_orm::{ActiveValue::Set, sqlx::types::chrono::Utc};
+use sea_orm::{
+    ActiveValue::Set,
+    sqlx::types::chrono::{DateTime, Utc},
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -49,18 +54,20 @@ pub struct PoolsQuery {
 }
 
 impl PoolsQuery {
+    /// First page of CLMM pools, meant to be walked with `TappHttpClient::get_all_pools`
     pub fn all_clmm_pools() -> Self {
         PoolsQuery {
             pool_type: PoolType::Clmm,
             page: 1,
-            page_size: 100,
+            page_size: super::api::MAX_PAGE_SIZE,
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum PriceInterval {
+    #[default]
     #[serde(rename = "1h")]
     OneHour,
     #[serde(rename = "4h")]
@@ -69,6 +76,42 @@ pub enum PriceInterval {
     OneDay,
 }
 
+impl PriceInterval {
+    /// Name used by the TAPP API, also stored in `pool_prices.interval`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriceInterval::OneHour => "1h",
+            PriceInterval::FourHours => "4h",
+            PriceInterval::OneDay => "1d",
+        }
+    }
+
+    pub fn millis(&self) -> u64 {
+        match self {
+            PriceInterval::OneHour => 3_600_000,
+            PriceInterval::FourHours => 4 * 3_600_000,
+            PriceInterval::OneDay => 24 * 3_600_000,
+        }
+    }
+
+    pub fn hours(&self) -> f64 {
+        self.millis() as f64 / 3_600_000.0
+    }
+}
+
+impl FromStr for PriceInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1h" => Ok(PriceInterval::OneHour),
+            "4h" => Ok(PriceInterval::FourHours),
+            "1d" => Ok(PriceInterval::OneDay),
+            other => anyhow::bail!("Unknown price interval {other}, expected 1h, 4h or 1d"),
+        }
+    }
+}
+
 /// Token information from TAPP API
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TappApiToken {
@@ -99,6 +142,20 @@ pub struct TappApiToken {
     pub volume: String,
 }
 
+impl TappApiToken {
+    pub fn to_active_model(&self) -> tokens::ActiveModel {
+        tokens::ActiveModel {
+            id: Set(self.addr.clone()),
+            symbol: Set(self.ticker.clone()),
+            name: Set(Some(self.name.clone())),
+            about: Set(None),
+            logo: Set(Some(self.img.clone())),
+            decimals: Set(self.decimals.into()),
+            updated_at: Set(Some(Utc::now().naive_utc())),
+        }
+    }
+}
+
 pub struct TappApiPool {}
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -120,6 +177,21 @@ pub struct PricePoint {
     pub y: String, // Price as string
 }
 
+impl PricePoint {
+    pub fn to_active_model(
+        &self,
+        pool_id: &str,
+        interval: PriceInterval,
+    ) -> anyhow::Result<pool_prices::ActiveModel> {
+        Ok(pool_prices::ActiveModel {
+            pool_id: Set(pool_id.to_string()),
+            interval: Set(interval.as_str().to_string()),
+            timestamp: Set(DateTime::parse_from_rfc3339(&self.x)?.naive_utc()),
+            price: Set(self.y.parse()?),
+        })
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Pool {
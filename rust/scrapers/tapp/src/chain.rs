@@ -1,4 +1,4 @@
-use crate::types::{Network, Position};
+use crate::types::{Network, Position, tick_spacing};
 use anyhow::Result;
 use aptos_rust_sdk::client::{
     builder::AptosClientBuilder, rest_api::AptosFullnodeClient,
@@ -6,11 +6,15 @@ use aptos_rust_sdk::client::{
 use aptos_rust_sdk_types::api_types::view::ViewRequest;
 use serde_json::Value;
 
+/// Resource stored at every TAPP pool address
+const MAINNET_POOL_RESOURCE: &str =
+    "0x5c2e5a4d1b355b939ab160c618ed5504a6e1addf109388aa3b83b73b207ab6c7::clmm::Pool";
+
 pub struct TappChainClient {
     aptos_client: AptosFullnodeClient,
     view_address: String,
     network: Network,
-    // NOTE: We're not dealing with router for now
+    // NOTE: read-only, router calls are built by `transactions::TappTransactionBuilder`
 }
 
 impl TappChainClient {
@@ -40,11 +44,11 @@ impl TappChainClient {
         }
     }
 
-    pub fn from_network(network: Network) -> Self {
-        let builder = AptosClientBuilder::new(network.to_aptos_network());
+    pub fn from_network(network: Network, fullnode_url: &str) -> Result<Self> {
+        let builder = AptosClientBuilder::new(network.to_aptos_network(fullnode_url)?);
         let aptos_client = builder.build();
 
-        TappChainClient::new(aptos_client, network)
+        Ok(TappChainClient::new(aptos_client, network))
     }
 
     pub fn network(&self) -> Network {
@@ -67,6 +71,42 @@ impl TappChainClient {
         Ok(serde_json::from_value(response[0].take())?)
     }
 
+    /// One position of a pool, by index
+    pub async fn get_position(&self, pool_id: &str, index: u64) -> Result<Position> {
+        let query = self
+            .aptos_client
+            .view_function(ViewRequest {
+                arguments: vec![
+                    Value::String(pool_id.to_string()),
+                    Value::String(index.to_string()),
+                ],
+                function: format!("{}::clmm_views::get_position", self.view_address),
+                type_arguments: vec![],
+            })
+            .await?;
+
+        let mut response = query.into_inner();
+
+        Ok(serde_json::from_value(response[0].take())?)
+    }
+
+    /// Tick spacing of a pool, read from its `clmm::Pool` resource
+    pub async fn get_tick_spacing(&self, pool_id: &str) -> Result<u32> {
+        let resource_type = match self.network {
+            Network::Mainnet => MAINNET_POOL_RESOURCE,
+        };
+        let resource = self
+            .aptos_client
+            .get_account_resources(pool_id.to_string())
+            .await?
+            .into_inner()
+            .into_iter()
+            .find(|r| r.type_ == resource_type)
+            .ok_or_else(|| anyhow::anyhow!("Resource {resource_type} not found at {pool_id}"))?;
+
+        tick_spacing(&resource.data)
+    }
+
     /// Get the current tick index for a pool
     pub async fn get_current_tick_index(&self, pool_id: &str) -> Result<i64> {
         let query = self
@@ -2,4 +2,5 @@ pub use chain::{convert_tick_bits_to_signed, TappChainClient};
 
 pub mod api;
 mod chain;
+pub mod transactions;
 pub mod types;
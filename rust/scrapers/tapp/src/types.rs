@@ -1,6 +1,11 @@
 use aptos_rust_sdk::client::config::AptosNetwork;
+use db::entities::positions;
+use scraper_common::fullnode::with_fullnode_url;
+use sea_orm::{ActiveValue::Set, sqlx::types::chrono::Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::chain::convert_tick_bits_to_signed;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TickIndex {
     pub bits: String,
@@ -18,6 +23,23 @@ pub struct Position {
     pub tick_upper_index: TickIndex,
 }
 
+impl Position {
+    pub fn to_active_model(self, pool_id: &str) -> anyhow::Result<positions::ActiveModel> {
+        let tick_lower_bits = self.tick_lower_index.bits.parse::<u64>()?;
+        let tick_upper_bits = self.tick_upper_index.bits.parse::<u64>()?;
+
+        Ok(positions::ActiveModel {
+            pool: Set(pool_id.to_string()),
+            index: Set(self.index.parse::<i32>()?),
+            tick_lower: Set(convert_tick_bits_to_signed(tick_lower_bits)),
+            tick_upper: Set(convert_tick_bits_to_signed(tick_upper_bits)),
+            liquidity: Set(self.liquidity),
+            updated_at: Set(Some(Utc::now().naive_utc())),
+            address: Set(None),
+        })
+    }
+}
+
 /// APR data from TAPP API/SDK
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TappApiApr {
@@ -86,6 +108,24 @@ pub struct TappSdkPool {
     pub tokens: Vec<PoolToken>,
 }
 
+/// Tick spacing of a TAPP CLMM pool, from the data of its `clmm::Pool` resource
+///
+/// Move serializes integers above `u32` as strings, smaller ones as numbers, both are read.
+pub fn tick_spacing(pool: &serde_json::Value) -> anyhow::Result<u32> {
+    let field = pool
+        .get("tick_spacing")
+        .ok_or_else(|| anyhow::anyhow!("Pool resource has no tick_spacing field"))?;
+    let spacing = match field {
+        serde_json::Value::String(spacing) => spacing.parse()?,
+        field => serde_json::from_value(field.clone())?,
+    };
+    if spacing == 0 {
+        anyhow::bail!("Pool resource has a zero tick_spacing");
+    }
+
+    Ok(spacing)
+}
+
 // Network related
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Network {
@@ -95,10 +135,39 @@ pub enum Network {
 }
 
 impl Network {
-    pub fn to_aptos_network(&self) -> AptosNetwork {
-        match self {
+    /// SDK settings of the network, reading the chain from `fullnode_url`
+    pub fn to_aptos_network(&self, fullnode_url: &str) -> anyhow::Result<AptosNetwork> {
+        let network = match self {
             Network::Mainnet => AptosNetwork::mainnet(),
             //Network::Testnet => AptosNetwork::testnet(),
+        };
+        with_fullnode_url(network, fullnode_url)
+    }
+}
+
+impl TryFrom<config::Network> for Network {
+    type Error = anyhow::Error;
+
+    fn try_from(network: config::Network) -> anyhow::Result<Self> {
+        match network {
+            config::Network::Mainnet => Ok(Network::Mainnet),
+            config::Network::Testnet => anyhow::bail!("TAPP has no {network} deployment"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick_spacing_is_read_from_the_pool_resource() {
+        let pool = |spacing| serde_json::json!({ "position_index": "3", "tick_spacing": spacing });
+
+        assert_eq!(tick_spacing(&pool(serde_json::json!(60))).unwrap(), 60);
+        assert_eq!(tick_spacing(&pool(serde_json::json!("10"))).unwrap(), 10);
+        assert!(tick_spacing(&pool(serde_json::json!(0))).is_err());
+        assert!(tick_spacing(&pool(serde_json::json!(-1))).is_err());
+        assert!(tick_spacing(&serde_json::json!({ "position_index": "3" })).is_err());
+    }
+}
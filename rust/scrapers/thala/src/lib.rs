@@ -1,5 +1,4 @@
 pub use scraper::ThalaClient;
 
-pub mod api;
 mod scraper;
 pub mod types;
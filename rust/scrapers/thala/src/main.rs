@@ -0,0 +1,109 @@
+use anyhow::Result;
+
+use config::Settings;
+use db::{
+    self,
+    entities::{pools, positions, tokens},
+    ingest::{self, IngestStats},
+    query,
+};
+use scraper_common::{Scraper, run};
+use sea_orm::DatabaseConnection;
+use thala::{
+    ThalaClient,
+    types::{DEX, Network},
+};
+
+struct ThalaScraper {
+    chain_client: ThalaClient,
+    database_connection: DatabaseConnection,
+}
+
+impl Scraper for ThalaScraper {
+    async fn scrape_pools(&self) -> anyhow::Result<IngestStats> {
+        let pools = self.chain_client.fetch_pools().await?;
+
+        let models: Vec<pools::ActiveModel> =
+            pools.into_iter().map(|p| p.to_active_model()).collect();
+
+        let stats = ingest::upsert_pools(&self.database_connection, models).await?;
+
+        Ok(stats)
+    }
+
+    async fn scrape_pool(&self, id: &str) -> anyhow::Result<IngestStats> {
+        let pool = self.chain_client.fetch_pool(id).await?;
+
+        let stats =
+            ingest::upsert_pools(&self.database_connection, vec![pool.to_active_model()]).await?;
+
+        Ok(stats)
+    }
+
+    async fn scrape_positions(&self, id: &str) -> anyhow::Result<IngestStats> {
+        let positions = self.chain_client.fetch_positions(id).await?;
+
+        let models = positions
+            .into_iter()
+            .map(|p| p.to_active_model(id))
+            .collect::<Result<Vec<positions::ActiveModel>>>()?;
+
+        let stats = ingest::sync_positions(&self.database_connection, id, models).await?;
+
+        Ok(stats)
+    }
+
+    /// Tokens of the stored pools, or of every pool on chain before the first `pools` scrape
+    async fn scrape_tokens(&self) -> anyhow::Result<IngestStats> {
+        let mut addresses = query::pool_token_ids(&self.database_connection, DEX).await?;
+        if addresses.is_empty() {
+            addresses = self
+                .chain_client
+                .fetch_pools()
+                .await?
+                .into_iter()
+                .flat_map(|p| [p.token_a, p.token_b])
+                .collect();
+        }
+        let tokens = self.chain_client.fetch_tokens(addresses).await?;
+
+        let token_models: Vec<tokens::ActiveModel> =
+            tokens.iter().map(|t| t.to_active_model()).collect();
+
+        let stats = ingest::upsert_tokens(&self.database_connection, token_models).await?;
+
+        Ok(stats)
+    }
+
+    /// Thala pools are stored without market data, so they all rank at the default TVL of
+    /// zero and the first `limit` by id are returned. A limit above the pool count covers them
+    /// all.
+    async fn top_pools(&self, limit: u64) -> anyhow::Result<Vec<String>> {
+        Ok(query::top_pools_by_tvl(&self.database_connection, DEX, limit).await?)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    println!("Starting Thala scrapper.");
+    let settings = Settings::load()?;
+    println!("{settings}");
+
+    let protocol_address = settings.thala_protocol_address()?.to_string();
+
+    let connection =
+        db::create_pool(&settings.database.url, settings.database.max_connections).await?;
+
+    let scraper = ThalaScraper {
+        chain_client: ThalaClient::from_network(
+            Network::try_from(settings.network)?,
+            &settings.rpc.fullnode_url,
+            protocol_address,
+        )?,
+        database_connection: connection,
+    };
+    scraper.chain_client.verify_deployment().await?;
+    run(scraper).await;
+
+    Ok(())
+}
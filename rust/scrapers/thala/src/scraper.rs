@@ -1,57 +1,190 @@
-use crate::types::{Network, Position, ThalaPool};
-use anyhow::Result;
+use crate::types::{
+    FungibleAssetMetadata, MoveModule, Network, PoolResource, Position, ThalaPool, ThalaToken,
+};
+use anyhow::{Context, Result};
 use aptos_rust_sdk::client::{
     builder::AptosClientBuilder, rest_api::AptosFullnodeClient,
 };
 use aptos_rust_sdk_types::api_types::view::ViewRequest;
+use futures::{StreamExt, TryStreamExt, stream};
 use serde_json::Value;
 
+const FUNGIBLE_ASSET_METADATA: &str = "0x1::fungible_asset::Metadata";
+
+/// Resources fetched at once when reading every pool or token
+const CONCURRENT_REQUESTS: usize = 8;
+
 pub struct ThalaClient {
     aptos_client: AptosFullnodeClient,
+    /// Fullnode REST API, for the module ABI the SDK client doesn't expose
+    fullnode_url: String,
+    http_client: reqwest::Client,
+    /// Address of Thala's CLMM package, exposing the `pool` module
+    protocol_address: String,
     network: Network,
-    // TODO: Research and add Thala's smart contract addresses
-    // protocol_address: String,
 }
 
 impl ThalaClient {
-    pub fn new(aptos_client: AptosFullnodeClient, network: Network) -> Self {
+    pub fn new(
+        aptos_client: AptosFullnodeClient,
+        network: Network,
+        fullnode_url: &str,
+        protocol_address: String,
+    ) -> Self {
         Self {
             aptos_client,
+            fullnode_url: fullnode_url.trim_end_matches('/').to_string(),
+            http_client: reqwest::Client::new(),
+            protocol_address,
             network,
         }
     }
 
-    pub fn from_network(network: Network) -> Self {
-        let builder = AptosClientBuilder::new(network.to_aptos_network());
+    pub fn from_network(
+        network: Network,
+        fullnode_url: &str,
+        protocol_address: String,
+    ) -> Result<Self> {
+        let builder = AptosClientBuilder::new(network.to_aptos_network(fullnode_url)?);
         let aptos_client = builder.build();
 
-        ThalaClient::new(aptos_client, network)
+        Ok(ThalaClient::new(
+            aptos_client,
+            network,
+            fullnode_url,
+            protocol_address,
+        ))
+    }
+
+    /// Check the protocol address publishes the `pool` module the scraper reads, with the
+    /// views and struct fields it expects
+    pub async fn verify_deployment(&self) -> Result<()> {
+        let url = format!(
+            "{}/accounts/{}/module/pool",
+            self.fullnode_url, self.protocol_address
+        );
+        let module: MoveModule = self
+            .http_client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("No pool module published at {}", self.protocol_address))?
+            .json()
+            .await?;
+
+        module
+            .abi
+            .check()
+            .with_context(|| format!("Unexpected pool module at {}", self.protocol_address))
     }
 
     pub fn network(&self) -> Network {
         self.network
     }
 
-    // TODO: Implement position fetching for Thala
-    // Research needed:
-    // 1. Does Thala have a view function to fetch all positions?
-    // 2. What's the smart contract module and function name?
-    // 3. What arguments does it require?
-    pub async fn fetch_positions(&self, _pool_id: &str) -> Result<Vec<Position>> {
-        anyhow::bail!("Thala position fetching not yet implemented - research needed")
+    async fn view(&self, function: &str, arguments: Vec<Value>) -> Result<Vec<Value>> {
+        let query = self
+            .aptos_client
+            .view_function(ViewRequest {
+                arguments,
+                function: format!("{}::{}", self.protocol_address, function),
+                type_arguments: vec![],
+            })
+            .await?;
+
+        Ok(query.into_inner())
+    }
+
+    /// Fetch a resource stored at `address` and deserialize its data
+    async fn fetch_resource<T>(&self, address: &str, resource_type: &str) -> Result<T>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let resources = self
+            .aptos_client
+            .get_account_resources(address.to_string())
+            .await?;
+
+        let resource = resources
+            .into_inner()
+            .into_iter()
+            .find(|r| r.type_ == resource_type)
+            .ok_or_else(|| anyhow::anyhow!("Resource {} not found at {}", resource_type, address))?;
+
+        Ok(serde_json::from_value(resource.data)?)
+    }
+
+    /// Fetch every open position of a pool
+    /// Uses pool::positions view function
+    pub async fn fetch_positions(&self, pool_id: &str) -> Result<Vec<Position>> {
+        let mut response = self
+            .view("pool::positions", vec![Value::String(pool_id.to_string())])
+            .await?;
+
+        let positions = response
+            .get_mut(0)
+            .map(Value::take)
+            .ok_or_else(|| anyhow::anyhow!("Empty response from pool::positions"))?;
+
+        Ok(serde_json::from_value(positions)?)
+    }
+
+    /// Addresses of every pool created through the factory
+    /// Uses pool::pools view function
+    pub async fn fetch_pool_addresses(&self) -> Result<Vec<String>> {
+        let mut response = self.view("pool::pools", vec![]).await?;
+
+        let addresses = response
+            .get_mut(0)
+            .map(Value::take)
+            .ok_or_else(|| anyhow::anyhow!("Empty response from pool::pools"))?;
+
+        Ok(serde_json::from_value(addresses)?)
+    }
+
+    /// Fetch pool resource directly from chain
+    /// Resource type: pool::Pool
+    pub async fn fetch_pool(&self, pool_id: &str) -> Result<ThalaPool> {
+        let resource_type = format!("{}::pool::Pool", self.protocol_address);
+        let resource: PoolResource = self.fetch_resource(pool_id, &resource_type).await?;
+
+        ThalaPool::from_resource(pool_id, resource)
     }
 
-    // TODO: Implement pool fetching
-    // Research needed:
-    // 1. Does Thala expose pool data on-chain or via API?
-    // 2. What's the data structure?
-    // 3. How to fetch all pools vs single pool?
     pub async fn fetch_pools(&self) -> Result<Vec<ThalaPool>> {
-        anyhow::bail!("Thala pool fetching not yet implemented - research needed")
+        let addresses = self.fetch_pool_addresses().await?;
+
+        stream::iter(addresses)
+            .map(|address| async move { self.fetch_pool(&address).await })
+            .buffered(CONCURRENT_REQUESTS)
+            .try_collect()
+            .await
+    }
+
+    /// Fetch fungible asset metadata for a token
+    pub async fn fetch_token(&self, address: &str) -> Result<ThalaToken> {
+        let metadata: FungibleAssetMetadata =
+            self.fetch_resource(address, FUNGIBLE_ASSET_METADATA).await?;
+
+        Ok(ThalaToken::from_metadata(address, metadata))
     }
 
-    // TODO: If Thala uses concentrated liquidity, implement tick conversion
-    // pub async fn get_current_tick_index(&self, pool_id: &str) -> Result<i64> {
-    //     anyhow::bail!("Not yet implemented")
-    // }
+    /// Metadata of the given tokens, each fetched once
+    pub async fn fetch_tokens(&self, mut addresses: Vec<String>) -> Result<Vec<ThalaToken>> {
+        addresses.sort_unstable();
+        addresses.dedup();
+
+        stream::iter(addresses)
+            .map(|address| async move { self.fetch_token(&address).await })
+            .buffered(CONCURRENT_REQUESTS)
+            .try_collect()
+            .await
+    }
+
+    /// Get current tick from pool
+    pub async fn get_current_tick_index(&self, pool_id: &str) -> Result<i32> {
+        let pool = self.fetch_pool(pool_id).await?;
+        Ok(pool.current_tick)
+    }
 }
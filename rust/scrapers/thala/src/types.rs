@@ -1,39 +1,260 @@
 use aptos_rust_sdk::client::config::AptosNetwork;
+use db::entities::{pools, positions, tokens};
+use rust_decimal::Decimal;
+use scraper_common::fullnode::with_fullnode_url;
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    sqlx::types::chrono::Utc,
+};
 use serde::{Deserialize, Serialize};
 
-// TODO: Research Thala's position data structure
-// - Check if Thala uses CLMM like TAPP
-// - Identify tick structure (if applicable)
-// - Determine liquidity representation
+/// Value stored in the `dex` column for Thala pools
+pub const DEX: &str = "thala";
+
+/// Move `i32` as serialized by Thala's CLMM: the two's complement bits in a u32
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct I32 {
+    pub bits: u32,
+}
+
+impl I32 {
+    pub fn value(&self) -> i32 {
+        self.bits as i32
+    }
+}
+
+/// `Object<Metadata>` reference
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectRef {
+    pub inner: String,
+}
+
+/// Position as returned by the `pool::positions` view function
+///
+/// Its fields are checked against the published module by [`PoolModuleAbi::check`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub index: String,
     pub liquidity: String,
-    // TODO: Add tick ranges if Thala uses concentrated liquidity
-    // pub tick_lower: Option<TickIndex>,
-    // pub tick_upper: Option<TickIndex>,
+    pub tick_lower: I32,
+    pub tick_upper: I32,
+    pub fee_growth_inside_a_last: String,
+    pub fee_growth_inside_b_last: String,
+    pub fee_owed_a: String,
+    pub fee_owed_b: String,
+}
+
+impl Position {
+    pub fn to_active_model(self, pool_id: &str) -> anyhow::Result<positions::ActiveModel> {
+        Ok(positions::ActiveModel {
+            pool: Set(pool_id.to_string()),
+            index: Set(self.index.parse::<i32>()?),
+            tick_lower: Set(self.tick_lower.value().into()),
+            tick_upper: Set(self.tick_upper.value().into()),
+            liquidity: Set(self.liquidity),
+            updated_at: Set(Some(Utc::now().naive_utc())),
+            address: Set(None),
+        })
+    }
+}
+
+/// Fields of [`Position`], in declaration order
+const POSITION_FIELDS: &[&str] = &[
+    "index",
+    "liquidity",
+    "tick_lower",
+    "tick_upper",
+    "fee_growth_inside_a_last",
+    "fee_growth_inside_b_last",
+    "fee_owed_a",
+    "fee_owed_b",
+];
+
+/// `pool::Pool` resource stored at the pool object address
+///
+/// Its fields are checked against the published module by [`PoolModuleAbi::check`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolResource {
+    pub token_a: ObjectRef,
+    pub token_b: ObjectRef,
+    /// Swap fee in hundredths of a basis point (3000 = 0.3%)
+    pub fee_rate: String,
+    pub tick_spacing: u32,
+    pub current_tick: I32,
+    pub sqrt_price: String,
+    pub liquidity: String,
+}
+
+/// Fields of [`PoolResource`] the scraper reads
+const POOL_FIELDS: &[&str] = &[
+    "token_a",
+    "token_b",
+    "fee_rate",
+    "tick_spacing",
+    "current_tick",
+    "sqrt_price",
+    "liquidity",
+];
+
+/// View functions of the `pool` module the scraper calls
+const POOL_VIEWS: &[&str] = &["pools", "positions"];
+
+/// ABI of a published module, as returned by the fullnode's `/accounts/{address}/module/{name}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct MoveModule {
+    pub abi: PoolModuleAbi,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolModuleAbi {
+    pub name: String,
+    pub exposed_functions: Vec<MoveFunction>,
+    pub structs: Vec<MoveStruct>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MoveFunction {
+    pub name: String,
+    pub is_view: bool,
+    #[serde(rename = "return")]
+    pub returns: Vec<String>,
 }
 
-// TODO: Research Thala's pool data structure
-// - API endpoint or on-chain resource type
-// - Fee structure
-// - APR calculation method
+#[derive(Debug, Clone, Deserialize)]
+pub struct MoveStruct {
+    pub name: String,
+    pub fields: Vec<MoveField>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MoveField {
+    pub name: String,
+}
+
+impl PoolModuleAbi {
+    /// Check the module publishes what the scraper reads: the `pools` and `positions` views,
+    /// a `Pool` struct and the struct `positions` returns, with the fields deserialized into
+    /// [`PoolResource`] and [`Position`]. Run once at startup so a wrong
+    /// `THALA_PROTOCOL_ADDRESS`, or an upgrade renaming a field, fails loudly instead of
+    /// surfacing as missing resources.
+    pub fn check(&self) -> anyhow::Result<()> {
+        if self.name != "pool" {
+            anyhow::bail!("Expected the pool module, got {}", self.name);
+        }
+        for view in POOL_VIEWS {
+            let function = self.function(view)?;
+            if !function.is_view {
+                anyhow::bail!("pool::{} is not a view function", view);
+            }
+        }
+
+        self.check_fields("Pool", POOL_FIELDS)?;
+
+        let returned = self.function("positions")?.returns.first().cloned();
+        let position = returned
+            .as_deref()
+            .and_then(|ty| ty.strip_prefix("vector<")?.strip_suffix('>'))
+            .and_then(|ty| ty.split_once("::pool::"))
+            .map(|(_, name)| name)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "pool::positions returns {:?}, not a vector of a pool struct",
+                    returned
+                )
+            })?;
+        self.check_fields(position, POSITION_FIELDS)
+    }
+
+    fn function(&self, name: &str) -> anyhow::Result<&MoveFunction> {
+        self.exposed_functions
+            .iter()
+            .find(|f| f.name == name)
+            .ok_or_else(|| anyhow::anyhow!("pool::{} is not exposed", name))
+    }
+
+    fn check_fields(&self, name: &str, expected: &[&str]) -> anyhow::Result<()> {
+        let declared = self
+            .structs
+            .iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| anyhow::anyhow!("pool::{} is not declared", name))?;
+
+        let missing: Vec<&str> = expected
+            .iter()
+            .copied()
+            .filter(|field| !declared.fields.iter().any(|f| f.name == *field))
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!("pool::{} has no field {}", name, missing.join(", "));
+        }
+        Ok(())
+    }
+}
+
+/// Thala CLMM pool, read from chain
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
 pub struct ThalaPool {
     pub pool_id: String,
-    pub fee: String,
-    pub tvl: String,
-    pub volume_24h: Option<String>,
-    pub apr: Option<f64>,
-    // TODO: Add token addresses
-    // pub token_a: String,
-    // pub token_b: String,
-}
-
-// TODO: Research Thala's token list format
-// - Check if they have an HTTP API like TAPP
-// - Or if token data comes from on-chain
+    pub token_a: String,
+    pub token_b: String,
+    pub fee_rate: u64,
+    pub tick_spacing: u32,
+    pub current_tick: i32,
+    pub sqrt_price: String,
+    pub liquidity: String,
+}
+
+impl ThalaPool {
+    pub fn from_resource(pool_id: &str, resource: PoolResource) -> anyhow::Result<Self> {
+        Ok(Self {
+            pool_id: pool_id.to_string(),
+            token_a: resource.token_a.inner,
+            token_b: resource.token_b.inner,
+            fee_rate: resource.fee_rate.parse()?,
+            tick_spacing: resource.tick_spacing,
+            current_tick: resource.current_tick.value(),
+            sqrt_price: resource.sqrt_price,
+            liquidity: resource.liquidity,
+        })
+    }
+
+    /// Fee as a percentage, the unit used by the `pools.fee` column
+    pub fn fee_percentage(&self) -> Decimal {
+        Decimal::new(self.fee_rate as i64, 4)
+    }
+
+    /// Market data (TVL, volume, APR) isn't available on-chain and is left unset, so the
+    /// columns keep their defaults and no `pool_snapshots` row is recorded for the pool.
+    pub fn to_active_model(self) -> pools::ActiveModel {
+        pools::ActiveModel {
+            position_index: Set(None),
+            fee: Set(self.fee_percentage()),
+            id: Set(self.pool_id),
+            dex: Set(DEX.to_string()),
+            trading_apr: NotSet,
+            bonus_apr: NotSet,
+            tvl: NotSet,
+            volume_day: NotSet,
+            volume_week: NotSet,
+            volume_month: NotSet,
+            volume_prev_day: NotSet,
+            token_a: Set(Some(self.token_a)),
+            token_b: Set(Some(self.token_b)),
+            updated_at: Set(Some(Utc::now().naive_utc())),
+        }
+    }
+}
+
+/// `0x1::fungible_asset::Metadata` resource
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FungibleAssetMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub icon_uri: String,
+    pub project_uri: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThalaToken {
     pub address: String,
@@ -43,6 +264,30 @@ pub struct ThalaToken {
     pub logo: Option<String>,
 }
 
+impl ThalaToken {
+    pub fn from_metadata(address: &str, metadata: FungibleAssetMetadata) -> Self {
+        Self {
+            address: address.to_string(),
+            symbol: metadata.symbol,
+            decimals: metadata.decimals,
+            name: Some(metadata.name).filter(|n| !n.is_empty()),
+            logo: Some(metadata.icon_uri).filter(|uri| !uri.is_empty()),
+        }
+    }
+
+    pub fn to_active_model(&self) -> tokens::ActiveModel {
+        tokens::ActiveModel {
+            id: Set(self.address.clone()),
+            symbol: Set(self.symbol.clone()),
+            name: Set(self.name.clone()),
+            about: Set(None),
+            logo: Set(self.logo.clone()),
+            decimals: Set(self.decimals.into()),
+            updated_at: Set(Some(Utc::now().naive_utc())),
+        }
+    }
+}
+
 // Network configuration
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Network {
@@ -50,9 +295,199 @@ pub enum Network {
 }
 
 impl Network {
-    pub fn to_aptos_network(&self) -> AptosNetwork {
-        match self {
+    /// SDK settings of the network, reading the chain from `fullnode_url`
+    pub fn to_aptos_network(&self, fullnode_url: &str) -> anyhow::Result<AptosNetwork> {
+        let network = match self {
             Network::Mainnet => AptosNetwork::mainnet(),
+        };
+        with_fullnode_url(network, fullnode_url)
+    }
+}
+
+impl TryFrom<config::Network> for Network {
+    type Error = anyhow::Error;
+
+    fn try_from(network: config::Network) -> anyhow::Result<Self> {
+        match network {
+            config::Network::Mainnet => Ok(Network::Mainnet),
+            config::Network::Testnet => anyhow::bail!("Thala has no {network} deployment"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Shaped after the fields `PoolModuleAbi::check` requires, which the scraper verifies
+    // against the published module at startup
+    const POOL_RESOURCE: &str = r#"{
+        "token_a": { "inner": "0xa" },
+        "token_b": { "inner": "0xbae207659db88bea0cbead6da0ed00aac12edcdda169e591cd41c94180b46f3b" },
+        "fee_rate": "3000",
+        "tick_spacing": 60,
+        "current_tick": { "bits": 4294967236 },
+        "sqrt_price": "18446744073709551616",
+        "liquidity": "987654321"
+    }"#;
+
+    const POSITIONS_VIEW: &str = r#"[[{
+        "index": "7",
+        "liquidity": "1000",
+        "tick_lower": { "bits": 4294966096 },
+        "tick_upper": { "bits": 1200 },
+        "fee_growth_inside_a_last": "0",
+        "fee_growth_inside_b_last": "0",
+        "fee_owed_a": "0",
+        "fee_owed_b": "0"
+    }]]"#;
+
+    const POOL_MODULE: &str = r#"{
+        "bytecode": "0x",
+        "abi": {
+            "address": "0xthala",
+            "name": "pool",
+            "friends": [],
+            "exposed_functions": [
+                {
+                    "name": "pools", "visibility": "public", "is_entry": false, "is_view": true,
+                    "generic_type_params": [], "params": [], "return": ["vector<address>"]
+                },
+                {
+                    "name": "positions", "visibility": "public", "is_entry": false,
+                    "is_view": true, "generic_type_params": [], "params": ["address"],
+                    "return": ["vector<0xthala::pool::PositionInfo>"]
+                }
+            ],
+            "structs": [
+                {
+                    "name": "Pool", "is_native": false, "abilities": ["key"],
+                    "generic_type_params": [],
+                    "fields": [
+                        { "name": "token_a", "type": "0x1::object::Object<0x1::fungible_asset::Metadata>" },
+                        { "name": "token_b", "type": "0x1::object::Object<0x1::fungible_asset::Metadata>" },
+                        { "name": "fee_rate", "type": "u64" },
+                        { "name": "tick_spacing", "type": "u32" },
+                        { "name": "current_tick", "type": "0xthala::i32::I32" },
+                        { "name": "sqrt_price", "type": "u128" },
+                        { "name": "liquidity", "type": "u128" },
+                        { "name": "position_index", "type": "u64" }
+                    ]
+                },
+                {
+                    "name": "PositionInfo", "is_native": false, "abilities": ["copy", "drop"],
+                    "generic_type_params": [],
+                    "fields": [
+                        { "name": "index", "type": "u64" },
+                        { "name": "liquidity", "type": "u128" },
+                        { "name": "tick_lower", "type": "0xthala::i32::I32" },
+                        { "name": "tick_upper", "type": "0xthala::i32::I32" },
+                        { "name": "fee_growth_inside_a_last", "type": "u128" },
+                        { "name": "fee_growth_inside_b_last", "type": "u128" },
+                        { "name": "fee_owed_a", "type": "u64" },
+                        { "name": "fee_owed_b", "type": "u64" }
+                    ]
+                }
+            ]
+        }
+    }"#;
+
+    fn pool_module() -> PoolModuleAbi {
+        serde_json::from_str::<MoveModule>(POOL_MODULE).unwrap().abi
+    }
+
+    #[test]
+    fn the_expected_pool_module_passes_the_check() {
+        pool_module().check().unwrap();
+    }
+
+    #[test]
+    fn a_renamed_pool_field_fails_the_check() {
+        let mut abi = pool_module();
+        abi.structs[0].fields[6].name = "active_liquidity".to_string();
+
+        let err = abi.check().unwrap_err();
+
+        assert_eq!(err.to_string(), "pool::Pool has no field liquidity");
+    }
+
+    #[test]
+    fn a_missing_position_field_fails_the_check() {
+        let mut abi = pool_module();
+        abi.structs[1].fields.retain(|f| f.name != "fee_owed_b");
+
+        let err = abi.check().unwrap_err();
+
+        assert_eq!(err.to_string(), "pool::PositionInfo has no field fee_owed_b");
+    }
+
+    #[test]
+    fn a_view_turned_entry_fails_the_check() {
+        let mut abi = pool_module();
+        abi.exposed_functions[0].is_view = false;
+
+        assert!(abi.check().is_err());
+
+        abi.exposed_functions.remove(0);
+        let err = abi.check().unwrap_err();
+        assert_eq!(err.to_string(), "pool::pools is not exposed");
+    }
+
+    #[test]
+    fn i32_bits_are_twos_complement() {
+        assert_eq!(I32 { bits: 0 }.value(), 0);
+        assert_eq!(I32 { bits: 60 }.value(), 60);
+        assert_eq!(I32 { bits: u32::MAX }.value(), -1);
+        assert_eq!(I32 { bits: 4294967236 }.value(), -60);
+        assert_eq!(I32 { bits: 1 << 31 }.value(), i32::MIN);
+    }
+
+    #[test]
+    fn pool_resource_maps_to_a_pool_without_market_data() {
+        let resource: PoolResource = serde_json::from_str(POOL_RESOURCE).unwrap();
+        let pool = ThalaPool::from_resource("0xpool", resource).unwrap();
+
+        assert_eq!(pool.current_tick, -60);
+        assert_eq!(pool.tick_spacing, 60);
+        assert_eq!(pool.fee_percentage(), Decimal::new(3, 1));
+
+        let model = pool.to_active_model();
+        assert_eq!(model.fee.unwrap(), Decimal::new(3, 1));
+        assert!(model.tvl.is_not_set());
+        assert!(model.volume_day.is_not_set());
+        assert!(model.trading_apr.is_not_set());
+    }
+
+    #[test]
+    fn fee_percentage_keeps_sub_basis_point_rates() {
+        let pool = |fee_rate| ThalaPool {
+            pool_id: "0xpool".to_string(),
+            token_a: "0xa".to_string(),
+            token_b: "0xb".to_string(),
+            fee_rate,
+            tick_spacing: 1,
+            current_tick: 0,
+            sqrt_price: "0".to_string(),
+            liquidity: "0".to_string(),
+        };
+
+        assert_eq!(pool(100).fee_percentage().to_string(), "0.0100");
+        assert_eq!(pool(500).fee_percentage().to_string(), "0.0500");
+        assert_eq!(pool(10_000).fee_percentage().to_string(), "1.0000");
+        assert_eq!(pool(1).fee_percentage().to_string(), "0.0001");
+    }
+
+    #[test]
+    fn positions_view_keeps_negative_ticks() {
+        let mut response: Vec<Vec<Position>> = serde_json::from_str(POSITIONS_VIEW).unwrap();
+        let model = response
+            .remove(0)
+            .remove(0)
+            .to_active_model("0xpool")
+            .unwrap();
+
+        assert_eq!(model.index.unwrap(), 7);
+        assert_eq!(model.tick_lower.unwrap(), -1200);
+        assert_eq!(model.tick_upper.unwrap(), 1200);
+    }
+}
@@ -0,0 +1,24 @@
+#!/bin/bash
+# Save the entry function payload and events of TAPP router transactions, as returned by a
+# fullnode, to rust/scrapers/tapp/fixtures/<function>-<hash>.json for the transaction builder
+# tests.
+#
+# Usage: scripts/capture-tapp-payloads.sh <transaction hash>...
+
+set -euo pipefail
+
+if [ $# -eq 0 ]; then
+    echo "Usage: $0 <transaction hash>..."
+    exit 1
+fi
+
+FULLNODE_URL="${APTOS_FULLNODE_URL:-https://api.mainnet.aptoslabs.com/v1}"
+OUTPUT_DIR="$(dirname "$0")/../rust/scrapers/tapp/fixtures"
+mkdir -p "$OUTPUT_DIR"
+
+for hash in "$@"; do
+    transaction=$(curl -sf "$FULLNODE_URL/transactions/by_hash/$hash" | jq -e '{payload, events}')
+    function=$(echo "$transaction" | jq -r '.payload.function | split("::") | last')
+    echo "$transaction" > "$OUTPUT_DIR/$function-$hash.json"
+    echo "Saved $function-$hash.json"
+done